@@ -0,0 +1,72 @@
+// Renders a vertex colored cube through the 3D pipeline
+//
+// Each face is made of two triangles wound counter-clockwise when seen from
+// outside of the cube, so the back faces get culled
+
+use image::{save_buffer, ColorType};
+use olivec::{rgb, Camera, Canvas, Drawable, Mat4, Pipeline, Vec3, Vertex};
+
+const CANVAS_WIDTH: usize = 800;
+const CANVAS_HEIGHT: usize = 600;
+
+fn main() {
+    let mut canvas = Canvas::new(CANVAS_WIDTH, CANVAS_HEIGHT);
+    canvas.fill(rgb!(0x18, 0x18, 0x18));
+
+    let camera = Camera::perspective(60f32.to_radians(), 0.1, 100.0)
+        .look_at(Vec3::new(0.0, 1.5, 4.0), Vec3::ZERO);
+
+    let pipeline = Pipeline::new(camera).with_model(Mat4::rotation_y(0.6) * Mat4::rotation_x(0.3));
+
+    let corner = |x: f32, y: f32, z: f32| {
+        let color = rgb!(
+            ((x + 1.0) * 127.5) as u32,
+            ((y + 1.0) * 127.5) as u32,
+            ((z + 1.0) * 127.5) as u32
+        );
+
+        Vertex::new(Vec3::new(x, y, z)).with_color(color)
+    };
+
+    // Faces as quads of corners in counter-clockwise order
+    let faces = [
+        [(-1., -1., 1.), (1., -1., 1.), (1., 1., 1.), (-1., 1., 1.)],
+        [
+            (1., -1., -1.),
+            (-1., -1., -1.),
+            (-1., 1., -1.),
+            (1., 1., -1.),
+        ],
+        [(1., -1., 1.), (1., -1., -1.), (1., 1., -1.), (1., 1., 1.)],
+        [
+            (-1., -1., -1.),
+            (-1., -1., 1.),
+            (-1., 1., 1.),
+            (-1., 1., -1.),
+        ],
+        [(-1., 1., 1.), (1., 1., 1.), (1., 1., -1.), (-1., 1., -1.)],
+        [
+            (-1., -1., -1.),
+            (1., -1., -1.),
+            (1., -1., 1.),
+            (-1., -1., 1.),
+        ],
+    ];
+
+    for face in faces {
+        let [a, b, c, d] = face.map(|(x, y, z)| corner(x, y, z));
+
+        pipeline.draw_triangles(&mut canvas, &[[a, b, c], [a, c, d]]);
+    }
+
+    /* Save image buffer to disk */
+
+    save_buffer(
+        "cube.png",
+        canvas.get_data(),
+        canvas.get_width() as u32,
+        canvas.get_height() as u32,
+        ColorType::Rgba8,
+    )
+    .expect("could not save image");
+}
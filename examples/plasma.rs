@@ -0,0 +1,33 @@
+// Renders a procedural plasma background with `Drawable::shade`
+//
+// Run with `--features rayon` to shade the rows in parallel
+
+use image::{save_buffer, ColorType};
+use olivec::{rgb, Canvas, Drawable};
+
+const CANVAS_WIDTH: usize = 800;
+const CANVAS_HEIGHT: usize = 600;
+
+fn main() {
+    let mut canvas = Canvas::new(CANVAS_WIDTH, CANVAS_HEIGHT);
+
+    canvas.shade(|x, y| {
+        let (x, y) = (x as f32 / 40.0, y as f32 / 40.0);
+        let v = (x.sin() + (y * 0.7).sin() + ((x + y) * 0.5).sin() + (x.hypot(y)).sin()) / 4.0;
+
+        let channel = |phase: f32| (((v * 3.0 + phase).sin() * 0.5 + 0.5) * 255.0) as u32;
+
+        rgb!(channel(0.0), channel(2.0), channel(4.0))
+    });
+
+    /* Save image buffer to disk */
+
+    save_buffer(
+        "plasma.png",
+        canvas.get_data(),
+        canvas.get_width() as u32,
+        canvas.get_height() as u32,
+        ColorType::Rgba8,
+    )
+    .expect("could not save image");
+}
@@ -0,0 +1,58 @@
+// Compares drawing thousands of labels with `Drawable::text` and with a `GlyphCache`
+//
+// Run with `--release` for meaningful timings
+
+use std::time::{Duration, Instant};
+
+use olivec::{Canvas, Drawable, FixedFont, GlyphCache};
+
+const CANVAS_WIDTH: usize = 1920;
+const CANVAS_HEIGHT: usize = 1080;
+const LABELS: usize = 5000;
+const FRAMES: usize = 20;
+
+fn label(i: usize) -> String {
+    format!("unit-{:04} hp {:3}%", i, (i * 37) % 101)
+}
+
+fn bench(name: &str, mut draw_frame: impl FnMut(&mut Canvas)) -> Duration {
+    let mut canvas = Canvas::new(CANVAS_WIDTH, CANVAS_HEIGHT);
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        canvas.fill(0xFF181818);
+        draw_frame(&mut canvas);
+    }
+    let elapsed = start.elapsed() / FRAMES as u32;
+
+    println!("{name:>12}: {elapsed:?} per frame of {LABELS} labels");
+    elapsed
+}
+
+fn main() {
+    let font = FixedFont::<6, 6>::default();
+    let labels: Vec<String> = (0..LABELS).map(label).collect();
+    let position = |i: usize| ((i % 16) as i32 * 120, (i / 16) as i32 * 3);
+
+    let direct = bench("text", |canvas| {
+        for (i, label) in labels.iter().enumerate() {
+            let (x, y) = position(i);
+            canvas.text(label.as_str(), x, y, &font, 1, 0xFFFFFFFF);
+        }
+    });
+
+    let mut cache = GlyphCache::new();
+    let cached = bench("GlyphCache", |canvas| {
+        for (i, label) in labels.iter().enumerate() {
+            let (x, y) = position(i);
+            cache.text(canvas, label, x, y, &font, 1, 0xFFFFFFFF);
+        }
+    });
+
+    println!(
+        "{:>12}: {:.1}x with {} cached glyphs",
+        "speedup",
+        direct.as_secs_f64() / cached.as_secs_f64(),
+        cache.len()
+    );
+}
@@ -0,0 +1,344 @@
+use std::{fmt::Display, path::Path};
+
+use crate::{DynFont, FontError, MAX_FONT_CELL_SIZE};
+
+/// A glyph as described by a `STARTCHAR` block
+struct BdfChar {
+    encoding: i64,
+    /// `DWIDTH` x component, the advance width of the glyph
+    advance: Option<i32>,
+    /// `BBX` width, height, x offset and y offset
+    bbx: [i32; 4],
+    rows: Vec<Vec<u8>>,
+}
+
+fn parse_error(line: usize, message: impl Display) -> FontError {
+    FontError::Parse(format!("line {line}: {message}"))
+}
+
+/// Parses the whitespace separated integers following a keyword
+fn parse_ints<const N: usize>(
+    line_no: usize,
+    keyword: &str,
+    tokens: std::str::SplitWhitespace,
+) -> Result<[i32; N], FontError> {
+    let values: Vec<i32> = tokens
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| parse_error(line_no, format!("invalid {keyword}")))?;
+
+    values
+        .get(..N)
+        .and_then(|values| values.try_into().ok())
+        .ok_or_else(|| parse_error(line_no, format!("expected {N} values after {keyword}")))
+}
+
+impl DynFont {
+    /// Parses a font in the Glyph Bitmap Distribution Format
+    ///
+    /// Every glyph is placed in a cell the size of the font bounding box, aligned on
+    /// the baseline given by the `FONT_ASCENT` property, and advances by its `DWIDTH`
+    /// so proportional fonts keep their spacing. Encodings are treated as Unicode
+    /// code points, and the `DEFAULT_CHAR` property becomes the fallback character
+    ///
+    /// Fonts whose glyph cells are wider or taller than 512 pixels are rejected
+    ///
+    /// ```
+    /// use olivec::DynFont;
+    ///
+    /// let font = DynFont::parse_bdf("\
+    /// STARTFONT 2.1
+    /// FONT -misc-tiny-medium-r-normal--4-40-75-75-c-40-iso10646-1
+    /// SIZE 4 75 75
+    /// FONTBOUNDINGBOX 4 4 0 -1
+    /// STARTPROPERTIES 2
+    /// FONT_ASCENT 3
+    /// FONT_DESCENT 1
+    /// ENDPROPERTIES
+    /// CHARS 1
+    /// STARTCHAR uni0416
+    /// ENCODING 1046
+    /// DWIDTH 4 0
+    /// BBX 3 3 0 0
+    /// BITMAP
+    /// A0
+    /// 40
+    /// A0
+    /// ENDCHAR
+    /// ENDFONT
+    /// ").unwrap();
+    ///
+    /// assert_eq!((font.get_width(), font.get_height()), (4, 4));
+    /// assert!(font.contains('Ж'));
+    /// assert_eq!(font.advance_of('Ж'), Some(4));
+    /// ```
+    pub fn parse_bdf(source: &str) -> Result<DynFont, FontError> {
+        let mut bounding_box = None;
+        let mut ascent = None;
+        let mut descent = None;
+        let mut default_char = None;
+        let mut chars = Vec::new();
+
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+        while let Some((line_no, line)) = lines.next() {
+            let mut tokens = line.split_whitespace();
+
+            match tokens.next() {
+                Some("FONTBOUNDINGBOX") => {
+                    bounding_box = Some(parse_ints::<4>(line_no, "FONTBOUNDINGBOX", tokens)?)
+                }
+                Some("FONT_ASCENT") => {
+                    ascent = Some(parse_ints::<1>(line_no, "FONT_ASCENT", tokens)?[0])
+                }
+                Some("FONT_DESCENT") => {
+                    descent = Some(parse_ints::<1>(line_no, "FONT_DESCENT", tokens)?[0])
+                }
+                Some("DEFAULT_CHAR") => {
+                    default_char = Some(parse_ints::<1>(line_no, "DEFAULT_CHAR", tokens)?[0])
+                }
+                Some("STARTCHAR") => {
+                    let mut glyph = BdfChar {
+                        encoding: -1,
+                        advance: None,
+                        bbx: [0; 4],
+                        rows: Vec::new(),
+                    };
+
+                    loop {
+                        let Some((line_no, line)) = lines.next() else {
+                            return Err(parse_error(line_no, "unterminated STARTCHAR"));
+                        };
+
+                        let mut tokens = line.split_whitespace();
+
+                        match tokens.next() {
+                            Some("ENCODING") => {
+                                glyph.encoding =
+                                    parse_ints::<1>(line_no, "ENCODING", tokens)?[0] as i64
+                            }
+                            Some("DWIDTH") => {
+                                glyph.advance = Some(parse_ints::<1>(line_no, "DWIDTH", tokens)?[0])
+                            }
+                            Some("BBX") => glyph.bbx = parse_ints::<4>(line_no, "BBX", tokens)?,
+                            Some("BITMAP") => {
+                                for _ in 0..glyph.bbx[1].max(0) {
+                                    let Some((line_no, row)) = lines.next() else {
+                                        return Err(parse_error(line_no, "unterminated BITMAP"));
+                                    };
+
+                                    glyph.rows.push(parse_hex_row(line_no, row.trim())?);
+                                }
+                            }
+                            Some("ENDCHAR") => break,
+                            _ => {}
+                        }
+                    }
+
+                    chars.push(glyph);
+                }
+                _ => {}
+            }
+        }
+
+        let [fbb_w, fbb_h, fbb_x, fbb_y] =
+            bounding_box.ok_or_else(|| FontError::Parse("missing FONTBOUNDINGBOX".into()))?;
+
+        let out_of_range = || FontError::Parse("font bounding box out of range".into());
+
+        let ascent = match ascent {
+            Some(ascent) => ascent,
+            None => fbb_h.checked_add(fbb_y).ok_or_else(out_of_range)?,
+        }
+        .max(0);
+        let descent = match descent {
+            Some(descent) => descent,
+            None => fbb_y.checked_neg().ok_or_else(out_of_range)?,
+        }
+        .max(0);
+
+        let width = fbb_w.max(0) as usize;
+        let height = ascent.checked_add(descent).ok_or_else(out_of_range)? as usize;
+
+        if width > MAX_FONT_CELL_SIZE || height > MAX_FONT_CELL_SIZE {
+            return Err(FontError::Parse(format!(
+                "glyphs of {width}x{height} pixels are larger than {MAX_FONT_CELL_SIZE} pixels"
+            )));
+        }
+
+        let mut font = DynFont::new(width, height).with_ascent(ascent as usize);
+
+        for glyph in chars {
+            let Some(c) = u32::try_from(glyph.encoding).ok().and_then(char::from_u32) else {
+                continue;
+            };
+
+            let [w, h, x_offset, y_offset] = glyph.bbx.map(i64::from);
+            let mut pixels = vec![0u8; width * height];
+
+            // Position of the top left corner of the glyph box within the cell, which
+            // can't overflow as 64 bit sums of 32 bit values
+            let left = x_offset - fbb_x as i64;
+            let top = ascent as i64 - (y_offset + h);
+
+            for (gy, row) in glyph.rows.iter().enumerate() {
+                // Bits past the end of the row are empty
+                for gx in 0..w.min(row.len() as i64 * 8) {
+                    let bit = row[gx as usize / 8] & (0x80 >> (gx % 8)) != 0;
+                    let (x, y) = (left + gx, top + gy as i64);
+
+                    if bit && (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+                        pixels[y as usize * width + x as usize] = 1;
+                    }
+                }
+            }
+
+            let advance = glyph.advance.map_or(width, |advance| {
+                advance.clamp(0, MAX_FONT_CELL_SIZE as i32) as usize
+            });
+            font.insert_with_advance(c, &pixels, advance);
+        }
+
+        if let Some(c) = default_char
+            .and_then(|code| u32::try_from(code).ok())
+            .and_then(char::from_u32)
+        {
+            font = font.with_fallback(c);
+        }
+
+        Ok(font)
+    }
+
+    /// Loads a BDF font from disk, see [`DynFont::parse_bdf`]
+    pub fn load_bdf(path: impl AsRef<Path>) -> Result<DynFont, FontError> {
+        Self::parse_bdf(&std::fs::read_to_string(path)?)
+    }
+}
+
+fn parse_hex_row(line_no: usize, row: &str) -> Result<Vec<u8>, FontError> {
+    if row.len() % 2 == 1 || !row.is_ascii() {
+        return Err(parse_error(line_no, format!("invalid bitmap row `{row}`")));
+    }
+
+    (0..row.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&row[i..i + 2], 16)
+                .map_err(|_| parse_error(line_no, format!("invalid bitmap row `{row}`")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A font with a 4 by 4 cell and a baseline 3 pixels from the top, followed by
+    /// the provided properties and glyphs
+    fn bdf(properties: &str, glyphs: &str) -> String {
+        format!(
+            "STARTFONT 2.1\nFONTBOUNDINGBOX 4 4 0 -1\n{properties}\nCHARS 1\n{glyphs}\nENDFONT\n"
+        )
+    }
+
+    const PROPERTIES: &str = "STARTPROPERTIES 2\nFONT_ASCENT 3\nFONT_DESCENT 1\nENDPROPERTIES";
+
+    fn glyph(encoding: i64, bbx: &str, rows: &str) -> String {
+        format!("STARTCHAR g\nENCODING {encoding}\nDWIDTH 3 0\nBBX {bbx}\nBITMAP\n{rows}\nENDCHAR")
+    }
+
+    #[test]
+    fn glyphs_are_placed_on_the_baseline() {
+        let source = [
+            glyph('x' as i64, "3 3 0 0", "A0\n40\nA0"),
+            glyph('.' as i64, "1 1 1 -1", "80"),
+        ]
+        .join("\n");
+        let font = DynFont::parse_bdf(&bdf(PROPERTIES, &source)).unwrap();
+
+        #[rustfmt::skip]
+        assert_eq!(font.bitmap('x'), Some(&[
+            255, 0, 255, 0,
+            0, 255, 0, 0,
+            255, 0, 255, 0,
+            0, 0, 0, 0,
+        ][..]));
+        assert_eq!(font.bitmap('.').unwrap()[12..], [0, 255, 0, 0]);
+        assert_eq!(font.advance_of('x'), Some(3));
+        assert_eq!(crate::Font::ascent(&font, 2), 6);
+    }
+
+    #[test]
+    fn metrics_default_to_the_bounding_box() {
+        let font = DynFont::parse_bdf(&bdf("", &glyph(65, "4 4 0 -1", "F0\nF0\nF0\nF0"))).unwrap();
+
+        assert_eq!((font.get_width(), font.get_height()), (4, 4));
+        assert!(font.bitmap('A').unwrap().iter().all(|&p| p == 255));
+    }
+
+    #[test]
+    fn default_char_becomes_the_fallback() {
+        let properties = "STARTPROPERTIES 1\nDEFAULT_CHAR 63\nENDPROPERTIES";
+        let font = DynFont::parse_bdf(&bdf(properties, &glyph(63, "1 1 0 0", "80"))).unwrap();
+
+        assert_eq!(font.fallback(), Some('?'));
+        assert_eq!(font.bitmap_or_fallback('é'), font.bitmap('?'));
+    }
+
+    #[test]
+    fn unencoded_glyphs_are_skipped() {
+        let font = DynFont::parse_bdf(&bdf(PROPERTIES, &glyph(-1, "1 1 0 0", "80"))).unwrap();
+
+        assert!(font.is_empty());
+    }
+
+    #[test]
+    fn malformed_fonts_are_parse_errors() {
+        let truncated_char = bdf(PROPERTIES, "STARTCHAR g\nENCODING 65\nBBX 1 1 0 0");
+        let truncated_bitmap = "FONTBOUNDINGBOX 4 4 0 0\nSTARTCHAR g\nBBX 1 3 0 0\nBITMAP\n80";
+
+        let invalid = [
+            "STARTFONT 2.1\nENDFONT".to_owned(),
+            truncated_char.clone(),
+            truncated_char.replace("BBX 1 1 0 0", "BBX 1 1 0 0\nBITMAP\nXY\nENDCHAR"),
+            truncated_bitmap.to_owned(),
+            bdf(PROPERTIES, &glyph(65, "1 1 0", "80")),
+            bdf(PROPERTIES, &glyph(65, "1 1 0 0", "800")),
+        ];
+
+        for source in invalid {
+            assert!(
+                matches!(DynFont::parse_bdf(&source), Err(FontError::Parse(_))),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn oversized_and_overflowing_metrics_are_rejected() {
+        let huge = bdf(PROPERTIES, "").replace("4 4 0 -1", "100000 100000 0 0");
+        let overflowing =
+            "STARTPROPERTIES 2\nFONT_ASCENT 2147483647\nFONT_DESCENT 1\nENDPROPERTIES";
+        let no_descent = "FONTBOUNDINGBOX 4 4 0 -2147483648\nFONT_ASCENT 3";
+
+        for source in [huge, bdf(overflowing, ""), no_descent.to_owned()] {
+            assert!(
+                matches!(DynFont::parse_bdf(&source), Err(FontError::Parse(_))),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn extreme_glyph_boxes_are_clipped() {
+        let source = [
+            glyph(65, "2147483647 1 -2147483648 2147483647", "FF"),
+            glyph(66, "2147483647 1 0 0", "FF"),
+        ]
+        .join("\n");
+        let font = DynFont::parse_bdf(&bdf(PROPERTIES, &source)).unwrap();
+
+        assert!(font.bitmap('A').unwrap().iter().all(|&p| p == 0));
+        assert_eq!(font.bitmap('B').unwrap()[8..12], [255; 4]);
+    }
+}
@@ -0,0 +1,307 @@
+// Renders a scene file to an image
+//
+// Build with `--features cli`. Run `olivec --help` for the list of options
+
+use std::{
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+    process::ExitCode,
+};
+
+use image::{DynamicImage, RgbaImage};
+use olivec::{
+    parse_hex_color, Canvas, Drawable, Scene, SceneAssets, MAX_SCENE_PIXELS, MAX_SCENE_SIZE,
+};
+
+const USAGE: &str = "\
+Usage: olivec <SCENE> [OPTIONS]
+
+Renders a JSON or binary scene file to a PNG, PPM or QOI image
+
+Options:
+  -o, --output <FILE>       Output image, the format is picked from the extension [default: <SCENE>.png]
+  -W, --width <PIXELS>      Stretch the scene horizontally to a width
+  -H, --height <PIXELS>     Stretch the scene vertically to a height
+  -b, --background <COLOR>  Override the background with a #RRGGBB or #RRGGBBAA color
+  -s, --scale <FACTOR>      Scale the canvas and every primitive by a factor [default: 1]
+  -a, --asset <ID=FILE>     Load the image used for an asset id, can be repeated.
+                            Unlisted ids are loaded as paths relative to the scene file,
+                            and must not be absolute or contain `..`
+  -h, --help                Print this message";
+
+struct Args {
+    scene: PathBuf,
+    output: Option<PathBuf>,
+    width: Option<usize>,
+    height: Option<usize>,
+    background: Option<u32>,
+    scale: f32,
+    assets: Vec<(String, PathBuf)>,
+}
+
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut args = std::env::args().skip(1);
+
+    let mut scene = None;
+    let mut parsed = Args {
+        scene: PathBuf::new(),
+        output: None,
+        width: None,
+        height: None,
+        background: None,
+        scale: 1.0,
+        assets: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for `{arg}`"));
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => parsed.output = Some(value()?.into()),
+            "-W" | "--width" => parsed.width = Some(parse_number(&value()?)?),
+            "-H" | "--height" => parsed.height = Some(parse_number(&value()?)?),
+            "-b" | "--background" => {
+                let color = value()?;
+                parsed.background =
+                    Some(parse_hex_color(&color).ok_or(format!("invalid color `{color}`"))?);
+            }
+            "-s" | "--scale" => {
+                let scale: f32 = parse_number(&value()?)?;
+
+                if !(scale > 0.0 && scale.is_finite()) {
+                    return Err(format!("scale must be positive, got `{scale}`"));
+                }
+
+                parsed.scale = scale;
+            }
+            "-a" | "--asset" => {
+                let asset = value()?;
+                let (id, path) = asset
+                    .split_once('=')
+                    .ok_or(format!("expected `ID=FILE`, got `{asset}`"))?;
+
+                parsed.assets.push((id.to_owned(), path.into()));
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ if scene.is_none() => scene = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+
+    parsed.scene = scene.ok_or("missing scene file")?;
+
+    Ok(Some(parsed))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number `{value}`"))
+}
+
+fn load_scene(path: &Path) -> Result<Scene, String> {
+    let data = std::fs::read(path).map_err(|e| context(path, e))?;
+
+    let scene = if data.starts_with(b"OLVS") {
+        Scene::from_binary(&data)
+    } else {
+        let json = String::from_utf8(data).map_err(|e| context(path, e))?;
+        Scene::from_json(&json)
+    };
+
+    scene.map_err(|e| context(path, e))
+}
+
+/// Returns the file of an asset, either mapped with `--asset` or relative to the
+/// directory of the scene
+///
+/// Scene files may come from anywhere, so unmapped ids can't reach outside of the
+/// directory of the scene
+fn asset_path(id: &str, base_dir: &Path, mapped: &[(String, PathBuf)]) -> Result<PathBuf, String> {
+    if let Some((_, path)) = mapped.iter().rev().find(|(asset, _)| asset == id) {
+        return Ok(path.clone());
+    }
+
+    let relative = Path::new(id);
+    let is_inside = relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+
+    if id.is_empty() || !is_inside {
+        return Err(format!(
+            "asset `{id}` is outside of the scene directory, map it with `--asset {id}=FILE`"
+        ));
+    }
+
+    Ok(base_dir.join(relative))
+}
+
+fn load_assets(scene: &Scene, args: &Args) -> Result<SceneAssets, String> {
+    let base_dir = args.scene.parent().unwrap_or(Path::new(""));
+    let mut assets = SceneAssets::new();
+
+    for id in scene.asset_ids() {
+        let path = asset_path(id, base_dir, &args.assets)?;
+        let canvas = Canvas::load_image(&path).map_err(|e| context(&path, e))?;
+        assets.insert(id, canvas);
+    }
+
+    Ok(assets)
+}
+
+fn render(scene: &Scene, assets: &SceneAssets, args: &Args) -> Result<Canvas, String> {
+    let width = args.width.unwrap_or(scene.width);
+    let height = args.height.unwrap_or(scene.height);
+
+    let (sx, sy) = stretch(scene, width, height);
+    let (width, height) = output_size(width, height, args.scale)?;
+    let mut canvas = Canvas::new(width, height);
+
+    let list = scene
+        .to_display_list(assets)
+        .map_err(|e| context(&args.scene, e))?;
+
+    list.scaled(sx * args.scale, sy * args.scale)
+        .replay(&mut canvas, assets.canvases());
+
+    Ok(canvas)
+}
+
+/// Returns the factors that stretch the scene to the requested size
+fn stretch(scene: &Scene, width: usize, height: usize) -> (f32, f32) {
+    let factor = |to: usize, from: usize| {
+        if from == 0 {
+            1.0
+        } else {
+            to as f32 / from as f32
+        }
+    };
+
+    (factor(width, scene.width), factor(height, scene.height))
+}
+
+/// Returns the size of the scaled canvas, within the same limits as a scene
+fn output_size(width: usize, height: usize, scale: f32) -> Result<(usize, usize), String> {
+    let (width, height) = (
+        (width as f64 * scale as f64).round(),
+        (height as f64 * scale as f64).round(),
+    );
+
+    if width > MAX_SCENE_SIZE as f64
+        || height > MAX_SCENE_SIZE as f64
+        || width * height > MAX_SCENE_PIXELS as f64
+    {
+        return Err(format!(
+            "output of {width}x{height} pixels is too large, the limit is {MAX_SCENE_SIZE} pixels per side and {MAX_SCENE_PIXELS} pixels in total"
+        ));
+    }
+
+    Ok((width as usize, height as usize))
+}
+
+fn save(canvas: &Canvas, path: &Path) -> Result<(), String> {
+    let image = RgbaImage::from_raw(
+        canvas.get_width() as u32,
+        canvas.get_height() as u32,
+        canvas.get_data().to_vec(),
+    )
+    .unwrap();
+
+    let is_ppm = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ppm"));
+
+    // PPM has no alpha channel
+    let result = if is_ppm {
+        DynamicImage::ImageRgba8(image).to_rgb8().save(path)
+    } else {
+        image.save(path)
+    };
+
+    result.map_err(|e| context(path, e))
+}
+
+fn context(path: &Path, err: impl Display) -> String {
+    format!("{}: {err}", path.display())
+}
+
+fn run() -> Result<(), String> {
+    let Some(args) = parse_args()? else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+
+    let mut scene = load_scene(&args.scene)?;
+
+    if args.background.is_some() {
+        scene.background = args.background;
+    }
+
+    let assets = load_assets(&scene, &args)?;
+    let canvas = render(&scene, &assets, &args)?;
+
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| args.scene.with_extension("png"));
+
+    save(&canvas, &output)
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            eprintln!("Run `olivec --help` for usage");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_size_is_scaled_and_capped() {
+        assert_eq!(output_size(40, 30, 1.5), Ok((60, 45)));
+        assert!(output_size(MAX_SCENE_SIZE, 1, 2.0).is_err());
+        assert!(output_size(MAX_SCENE_SIZE, MAX_SCENE_SIZE, 1.0).is_err());
+        assert!(output_size(1000, 1000, f32::MAX).is_err());
+    }
+
+    #[test]
+    fn width_and_height_stretch_the_scene() {
+        let scene = Scene::new(40, 30);
+
+        assert_eq!(stretch(&scene, 40, 30), (1.0, 1.0));
+        assert_eq!(stretch(&scene, 80, 15), (2.0, 0.5));
+        assert_eq!(stretch(&Scene::new(0, 0), 10, 10), (1.0, 1.0));
+    }
+
+    #[test]
+    fn unmapped_assets_stay_in_the_scene_directory() {
+        let base_dir = Path::new("scenes");
+        let mapped = vec![("../logo.png".to_owned(), PathBuf::from("/tmp/logo.png"))];
+
+        assert_eq!(
+            asset_path("images/logo.png", base_dir, &mapped),
+            Ok(Path::new("scenes").join("images/logo.png"))
+        );
+        assert_eq!(
+            asset_path("../logo.png", base_dir, &mapped),
+            Ok(PathBuf::from("/tmp/logo.png"))
+        );
+
+        for id in [
+            "",
+            "/etc/passwd",
+            "../secret.png",
+            "images/../../secret.png",
+        ] {
+            assert!(asset_path(id, base_dir, &mapped).is_err(), "{id:?}");
+        }
+    }
+}
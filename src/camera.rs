@@ -0,0 +1,85 @@
+use crate::{Mat4, Vec3};
+
+/// Describes how the camera maps view space onto the clip volume
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Perspective projection with a vertical field of view in radians
+    Perspective { fov_y: f32, near: f32, far: f32 },
+    /// Orthographic projection showing `height` world units vertically. The
+    /// visible width is derived from the aspect ratio of the target canvas
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+impl Projection {
+    /// Builds the projection matrix for a target with the provided aspect ratio
+    /// (width / height)
+    pub fn matrix(&self, aspect: f32) -> Mat4 {
+        match *self {
+            Projection::Perspective { fov_y, near, far } => {
+                Mat4::perspective(fov_y, aspect, near, far)
+            }
+            Projection::Orthographic { height, near, far } => {
+                let half_h = height / 2.0;
+                let half_w = half_h * aspect;
+
+                Mat4::orthographic(-half_w, half_w, -half_h, half_h, near, far)
+            }
+        }
+    }
+}
+
+/// A camera looking from `position` towards `target`
+///
+/// ```
+/// use olivec::{Camera, Vec3};
+///
+/// let camera = Camera::perspective(60f32.to_radians(), 0.1, 100.0)
+///     .look_at(Vec3::new(0.0, 2.0, 5.0), Vec3::ZERO);
+///
+/// let vp = camera.view_projection(16.0 / 9.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub projection: Projection,
+}
+
+impl Camera {
+    /// Creates a perspective camera at the origin looking down the negative z axis
+    pub fn perspective(fov_y: f32, near: f32, far: f32) -> Self {
+        Self::new(Projection::Perspective { fov_y, near, far })
+    }
+
+    /// Creates an orthographic camera at the origin looking down the negative z axis
+    pub fn orthographic(height: f32, near: f32, far: f32) -> Self {
+        Self::new(Projection::Orthographic { height, near, far })
+    }
+
+    pub fn new(projection: Projection) -> Self {
+        Self {
+            position: Vec3::ZERO,
+            target: -Vec3::Z,
+            up: Vec3::Y,
+            projection,
+        }
+    }
+
+    /// Moves the camera to `position` and points it towards `target`
+    pub fn look_at(mut self, position: Vec3, target: Vec3) -> Self {
+        self.position = position;
+        self.target = target;
+        self
+    }
+
+    /// Returns the matrix transforming world space into view space
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at(self.position, self.target, self.up)
+    }
+
+    /// Returns the combined matrix transforming world space into clip space
+    pub fn view_projection(&self, aspect: f32) -> Mat4 {
+        self.projection.matrix(aspect) * self.view()
+    }
+}
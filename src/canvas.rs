@@ -3,19 +3,28 @@ use std::{marker::PhantomData, mem::size_of};
 use olivec_sys::{
     olivec_canvas, olivec_circle, olivec_ellipse, olivec_fill, olivec_frame, olivec_line,
     olivec_pixel_bilinear, olivec_rect, olivec_sprite_blend, olivec_sprite_copy,
-    olivec_sprite_copy_bilinear, olivec_subcanvas, olivec_text, olivec_triangle, olivec_triangle3c,
+    olivec_sprite_copy_bilinear, olivec_subcanvas, olivec_triangle, olivec_triangle3c,
     olivec_triangle3uv, olivec_triangle3uv_bilinear, olivec_triangle3z, size_t, Olivec_Canvas,
 };
 
-use crate::{normalize_rect, Font, NormalizedRect};
+use crate::{
+    barycentric, blend_color_in_place, brightness_color, color_ops::map_pixels_in, contrast_color,
+    draw_chars, effects::draw_with_shadow, frame_bounds, gamma_color, grayscale_color,
+    hue_rotate_color, invert_color, normalize_rect, normalize_triangle, saturation_color,
+    sepia_color, text_style::draw_styled_text, threshold_color, triangle_bounds, Font, Fragment,
+    NormalizedRect, Rect, RectFragment, TextStyle, Varying, Vec2,
+};
 
 /// Abstraction over the Olivec_Canvas struct which provides the necessary methods to mutate it,
 /// and owns the underlying allocated pixel buffer
 pub struct Canvas {
     /// Olivec_Canvas stores a pointer to this pixel buffer, so it is important that
     /// OwnedCanvas owns the underlying memory, and handles it properly to prevent UAF errors
-    _pixels: Vec<u32>,
+    pixels: Vec<u32>,
     canvas: Olivec_Canvas,
+    /// Regions modified since the last call to `take_damage`, or `None` if damage
+    /// tracking is disabled
+    damage: Option<Vec<Rect>>,
 }
 
 impl Canvas {
@@ -35,6 +44,9 @@ impl Canvas {
             return None;
         }
 
+        // Doesn't reallocate thanks to the check above
+        pixels.resize(width * height, 0);
+
         let canvas = unsafe {
             olivec_canvas(
                 pixels.as_mut_ptr(),
@@ -45,12 +57,67 @@ impl Canvas {
         };
 
         Some(Self {
-            _pixels: pixels,
+            pixels,
             canvas,
+            damage: None,
         })
     }
+
+    /// Enables or disables damage tracking
+    ///
+    /// While enabled, every drawing function records the area of the canvas it may
+    /// have modified, clipped the same way as [`Drawable::normalize_rect`].
+    /// Disabling tracking discards the recorded damage
+    ///
+    /// Drawing through a [`SubCanvas`] is not tracked, use [`Drawable::mark_damage`]
+    /// on the parent canvas to record it manually
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable, Rect };
+    ///
+    /// let mut canvas = Canvas::new(100, 100);
+    /// canvas.track_damage(true);
+    ///
+    /// canvas.rect(10, 10, 20, 20, 0xFF0000FF);
+    /// canvas.rect(90, 90, 20, 20, 0xFF00FF00);
+    ///
+    /// assert_eq!(
+    ///     canvas.take_damage(),
+    ///     vec![Rect::new(10, 10, 20, 20), Rect::new(90, 90, 10, 10)]
+    /// );
+    /// assert!(canvas.take_damage().is_empty());
+    /// ```
+    pub fn track_damage(&mut self, enabled: bool) {
+        self.damage = enabled.then(|| self.damage.take().unwrap_or_default());
+    }
+
+    pub fn is_tracking_damage(&self) -> bool {
+        self.damage.is_some()
+    }
+
+    /// Returns the regions modified since the last call and clears them
+    ///
+    /// Overlapping and touching regions are merged, so the returned rectangles are
+    /// at least a pixel apart. Always empty if damage tracking is disabled
+    pub fn take_damage(&mut self) -> Vec<Rect> {
+        self.damage.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// The pixels of the canvas row by row, without any padding
+    pub(crate) fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    pub(crate) fn pixels_mut(&mut self) -> &mut [u32] {
+        &mut self.pixels
+    }
 }
 
+// Safety: The canvas exclusively owns its pixel buffer, and the pointer stored in
+// Olivec_Canvas only ever refers to that buffer. Mutation requires `&mut self`
+unsafe impl Send for Canvas {}
+unsafe impl Sync for Canvas {}
+
 /// Represents a subspace of a Canvas object
 ///
 /// Modifying the SubCanvas will mutate the original Canvas object as internally
@@ -65,6 +132,29 @@ pub struct SubCanvas<'a> {
 
 pub trait ToOlivecCanvas {
     fn get_olivec_canvas(&self) -> Olivec_Canvas;
+
+    /// Returns whether [`ToOlivecCanvas::record_damage`] does anything, so that the
+    /// drawing functions only compute the area they modified when it is needed
+    ///
+    /// Defaults to `true`, implementations that ignore damage can return `false`
+    #[inline]
+    fn is_tracking_damage(&self) -> bool {
+        true
+    }
+
+    /// Called with the area that a drawing function may have modified, which can lie
+    /// partially outside of the canvas
+    #[inline]
+    fn record_damage(&mut self, _rect: Rect) {}
+}
+
+/// Records the area returned by `rect` as modified, only computing it if the canvas
+/// tracks damage
+#[inline]
+fn record_damage_with<T: ToOlivecCanvas + ?Sized>(canvas: &mut T, rect: impl FnOnce() -> Rect) {
+    if canvas.is_tracking_damage() {
+        canvas.record_damage(rect());
+    }
 }
 
 impl ToOlivecCanvas for Canvas {
@@ -72,6 +162,43 @@ impl ToOlivecCanvas for Canvas {
     fn get_olivec_canvas(&self) -> Olivec_Canvas {
         self.canvas
     }
+
+    #[inline]
+    fn is_tracking_damage(&self) -> bool {
+        self.damage.is_some()
+    }
+
+    fn record_damage(&mut self, rect: Rect) {
+        let Some(damage) = &mut self.damage else {
+            return;
+        };
+
+        let Some(nr) = normalize_rect(
+            rect.x,
+            rect.y,
+            rect.w,
+            rect.h,
+            self.canvas.width as usize,
+            self.canvas.height as usize,
+        ) else {
+            return;
+        };
+
+        let mut rect = Rect::from_corners(nr.x1, nr.y1, nr.x2, nr.y2);
+
+        // Merge overlapping and touching regions to keep the list short, so that
+        // primitives drawn pixel by pixel end up as a single rectangle
+        let touches = |r: &Rect, rect: &Rect| {
+            let grown = Rect::new(rect.x - 1, rect.y - 1, rect.w + 2, rect.h + 2);
+            r.intersection(&grown).is_some()
+        };
+
+        while let Some(i) = damage.iter().position(|r| touches(r, &rect)) {
+            rect = rect.union(&damage.swap_remove(i));
+        }
+
+        damage.push(rect);
+    }
 }
 
 impl<'a> ToOlivecCanvas for SubCanvas<'a> {
@@ -79,6 +206,11 @@ impl<'a> ToOlivecCanvas for SubCanvas<'a> {
     fn get_olivec_canvas(&self) -> Olivec_Canvas {
         self.canvas
     }
+
+    #[inline]
+    fn is_tracking_damage(&self) -> bool {
+        false
+    }
 }
 
 /// A common interface for anything that stores a reference to an Olivec_Canvas
@@ -182,15 +314,58 @@ pub trait Drawable {
         z3: f32,
         texture: &mut impl ToOlivecCanvas,
     );
-    fn text<'a, T: Into<&'a str>, const W: usize, const H: usize>(
+
+    /// Draws a line of text with its top left corner at `(x, y)` using any [`Font`]
+    ///
+    /// Characters without a glyph in the font are drawn with its fallback glyph
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable, FixedFont };
+    ///
+    /// let mut canvas = Canvas::new(200, 50);
+    ///
+    /// canvas.text("Größe: 42", 10, 10, &FixedFont::<6, 6>::default(), 3, 0xFFFFFFFF);
+    /// ```
+    fn text<'a, T: Into<&'a str>>(
+        &mut self,
+        text: T,
+        x: i32,
+        y: i32,
+        font: &(impl Font + ?Sized),
+        size: usize,
+        color: u32,
+    );
+
+    /// Same as [`Drawable::text`] but draws exactly the provided bytes, each of them
+    /// being a Latin-1 character
+    ///
+    /// Useful for fixed size buffers which may contain NUL bytes
+    fn text_bytes(
+        &mut self,
+        bytes: &[u8],
+        x: i32,
+        y: i32,
+        font: &(impl Font + ?Sized),
+        size: usize,
+        color: u32,
+    );
+
+    /// Same as [`Drawable::text`] but with rotation, vertical layout, an outline and
+    /// bold emulation, see [`TextStyle`]
+    ///
+    /// The text is rotated around `(x, y)`, its top left corner when upright
+    #[allow(clippy::too_many_arguments)]
+    fn text_styled<'a, T: Into<&'a str>>(
         &mut self,
         text: T,
         x: i32,
         y: i32,
-        font: Font<W, H>,
+        font: &(impl Font + ?Sized),
         size: usize,
         color: u32,
+        style: &TextStyle,
     );
+
     fn sprite_blend(&mut self, x: i32, y: i32, w: i32, h: i32, sprite: &mut impl ToOlivecCanvas);
     fn sprite_copy(&mut self, x: i32, y: i32, w: i32, h: i32, sprite: &mut impl ToOlivecCanvas);
     fn sprite_copy_bilinear(
@@ -205,6 +380,34 @@ pub trait Drawable {
 
     /* Additional Helpers */
 
+    /// Sets every pixel of the canvas to the color returned by `shader` for its
+    /// coordinates
+    ///
+    /// Rows are shaded in parallel when the `rayon` feature is enabled, so the shader
+    /// must not rely on being called in any particular order
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable, rgb };
+    ///
+    /// let mut canvas = Canvas::new(256, 256);
+    ///
+    /// canvas.shade(|x, y| rgb!(x as u32, y as u32, 128));
+    /// ```
+    fn shade(&mut self, shader: impl Fn(i32, i32) -> u32 + Sync);
+
+    /// Same as [`Drawable::shade`] but limited to the rectangle at `(x, y)` with a
+    /// width of `w` and height of `h`
+    ///
+    /// The shader still receives canvas coordinates
+    fn shade_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        shader: impl Fn(i32, i32) -> u32 + Sync,
+    );
+
     /// Sets an individual pixel in the canvas to the given color
     ///
     /// This function does a bounds check to make sure that the provided coordinates
@@ -219,6 +422,201 @@ pub trait Drawable {
     /// ```
     fn set_pixel(&mut self, x: i32, y: i32, color: u32);
 
+    /// Gets the color of an individual pixel in the canvas
+    ///
+    /// Returns `None` if the provided coordinates are outside the bounds of the canvas
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable };
+    ///
+    /// let mut canvas = Canvas::new(100, 100);
+    ///
+    /// canvas.set_pixel(20, 20, 0xFF0000FF);
+    ///
+    /// assert_eq!(canvas.get_pixel(20, 20), Some(0xFF0000FF));
+    /// assert_eq!(canvas.get_pixel(100, 20), None);
+    /// ```
+    fn get_pixel(&self, x: i32, y: i32) -> Option<u32>;
+
+    /// Blends the given color on top of an individual pixel in the canvas
+    ///
+    /// Does nothing if the provided coordinates are outside the bounds of the canvas
+    fn blend_pixel(&mut self, x: i32, y: i32, color: u32);
+
+    /// Records that the given area of the canvas was modified by means other than the
+    /// drawing functions of this canvas, such as through a [`SubCanvas`]
+    ///
+    /// Only has an effect on a [`Canvas`] with damage tracking enabled
+    fn mark_damage(&mut self, rect: Rect);
+
+    /// Draws a triangle with a color computed by the `shader` closure for every pixel
+    /// it covers
+    ///
+    /// Each point carries a varying that gets interpolated across the triangle and
+    /// handed to the shader in its [`Fragment`], use `()` if none is needed. The
+    /// returned colors are blended onto the canvas, so a fully transparent color
+    /// leaves the pixel untouched
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable, Vec3, rgb };
+    ///
+    /// let mut canvas = Canvas::new(100, 100);
+    ///
+    /// canvas.triangle_shaded(
+    ///     (50, 10, Vec3::new(1.0, 0.0, 0.0)),
+    ///     (10, 90, Vec3::new(0.0, 1.0, 0.0)),
+    ///     (90, 90, Vec3::new(0.0, 0.0, 1.0)),
+    ///     |frag| {
+    ///         let c = frag.varying * 255.0;
+    ///         rgb!(c.x as u32, c.y as u32, c.z as u32)
+    ///     },
+    /// );
+    /// ```
+    fn triangle_shaded<V: Varying>(
+        &mut self,
+        p1: (i32, i32, V),
+        p2: (i32, i32, V),
+        p3: (i32, i32, V),
+        shader: impl FnMut(Fragment<V>) -> u32,
+    );
+
+    /// Draws a rectangle with a color computed by the `shader` closure for every pixel
+    /// it covers
+    ///
+    /// The returned colors are blended onto the canvas the same way as with
+    /// [`Drawable::triangle_shaded`]
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable, rgba };
+    ///
+    /// let mut canvas = Canvas::new(100, 100);
+    ///
+    /// // A soft edged circle from its signed distance
+    /// canvas.rect_shaded(10, 10, 80, 80, |frag| {
+    ///     let d = (frag.uv.x - 0.5).hypot(frag.uv.y - 0.5) - 0.4;
+    ///     let alpha = (0.5 - d * 80.0).clamp(0.0, 1.0);
+    ///
+    ///     rgba!(255, 0, 0, (alpha * 255.0) as u32)
+    /// });
+    /// ```
+    fn rect_shaded(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        shader: impl FnMut(RectFragment) -> u32,
+    );
+
+    /// Draws whatever `draw` renders with a drop shadow underneath it
+    ///
+    /// The closure draws into a transparent offscreen layer, which it is called twice
+    /// to render. The alpha of the layer is tinted with `color`, blurred by
+    /// `blur_radius` pixels and composited at `offset`, before the layer itself is
+    /// composited on top, so translucent and anti-aliased parts of the drawing cast
+    /// a fainter shadow
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable };
+    ///
+    /// let mut canvas = Canvas::new(400, 300);
+    /// canvas.fill(0xFFE0E0E0);
+    ///
+    /// canvas.with_shadow((4, 6), 12.0, 0x80000000, |card| {
+    ///     card.rect(50, 50, 300, 200, 0xFFFFFFFF);
+    /// });
+    /// ```
+    fn with_shadow(
+        &mut self,
+        offset: (i32, i32),
+        blur_radius: f32,
+        color: u32,
+        draw: impl FnMut(&mut Canvas),
+    );
+
+    /// Draws whatever `draw` renders with a blurred halo of `color` around it
+    ///
+    /// Same as [`Drawable::with_shadow`] without any offset
+    fn with_glow(&mut self, blur_radius: f32, color: u32, draw: impl FnMut(&mut Canvas));
+
+    /// Replaces the color of every pixel of the canvas with the one returned by `f`
+    ///
+    /// Rows are processed in parallel when the `rayon` feature is enabled. Like the
+    /// color adjustments below, it has an `_in` variant limited to a region
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable, Rect, invert_color, grayscale_color };
+    ///
+    /// let mut canvas = Canvas::new(100, 100);
+    /// canvas.fill(0xFF2020FF);
+    ///
+    /// // Dim the sidebar
+    /// canvas.adjust_brightness_in(Rect::new(0, 0, 20, 100), 0.5);
+    ///
+    /// // Desaturate the footer
+    /// canvas.map_pixels_in(Rect::new(0, 80, 100, 20), grayscale_color);
+    ///
+    /// canvas.map_pixels(|c| invert_color(grayscale_color(c)));
+    /// ```
+    fn map_pixels(&mut self, f: impl Fn(u32) -> u32 + Sync);
+
+    /// Same as [`Drawable::map_pixels`] for the pixels inside of `rect` only
+    fn map_pixels_in(&mut self, rect: Rect, f: impl Fn(u32) -> u32 + Sync);
+
+    /// See [`grayscale_color`]
+    fn grayscale(&mut self);
+
+    /// Same as [`Drawable::grayscale`] for the pixels inside of `rect` only
+    fn grayscale_in(&mut self, rect: Rect);
+
+    /// See [`invert_color`]
+    fn invert(&mut self);
+
+    /// Same as [`Drawable::invert`] for the pixels inside of `rect` only
+    fn invert_in(&mut self, rect: Rect);
+
+    /// See [`brightness_color`]
+    fn adjust_brightness(&mut self, factor: f32);
+
+    /// Same as [`Drawable::adjust_brightness`] for the pixels inside of `rect` only
+    fn adjust_brightness_in(&mut self, rect: Rect, factor: f32);
+
+    /// See [`contrast_color`]
+    fn adjust_contrast(&mut self, factor: f32);
+
+    /// Same as [`Drawable::adjust_contrast`] for the pixels inside of `rect` only
+    fn adjust_contrast_in(&mut self, rect: Rect, factor: f32);
+
+    /// See [`saturation_color`]
+    fn adjust_saturation(&mut self, factor: f32);
+
+    /// Same as [`Drawable::adjust_saturation`] for the pixels inside of `rect` only
+    fn adjust_saturation_in(&mut self, rect: Rect, factor: f32);
+
+    /// See [`hue_rotate_color`]
+    fn rotate_hue(&mut self, degrees: f32);
+
+    /// Same as [`Drawable::rotate_hue`] for the pixels inside of `rect` only
+    fn rotate_hue_in(&mut self, rect: Rect, degrees: f32);
+
+    /// See [`gamma_color`]
+    fn gamma(&mut self, gamma: f32);
+
+    /// Same as [`Drawable::gamma`] for the pixels inside of `rect` only
+    fn gamma_in(&mut self, rect: Rect, gamma: f32);
+
+    /// See [`sepia_color`]
+    fn sepia(&mut self);
+
+    /// Same as [`Drawable::sepia`] for the pixels inside of `rect` only
+    fn sepia_in(&mut self, rect: Rect);
+
+    /// See [`threshold_color`]
+    fn threshold(&mut self, level: u8);
+
+    /// Same as [`Drawable::threshold`] for the pixels inside of `rect` only
+    fn threshold_in(&mut self, rect: Rect, level: u8);
+
     /// The point of this function is to produce two ranges `x1..=x2` and `y1..=y2` that
     /// are guaranteed to be safe to iterate over the canvas of size canvas_width
     /// by canvas_height without any boundary checks.
@@ -270,11 +668,14 @@ impl<T: ToOlivecCanvas> Drawable for T {
     #[inline]
     fn fill(&mut self, color: u32) {
         unsafe { olivec_fill(self.get_olivec_canvas(), color) }
+        let (width, height) = (self.get_width() as i32, self.get_height() as i32);
+        record_damage_with(self, || Rect::new(0, 0, width, height));
     }
 
     #[inline]
     fn rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: u32) {
         unsafe { olivec_rect(self.get_olivec_canvas(), x, y, w, h, color) }
+        record_damage_with(self, || Rect::from_signed(x, y, w, h));
     }
 
     #[inline]
@@ -290,26 +691,45 @@ impl<T: ToOlivecCanvas> Drawable for T {
                 color,
             )
         }
+        record_damage_with(self, || frame_bounds(x, y, w, h, thiccness));
     }
 
     #[inline]
     fn circle(&mut self, cx: i32, cy: i32, r: i32, color: u32) {
         unsafe { olivec_circle(self.get_olivec_canvas(), cx, cy, r, color) }
+        record_damage_with(self, || {
+            Rect::from_corners(
+                cx.saturating_sub(r),
+                cy.saturating_sub(r),
+                cx.saturating_add(r),
+                cy.saturating_add(r),
+            )
+        });
     }
 
     #[inline]
     fn ellipse(&mut self, cx: i32, cy: i32, rx: i32, ry: i32, color: u32) {
         unsafe { olivec_ellipse(self.get_olivec_canvas(), cx, cy, rx, ry, color) }
+        record_damage_with(self, || {
+            Rect::from_corners(
+                cx.saturating_sub(rx),
+                cy.saturating_sub(ry),
+                cx.saturating_add(rx),
+                cy.saturating_add(ry),
+            )
+        });
     }
 
     #[inline]
     fn line(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, color: u32) {
         unsafe { olivec_line(self.get_olivec_canvas(), x1, y1, x2, y2, color) }
+        record_damage_with(self, || Rect::from_corners(x1, y1, x2, y2));
     }
 
     #[inline]
     fn triangle(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, x3: i32, y3: i32, color: u32) {
         unsafe { olivec_triangle(self.get_olivec_canvas(), x1, y1, x2, y2, x3, y3, color) }
+        record_damage_with(self, || triangle_bounds(x1, y1, x2, y2, x3, y3));
     }
 
     #[inline]
@@ -326,6 +746,7 @@ impl<T: ToOlivecCanvas> Drawable for T {
         c3: u32,
     ) {
         unsafe { olivec_triangle3c(self.get_olivec_canvas(), x1, y1, x2, y2, x3, y3, c1, c2, c3) }
+        record_damage_with(self, || triangle_bounds(x1, y1, x2, y2, x3, y3));
     }
 
     #[inline]
@@ -342,6 +763,7 @@ impl<T: ToOlivecCanvas> Drawable for T {
         z3: f32,
     ) {
         unsafe { olivec_triangle3z(self.get_olivec_canvas(), x1, y1, x2, y2, x3, y3, z1, z2, z3) }
+        record_damage_with(self, || triangle_bounds(x1, y1, x2, y2, x3, y3));
     }
 
     #[inline]
@@ -385,6 +807,7 @@ impl<T: ToOlivecCanvas> Drawable for T {
                 texture.get_olivec_canvas(),
             )
         }
+        record_damage_with(self, || triangle_bounds(x1, y1, x2, y2, x3, y3));
     }
 
     #[inline]
@@ -428,31 +851,53 @@ impl<T: ToOlivecCanvas> Drawable for T {
                 texture.get_olivec_canvas(),
             )
         }
+        record_damage_with(self, || triangle_bounds(x1, y1, x2, y2, x3, y3));
     }
 
     #[inline]
-    fn text<'a, U: Into<&'a str>, const W: usize, const H: usize>(
+    fn text<'a, U: Into<&'a str>>(
         &mut self,
         text: U,
         x: i32,
         y: i32,
-        font: Font<W, H>,
+        font: &(impl Font + ?Sized),
         size: usize,
         color: u32,
     ) {
         let string: &str = text.into();
 
-        unsafe {
-            olivec_text(
-                self.get_olivec_canvas(),
-                string.as_ptr() as *const i8,
-                x,
-                y,
-                font.font,
-                size as size_t,
-                color,
-            )
-        }
+        // olivec_text expects a NUL terminated string of ASCII characters, so the
+        // glyphs are drawn here instead
+        draw_chars(self, string.chars(), x, y, font, size, color);
+    }
+
+    #[inline]
+    fn text_bytes(
+        &mut self,
+        bytes: &[u8],
+        x: i32,
+        y: i32,
+        font: &(impl Font + ?Sized),
+        size: usize,
+        color: u32,
+    ) {
+        let chars = bytes.iter().map(|&b| b as char);
+
+        draw_chars(self, chars, x, y, font, size, color);
+    }
+
+    #[inline]
+    fn text_styled<'a, U: Into<&'a str>>(
+        &mut self,
+        text: U,
+        x: i32,
+        y: i32,
+        font: &(impl Font + ?Sized),
+        size: usize,
+        color: u32,
+        style: &TextStyle,
+    ) {
+        draw_styled_text(self, text.into(), x, y, font, size, color, style);
     }
 
     #[inline]
@@ -467,6 +912,7 @@ impl<T: ToOlivecCanvas> Drawable for T {
                 sprite.get_olivec_canvas(),
             )
         }
+        record_damage_with(self, || Rect::from_signed(x, y, w, h));
     }
 
     #[inline]
@@ -481,6 +927,7 @@ impl<T: ToOlivecCanvas> Drawable for T {
                 sprite.get_olivec_canvas(),
             )
         }
+        record_damage_with(self, || Rect::from_signed(x, y, w, h));
     }
 
     #[inline]
@@ -502,24 +949,300 @@ impl<T: ToOlivecCanvas> Drawable for T {
                 sprite.get_olivec_canvas(),
             )
         }
+        record_damage_with(self, || Rect::from_signed(x, y, w, h));
     }
 
     fn pixel_bilinear(&mut self, nx: i32, ny: i32, w: i32, h: i32) -> u32 {
         unsafe { olivec_pixel_bilinear(self.get_olivec_canvas(), nx, ny, w, h) }
     }
 
+    #[inline]
+    fn shade(&mut self, shader: impl Fn(i32, i32) -> u32 + Sync) {
+        self.shade_rect(
+            0,
+            0,
+            self.get_width() as i32,
+            self.get_height() as i32,
+            shader,
+        )
+    }
+
+    fn shade_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        shader: impl Fn(i32, i32) -> u32 + Sync,
+    ) {
+        let Some(nr) = self.normalize_rect(x, y, w, h) else {
+            return;
+        };
+
+        // Safety: The rectangle was normalized against the bounds of this canvas and
+        // the rows are borrowed mutably through `self`
+        let mut rows = unsafe { rows_mut(self.get_olivec_canvas(), &nr) };
+        record_damage_with(self, || Rect::from_corners(nr.x1, nr.y1, nr.x2, nr.y2));
+
+        let shade_row = |(i, row): (usize, &mut &mut [u32])| {
+            let y = nr.y1 + i as i32;
+
+            for (pixel, x) in row.iter_mut().zip(nr.x1..) {
+                *pixel = shader(x, y);
+            }
+        };
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+
+            rows.par_iter_mut().enumerate().for_each(shade_row);
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        rows.iter_mut().enumerate().for_each(shade_row);
+    }
+
     #[inline]
     fn set_pixel(&mut self, x: i32, y: i32, color: u32) {
+        // Safety: The pixel is borrowed mutably through `self`
+        if let Some(pixel) = unsafe { pixel_mut(self.get_olivec_canvas(), x, y) } {
+            *pixel = color;
+            record_damage_with(self, || Rect::new(x, y, 1, 1));
+        }
+    }
+
+    #[inline]
+    fn get_pixel(&self, x: i32, y: i32) -> Option<u32> {
         let oc = self.get_olivec_canvas();
 
-        if x > 0 && x < oc.width as i32 && y > 0 && y < oc.height as i32 {
-            // Safety: As long as coords are within the canvas width and height,
-            // the following address calculation should be a safe index into the
-            // allocated pixel buffer
-            unsafe {
-                *oc.pixels.offset((y * oc.stride as i32 + x) as isize) = color;
+        if x >= 0 && x < oc.width as i32 && y >= 0 && y < oc.height as i32 {
+            // Safety: Same bounds check as `set_pixel`
+            unsafe { Some(*oc.pixels.offset((y * oc.stride as i32 + x) as isize)) }
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn blend_pixel(&mut self, x: i32, y: i32, color: u32) {
+        // Safety: The pixel is borrowed mutably through `self`
+        if let Some(pixel) = unsafe { pixel_mut(self.get_olivec_canvas(), x, y) } {
+            blend_color_in_place(pixel, color);
+            record_damage_with(self, || Rect::new(x, y, 1, 1));
+        }
+    }
+
+    #[inline]
+    fn mark_damage(&mut self, rect: Rect) {
+        self.record_damage(rect);
+    }
+
+    fn triangle_shaded<V: Varying>(
+        &mut self,
+        (x1, y1, v1): (i32, i32, V),
+        (x2, y2, v2): (i32, i32, V),
+        (x3, y3, v3): (i32, i32, V),
+        mut shader: impl FnMut(Fragment<V>) -> u32,
+    ) {
+        let (mut lx, mut hx, mut ly, mut hy) = (0, 0, 0, 0);
+
+        if !normalize_triangle(
+            self.get_width(),
+            self.get_height(),
+            x1,
+            y1,
+            x2,
+            y2,
+            x3,
+            y3,
+            &mut lx,
+            &mut hx,
+            &mut ly,
+            &mut hy,
+        ) {
+            return;
+        }
+
+        for y in ly..=hy {
+            for x in lx..=hx {
+                let (mut u1, mut u2, mut det) = (0, 0, 0);
+
+                if !barycentric(x1, y1, x2, y2, x3, y3, x, y, &mut u1, &mut u2, &mut det)
+                    || det == 0
+                {
+                    continue;
+                }
+
+                let l1 = u1 as f32 / det as f32;
+                let l2 = u2 as f32 / det as f32;
+                let barycentric = [l1, l2, 1.0 - l1 - l2];
+
+                let color = shader(Fragment {
+                    x,
+                    y,
+                    barycentric,
+                    varying: V::interpolate(v1, v2, v3, barycentric),
+                });
+
+                // Safety: The bounding box was normalized against this canvas
+                if let Some(pixel) = unsafe { pixel_mut(self.get_olivec_canvas(), x, y) } {
+                    blend_color_in_place(pixel, color);
+                }
             }
         }
+
+        record_damage_with(self, || Rect::from_corners(lx, ly, hx, hy));
+    }
+
+    fn rect_shaded(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        mut shader: impl FnMut(RectFragment) -> u32,
+    ) {
+        let Some(nr) = self.normalize_rect(x, y, w, h) else {
+            return;
+        };
+
+        let (w, h) = (w.abs() as f32, h.abs() as f32);
+
+        for y in nr.y1..=nr.y2 {
+            for x in nr.x1..=nr.x2 {
+                let uv = Vec2::new(
+                    ((x - nr.ox1) as f32 + 0.5) / w,
+                    ((y - nr.oy1) as f32 + 0.5) / h,
+                );
+
+                let color = shader(RectFragment { x, y, uv });
+
+                // Safety: The rectangle was normalized against this canvas
+                if let Some(pixel) = unsafe { pixel_mut(self.get_olivec_canvas(), x, y) } {
+                    blend_color_in_place(pixel, color);
+                }
+            }
+        }
+
+        record_damage_with(self, || Rect::from_corners(nr.x1, nr.y1, nr.x2, nr.y2));
+    }
+
+    #[inline]
+    fn with_shadow(
+        &mut self,
+        offset: (i32, i32),
+        blur_radius: f32,
+        color: u32,
+        draw: impl FnMut(&mut Canvas),
+    ) {
+        draw_with_shadow(self, offset, blur_radius, color, draw)
+    }
+
+    #[inline]
+    fn with_glow(&mut self, blur_radius: f32, color: u32, draw: impl FnMut(&mut Canvas)) {
+        draw_with_shadow(self, (0, 0), blur_radius, color, draw)
+    }
+
+    #[inline]
+    fn map_pixels(&mut self, f: impl Fn(u32) -> u32 + Sync) {
+        let bounds = Rect::new(0, 0, self.get_width() as i32, self.get_height() as i32);
+        map_pixels_in(self, bounds, f)
+    }
+
+    #[inline]
+    fn map_pixels_in(&mut self, rect: Rect, f: impl Fn(u32) -> u32 + Sync) {
+        map_pixels_in(self, rect, f)
+    }
+
+    #[inline]
+    fn grayscale(&mut self) {
+        self.map_pixels(grayscale_color)
+    }
+
+    #[inline]
+    fn grayscale_in(&mut self, rect: Rect) {
+        self.map_pixels_in(rect, grayscale_color)
+    }
+
+    #[inline]
+    fn invert(&mut self) {
+        self.map_pixels(invert_color)
+    }
+
+    #[inline]
+    fn invert_in(&mut self, rect: Rect) {
+        self.map_pixels_in(rect, invert_color)
+    }
+
+    #[inline]
+    fn adjust_brightness(&mut self, factor: f32) {
+        self.map_pixels(|c| brightness_color(c, factor))
+    }
+
+    #[inline]
+    fn adjust_brightness_in(&mut self, rect: Rect, factor: f32) {
+        self.map_pixels_in(rect, |c| brightness_color(c, factor))
+    }
+
+    #[inline]
+    fn adjust_contrast(&mut self, factor: f32) {
+        self.map_pixels(|c| contrast_color(c, factor))
+    }
+
+    #[inline]
+    fn adjust_contrast_in(&mut self, rect: Rect, factor: f32) {
+        self.map_pixels_in(rect, |c| contrast_color(c, factor))
+    }
+
+    #[inline]
+    fn adjust_saturation(&mut self, factor: f32) {
+        self.map_pixels(|c| saturation_color(c, factor))
+    }
+
+    #[inline]
+    fn adjust_saturation_in(&mut self, rect: Rect, factor: f32) {
+        self.map_pixels_in(rect, |c| saturation_color(c, factor))
+    }
+
+    #[inline]
+    fn rotate_hue(&mut self, degrees: f32) {
+        self.map_pixels(|c| hue_rotate_color(c, degrees))
+    }
+
+    #[inline]
+    fn rotate_hue_in(&mut self, rect: Rect, degrees: f32) {
+        self.map_pixels_in(rect, |c| hue_rotate_color(c, degrees))
+    }
+
+    #[inline]
+    fn gamma(&mut self, gamma: f32) {
+        self.map_pixels(|c| gamma_color(c, gamma))
+    }
+
+    #[inline]
+    fn gamma_in(&mut self, rect: Rect, gamma: f32) {
+        self.map_pixels_in(rect, |c| gamma_color(c, gamma))
+    }
+
+    #[inline]
+    fn sepia(&mut self) {
+        self.map_pixels(sepia_color)
+    }
+
+    #[inline]
+    fn sepia_in(&mut self, rect: Rect) {
+        self.map_pixels_in(rect, sepia_color)
+    }
+
+    #[inline]
+    fn threshold(&mut self, level: u8) {
+        self.map_pixels(|c| threshold_color(c, level))
+    }
+
+    #[inline]
+    fn threshold_in(&mut self, rect: Rect, level: u8) {
+        self.map_pixels_in(rect, |c| threshold_color(c, level))
     }
 
     #[inline]
@@ -549,3 +1272,252 @@ impl<T: ToOlivecCanvas> Drawable for T {
         }
     }
 }
+
+/// Splits the area of the canvas covered by the normalized rectangle into one mutable
+/// slice per row
+///
+/// # Safety
+///
+/// The rectangle must be normalized against the bounds of `oc`, and the caller must
+/// have exclusive access to its pixels for the lifetime `'a`
+pub(crate) unsafe fn rows_mut<'a>(oc: Olivec_Canvas, nr: &NormalizedRect) -> Vec<&'a mut [u32]> {
+    let width = (nr.x2 - nr.x1 + 1) as usize;
+
+    (nr.y1..=nr.y2)
+        .map(|y| {
+            let start = oc
+                .pixels
+                .add(y as usize * oc.stride as usize + nr.x1 as usize);
+
+            std::slice::from_raw_parts_mut(start, width)
+        })
+        .collect()
+}
+
+/// Returns the pixel at the given coordinates, or `None` if they are outside the bounds
+/// of the canvas
+///
+/// # Safety
+///
+/// The caller must have exclusive access to the pixels of `oc` for the lifetime `'a`
+pub(crate) unsafe fn pixel_mut<'a>(oc: Olivec_Canvas, x: i32, y: i32) -> Option<&'a mut u32> {
+    if x >= 0 && x < oc.width as i32 && y >= 0 && y < oc.height as i32 {
+        // As long as coords are within the canvas width and height, the following
+        // address calculation is a safe index into the allocated pixel buffer
+        Some(&mut *oc.pixels.add(y as usize * oc.stride as usize + x as usize))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FixedFont;
+
+    #[test]
+    fn set_pixel_reaches_the_first_row_and_column() {
+        let mut canvas = Canvas::new(3, 3);
+
+        canvas.set_pixel(0, 0, 0xFF0000FF);
+        canvas.set_pixel(0, 2, 0xFF00FF00);
+        canvas.set_pixel(2, 0, 0xFFFF0000);
+
+        assert_eq!(canvas.get_pixel(0, 0), Some(0xFF0000FF));
+        assert_eq!(canvas.get_pixel(0, 2), Some(0xFF00FF00));
+        assert_eq!(canvas.get_pixel(2, 0), Some(0xFFFF0000));
+    }
+
+    #[test]
+    fn set_pixel_ignores_coordinates_outside_the_canvas() {
+        let mut canvas = Canvas::new(3, 3);
+
+        canvas.set_pixel(-1, 0, 0xFFFFFFFF);
+        canvas.set_pixel(0, -1, 0xFFFFFFFF);
+        canvas.set_pixel(3, 0, 0xFFFFFFFF);
+        canvas.set_pixel(0, 3, 0xFFFFFFFF);
+
+        assert!(canvas.pixels().iter().all(|&pixel| pixel == 0));
+        assert_eq!(canvas.get_pixel(-1, 0), None);
+    }
+
+    #[test]
+    fn shade_sets_every_pixel_from_its_coordinates() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.shade(|x, y| (y * 3 + x) as u32);
+
+        assert_eq!(canvas.pixels(), &[0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn shade_rect_is_clipped_and_receives_canvas_coordinates() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.shade_rect(2, -1, 4, 3, |x, y| (x * 10 + y) as u32 + 1);
+
+        assert_eq!(canvas.pixels()[..8], [0, 0, 21, 31, 0, 0, 22, 32]);
+        assert!(canvas.pixels()[8..].iter().all(|&pixel| pixel == 0));
+    }
+
+    #[test]
+    fn shade_respects_the_stride_of_subcanvases() {
+        let mut canvas = Canvas::new(4, 3);
+        let mut sub = canvas.subcanvas(1, 1, 2, 2).unwrap();
+
+        sub.shade(|_, _| 7);
+
+        assert_eq!(canvas.pixels(), &[0, 0, 0, 0, 0, 7, 7, 0, 0, 7, 7, 0]);
+    }
+
+    #[test]
+    fn damage_is_only_recorded_while_tracking() {
+        let mut canvas = Canvas::new(8, 8);
+        canvas.rect(0, 0, 2, 2, 0xFFFFFFFF);
+        assert!(canvas.take_damage().is_empty());
+
+        canvas.track_damage(true);
+        canvas.rect(0, 0, 2, 2, 0xFFFFFFFF);
+        canvas.track_damage(false);
+
+        assert!(!canvas.is_tracking_damage());
+        assert!(canvas.take_damage().is_empty());
+    }
+
+    #[test]
+    fn damage_is_clipped_to_the_canvas() {
+        let mut canvas = Canvas::new(8, 8);
+        canvas.track_damage(true);
+
+        canvas.rect(-4, 6, 6, 10, 0xFFFFFFFF);
+        canvas.rect(20, 20, 4, 4, 0xFFFFFFFF);
+
+        assert_eq!(canvas.take_damage(), [Rect::new(0, 6, 2, 2)]);
+    }
+
+    #[test]
+    fn touching_damage_is_merged_and_distant_damage_is_not() {
+        let mut canvas = Canvas::new(16, 16);
+        canvas.track_damage(true);
+
+        canvas.rect(0, 0, 2, 2, 0xFFFFFFFF);
+        canvas.rect(2, 2, 2, 2, 0xFFFFFFFF);
+        canvas.rect(10, 0, 2, 2, 0xFFFFFFFF);
+
+        assert_eq!(
+            canvas.take_damage(),
+            [Rect::new(0, 0, 4, 4), Rect::new(10, 0, 2, 2)]
+        );
+
+        // A rect bridging two regions merges all three
+        canvas.rect(0, 0, 2, 2, 0xFFFFFFFF);
+        canvas.rect(10, 0, 2, 2, 0xFFFFFFFF);
+        canvas.rect(1, 0, 10, 1, 0xFFFFFFFF);
+
+        assert_eq!(canvas.take_damage(), [Rect::new(0, 0, 12, 2)]);
+    }
+
+    #[test]
+    fn triangle_drawn_pixel_by_pixel_is_a_single_damage_rect() {
+        let mut canvas = Canvas::new(64, 64);
+        canvas.track_damage(true);
+
+        for y in 0..64 {
+            for x in 0..=y {
+                canvas.set_pixel(x, y, 0xFFFFFFFF);
+            }
+        }
+
+        assert_eq!(canvas.take_damage(), [Rect::new(0, 0, 64, 64)]);
+    }
+
+    #[test]
+    fn subcanvas_damage_is_marked_on_the_parent() {
+        let mut canvas = Canvas::new(8, 8);
+        canvas.track_damage(true);
+
+        canvas.subcanvas(2, 2, 4, 4).unwrap().fill(0xFFFFFFFF);
+        assert!(canvas.take_damage().is_empty());
+
+        canvas.mark_damage(Rect::new(2, 2, 4, 4));
+        assert_eq!(canvas.take_damage(), [Rect::new(2, 2, 4, 4)]);
+    }
+
+    #[test]
+    fn extreme_coordinates_do_not_overflow_the_damage() {
+        for tracking in [false, true] {
+            let mut canvas = Canvas::new(4, 4);
+            canvas.track_damage(tracking);
+
+            canvas.rect(i32::MAX, 0, 2, 2, 0xFFFFFFFF);
+            canvas.rect(i32::MIN, i32::MIN, -2, -2, 0xFFFFFFFF);
+            canvas.frame(i32::MAX, 0, 2, 2, usize::MAX, 0xFFFFFFFF);
+            canvas.circle(0, 0, i32::MAX, 0xFFFFFFFF);
+
+            let damage = canvas.take_damage();
+            assert!(tracking || damage.is_empty());
+            assert!(damage
+                .iter()
+                .all(|rect| rect.intersection(&Rect::new(0, 0, 4, 4)) == Some(*rect)));
+        }
+    }
+
+    /// A 2 by 2 font where `a` is filled and `?` only has its top left pixel
+    fn blocks() -> FixedFont<2, 2> {
+        let mut rows = [[0u8; 2]; 128];
+        rows['a' as usize] = [0b11, 0b11];
+        rows['?' as usize] = [0b10, 0b00];
+
+        FixedFont::from_bitmasks(rows)
+    }
+
+    fn black(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        canvas.fill(0xFF000000);
+        canvas
+    }
+
+    const W: u32 = 0xFFFFFFFF;
+    const B: u32 = 0xFF000000;
+
+    #[test]
+    fn text_draws_unsupported_characters_with_the_fallback_glyph() {
+        let mut canvas = black(4, 2);
+        canvas.text("aé", 0, 0, &blocks(), 1, W);
+
+        assert_eq!(canvas.pixels(), &[W, W, W, B, W, W, B, B]);
+
+        let mut canvas = black(4, 2);
+        canvas.text("aé", 0, 0, &blocks().with_fallback(' '), 1, W);
+
+        assert_eq!(canvas.pixels(), &[W, W, B, B, W, W, B, B]);
+    }
+
+    #[test]
+    fn text_scales_glyph_pixels() {
+        let mut canvas = black(4, 4);
+        canvas.text("?", 0, 0, &blocks(), 2, W);
+
+        #[rustfmt::skip]
+        assert_eq!(canvas.pixels(), &[
+            W, W, B, B,
+            W, W, B, B,
+            B, B, B, B,
+            B, B, B, B,
+        ]);
+    }
+
+    #[test]
+    fn text_bytes_draws_past_nul_bytes() {
+        let mut canvas = black(6, 1);
+        canvas.text_bytes(b"a\0a", 0, 0, &blocks(), 1, W);
+
+        assert_eq!(canvas.pixels(), &[W, W, B, B, W, W]);
+    }
+
+    #[test]
+    fn text_is_clipped_to_the_canvas() {
+        let mut canvas = black(2, 2);
+        canvas.text("aaaa", -3, 1, &blocks(), 1, W);
+
+        assert_eq!(canvas.pixels(), &[B, B, W, W]);
+    }
+}
@@ -0,0 +1,239 @@
+//! Per pixel color transformations
+//!
+//! Every function leaves the alpha channel untouched. They are applied to canvases
+//! with the methods of the same name on [`Drawable`], or combined through
+//! [`Drawable::map_pixels`]
+
+use crate::{alpha, rows_mut, Drawable, Rect, ToOlivecCanvas};
+
+/// Applies `f` to the color channels on a scale of 0 to 255, clamping the result
+fn map_rgb(color: u32, f: impl FnOnce([f32; 3]) -> [f32; 3]) -> u32 {
+    let [r, g, b, a] = color.to_le_bytes();
+    let [r, g, b] = f([r, g, b].map(f32::from)).map(|c| c.round().clamp(0.0, 255.0) as u8);
+
+    u32::from_le_bytes([r, g, b, a])
+}
+
+/// Multiplies the color channels by a 3x3 matrix
+fn transform_rgb(color: u32, m: [[f32; 3]; 3]) -> u32 {
+    map_rgb(color, |[r, g, b]| {
+        m.map(|row| row[0] * r + row[1] * g + row[2] * b)
+    })
+}
+
+/// Returns the perceived brightness of the color from 0 to 255, using the Rec. 601
+/// weights
+pub fn color_luma(color: u32) -> f32 {
+    let [r, g, b, _] = color.to_le_bytes().map(f32::from);
+
+    0.299 * r + 0.587 * g + 0.114 * b
+}
+
+pub fn grayscale_color(color: u32) -> u32 {
+    let luma = color_luma(color);
+
+    map_rgb(color, |_| [luma; 3])
+}
+
+pub fn invert_color(color: u32) -> u32 {
+    color ^ 0x00FFFFFF
+}
+
+/// Scales the color channels by `factor`, where 1 leaves the color unchanged and 0
+/// turns it black
+pub fn brightness_color(color: u32, factor: f32) -> u32 {
+    map_rgb(color, |rgb| rgb.map(|c| c * factor))
+}
+
+/// Scales the distance of the color channels from mid gray by `factor`, where 1
+/// leaves the color unchanged and 0 turns it gray
+pub fn contrast_color(color: u32, factor: f32) -> u32 {
+    map_rgb(color, |rgb| rgb.map(|c| (c - 128.0) * factor + 128.0))
+}
+
+/// Scales the distance of the color from its grayscale version by `factor`, where 1
+/// leaves the color unchanged and 0 removes all saturation
+pub fn saturation_color(color: u32, factor: f32) -> u32 {
+    let luma = color_luma(color);
+
+    map_rgb(color, |rgb| rgb.map(|c| luma + (c - luma) * factor))
+}
+
+/// Rotates the hue of the color by `degrees` while roughly preserving its
+/// brightness, with the same matrix as the CSS `hue-rotate` filter
+pub fn hue_rotate_color(color: u32, degrees: f32) -> u32 {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+
+    transform_rgb(
+        color,
+        [
+            [
+                0.213 + cos * 0.787 - sin * 0.213,
+                0.715 - cos * 0.715 - sin * 0.715,
+                0.072 - cos * 0.072 + sin * 0.928,
+            ],
+            [
+                0.213 - cos * 0.213 + sin * 0.143,
+                0.715 + cos * 0.285 + sin * 0.140,
+                0.072 - cos * 0.072 - sin * 0.283,
+            ],
+            [
+                0.213 - cos * 0.213 - sin * 0.787,
+                0.715 - cos * 0.715 + sin * 0.715,
+                0.072 + cos * 0.928 + sin * 0.072,
+            ],
+        ],
+    )
+}
+
+/// Applies gamma correction to the color channels, where values above 1 brighten the
+/// midtones and values below 1 darken them
+pub fn gamma_color(color: u32, gamma: f32) -> u32 {
+    let exponent = 1.0 / gamma;
+
+    map_rgb(color, |rgb| rgb.map(|c| (c / 255.0).powf(exponent) * 255.0))
+}
+
+/// Gives the color a brown, aged tone, with the same matrix as the CSS `sepia` filter
+pub fn sepia_color(color: u32) -> u32 {
+    transform_rgb(
+        color,
+        [
+            [0.393, 0.769, 0.189],
+            [0.349, 0.686, 0.168],
+            [0.272, 0.534, 0.131],
+        ],
+    )
+}
+
+/// Turns the color white if its luma is at least `level`, and black otherwise
+pub fn threshold_color(color: u32, level: u8) -> u32 {
+    let rgb = if color_luma(color) >= level as f32 {
+        0x00FFFFFF
+    } else {
+        0
+    };
+
+    (alpha!(color) as u32) << 24 | rgb
+}
+
+/// Implementation of [`Drawable::map_pixels_in`]
+pub(crate) fn map_pixels_in<T: ToOlivecCanvas>(
+    canvas: &mut T,
+    rect: Rect,
+    f: impl Fn(u32) -> u32 + Sync,
+) {
+    let Some(nr) = canvas.normalize_rect(rect.x, rect.y, rect.w, rect.h) else {
+        return;
+    };
+
+    // Safety: The rectangle was normalized against the bounds of this canvas and
+    // the rows are borrowed mutably through `canvas`
+    let mut rows = unsafe { rows_mut(canvas.get_olivec_canvas(), &nr) };
+    canvas.mark_damage(Rect::from_corners(nr.x1, nr.y1, nr.x2, nr.y2));
+
+    let map_row = |row: &mut &mut [u32]| row.iter_mut().for_each(|pixel| *pixel = f(*pixel));
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        rows.par_iter_mut().for_each(map_row);
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    rows.iter_mut().for_each(map_row);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Canvas;
+
+    const COLOR: u32 = 0x80204080;
+
+    #[test]
+    fn adjustments_keep_the_alpha_channel() {
+        let adjusted = [
+            grayscale_color(COLOR),
+            invert_color(COLOR),
+            brightness_color(COLOR, 2.0),
+            contrast_color(COLOR, 0.5),
+            saturation_color(COLOR, 1.5),
+            hue_rotate_color(COLOR, 90.0),
+            gamma_color(COLOR, 2.2),
+            sepia_color(COLOR),
+            threshold_color(COLOR, 100),
+        ];
+
+        for color in adjusted {
+            assert_eq!(color >> 24, 0x80, "{color:08X}");
+        }
+    }
+
+    #[test]
+    fn neutral_factors_keep_the_color() {
+        assert_eq!(brightness_color(COLOR, 1.0), COLOR);
+        assert_eq!(contrast_color(COLOR, 1.0), COLOR);
+        assert_eq!(saturation_color(COLOR, 1.0), COLOR);
+        assert_eq!(gamma_color(COLOR, 1.0), COLOR);
+        assert_eq!(hue_rotate_color(COLOR, 0.0), COLOR);
+        assert_eq!(invert_color(invert_color(COLOR)), COLOR);
+    }
+
+    #[test]
+    fn extreme_factors_reach_black_and_gray() {
+        assert_eq!(brightness_color(COLOR, 0.0), 0x80000000);
+        assert_eq!(brightness_color(COLOR, 10.0), 0x80FFFFFF);
+        assert_eq!(contrast_color(COLOR, 0.0), 0x80808080);
+        assert_eq!(saturation_color(COLOR, 0.0), grayscale_color(COLOR));
+    }
+
+    #[test]
+    fn grayscale_uses_the_luma_weights() {
+        assert_eq!(color_luma(0xFFFFFFFF), 255.0);
+        assert_eq!(grayscale_color(0xFF0000FF), 0xFF4C4C4C);
+        assert_eq!(grayscale_color(0xFF00FF00), 0xFF969696);
+    }
+
+    #[test]
+    fn threshold_picks_black_or_white() {
+        assert_eq!(threshold_color(0xFF808080, 128), 0xFFFFFFFF);
+        assert_eq!(threshold_color(0xFF7F7F7F, 128), 0xFF000000);
+    }
+
+    #[test]
+    fn map_pixels_in_is_clipped_to_the_rect() {
+        let mut canvas = Canvas::new(4, 3);
+        canvas.track_damage(true);
+
+        canvas.map_pixels_in(Rect::new(2, -1, 5, 2), |_| 1);
+
+        assert_eq!(canvas.pixels(), &[0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(canvas.take_damage(), [Rect::new(2, 0, 2, 1)]);
+
+        canvas.map_pixels_in(Rect::new(10, 10, 2, 2), |_| 2);
+        assert!(canvas.take_damage().is_empty());
+    }
+
+    #[test]
+    fn adjustments_apply_to_subcanvases() {
+        let mut canvas = Canvas::new(3, 1);
+        canvas.fill(0xFF000000);
+
+        canvas.subcanvas(1, 0, 1, 1).unwrap().invert();
+
+        assert_eq!(canvas.pixels(), &[0xFF000000, 0xFFFFFFFF, 0xFF000000]);
+    }
+
+    #[test]
+    fn adjustments_in_a_rect_leave_the_rest_untouched() {
+        let mut canvas = Canvas::new(3, 1);
+        canvas.fill(0xFF000000);
+
+        canvas.invert_in(Rect::new(1, 0, 2, 1));
+        canvas.adjust_brightness_in(Rect::new(2, 0, 1, 1), 0.0);
+
+        assert_eq!(canvas.pixels(), &[0xFF000000, 0xFFFFFFFF, 0xFF000000]);
+    }
+}
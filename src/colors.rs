@@ -73,3 +73,88 @@ macro_rules! rgb {
             | ((0xFF) << (8 * 3))
     };
 }
+
+/// Linearly interpolates each channel of the two packed RGBA colors, where `t = 0`
+/// returns `color1` and `t = 1` returns `color2`
+pub fn lerp_color(color1: u32, color2: u32, t: f32) -> u32 {
+    let t = t.clamp(0.0, 1.0);
+    let channel = |shift: u32| {
+        let a = ((color1 >> shift) & 0xFF) as f32;
+        let b = ((color2 >> shift) & 0xFF) as f32;
+
+        ((a + (b - a) * t).round() as u32) << shift
+    };
+
+    channel(0) | channel(8) | channel(16) | channel(24)
+}
+
+/// Multiplies each channel of the two packed RGBA colors together, treating them
+/// as values between 0 and 1
+pub fn modulate_color(color1: u32, color2: u32) -> u32 {
+    let channel = |shift: u32| {
+        let a = (color1 >> shift) & 0xFF;
+        let b = (color2 >> shift) & 0xFF;
+
+        ((a * b + 127) / 255) << shift
+    };
+
+    channel(0) | channel(8) | channel(16) | channel(24)
+}
+
+/// Parses a color written as `#RRGGBB` or `#RRGGBBAA` into a packed RGBA color
+///
+/// The leading `#` is optional and colors without an alpha component are opaque
+///
+/// ```
+/// use olivec::{parse_hex_color, rgba};
+///
+/// assert_eq!(parse_hex_color("#FF8000"), Some(rgba!(0xFF, 0x80, 0x00, 0xFF)));
+/// assert_eq!(parse_hex_color("10203040"), Some(rgba!(0x10, 0x20, 0x30, 0x40)));
+/// assert_eq!(parse_hex_color("#F80"), None);
+/// assert_eq!(parse_hex_color("#+1+2+3"), None);
+/// ```
+pub fn parse_hex_color(hex: &str) -> Option<u32> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+
+    let channel = |i: usize| u32::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 0xFF };
+
+    Some(rgba!(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+/// Formats a packed RGBA color as `#RRGGBBAA`
+pub fn format_hex_color(color: u32) -> String {
+    format!(
+        "#{:02X}{:02X}{:02X}{:02X}",
+        red!(color),
+        green!(color),
+        blue!(color),
+        alpha!(color)
+    )
+}
+
+/// Unpacks a color into premultiplied floating point channels, so that transparent
+/// pixels don't bleed their color into their neighbours
+pub(crate) fn premultiply_color(color: u32) -> [f32; 4] {
+    let [r, g, b, a] = color.to_le_bytes().map(f32::from);
+    let alpha = a / 255.0;
+
+    [r * alpha, g * alpha, b * alpha, a]
+}
+
+pub(crate) fn unpremultiply_color([r, g, b, a]: [f32; 4]) -> u32 {
+    let a = a.clamp(0.0, 255.0);
+
+    if a == 0.0 {
+        return 0;
+    }
+
+    let scale = 255.0 / a;
+    let channel = |c: f32| (c * scale).round().clamp(0.0, 255.0) as u8;
+
+    u32::from_le_bytes([channel(r), channel(g), channel(b), a.round() as u8])
+}
@@ -0,0 +1,564 @@
+use olivec_sys::Olivec_Canvas;
+
+use crate::{frame_bounds, triangle_bounds, Canvas, Drawable, FixedFont, Rect, ToOlivecCanvas};
+
+/// Width and height of the glyphs of the default font
+const DEFAULT_GLYPH_SIZE: i32 = 6;
+
+/// A single recorded call to one of the drawing functions of [`Drawable`]
+///
+/// Sprites and textures are referenced by their index into a table of canvases
+/// which is provided when the command gets executed. Text is always drawn with
+/// the default font
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum DrawCommand {
+    Fill {
+        color: u32,
+    },
+    Rect {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        color: u32,
+    },
+    Frame {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        thiccness: usize,
+        color: u32,
+    },
+    Circle {
+        cx: i32,
+        cy: i32,
+        r: i32,
+        color: u32,
+    },
+    Ellipse {
+        cx: i32,
+        cy: i32,
+        rx: i32,
+        ry: i32,
+        color: u32,
+    },
+    Line {
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        color: u32,
+    },
+    Triangle {
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        color: u32,
+    },
+    Triangle3c {
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        c1: u32,
+        c2: u32,
+        c3: u32,
+    },
+    Triangle3z {
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        z1: f32,
+        z2: f32,
+        z3: f32,
+    },
+    Triangle3uv {
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        tx1: f32,
+        ty1: f32,
+        tx2: f32,
+        ty2: f32,
+        tx3: f32,
+        ty3: f32,
+        z1: f32,
+        z2: f32,
+        z3: f32,
+        texture: usize,
+        bilinear: bool,
+    },
+    Text {
+        text: String,
+        x: i32,
+        y: i32,
+        size: usize,
+        color: u32,
+    },
+    SpriteBlend {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        sprite: usize,
+    },
+    SpriteCopy {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        sprite: usize,
+        bilinear: bool,
+    },
+}
+
+/// Lets a shared canvas be passed where olive.c takes a sprite or texture, which
+/// it only ever reads from
+struct SpriteRef(Olivec_Canvas);
+
+impl ToOlivecCanvas for SpriteRef {
+    #[inline]
+    fn get_olivec_canvas(&self) -> Olivec_Canvas {
+        self.0
+    }
+}
+
+impl DrawCommand {
+    /// Performs the drawing call on the canvas
+    ///
+    /// Commands referencing a sprite or texture index outside of `sprites` are skipped
+    pub fn execute(&self, canvas: &mut impl Drawable, sprites: &[Canvas]) {
+        let sprite = |index: usize| sprites.get(index).map(|s| SpriteRef(s.get_olivec_canvas()));
+
+        match *self {
+            DrawCommand::Fill { color } => canvas.fill(color),
+            DrawCommand::Rect { x, y, w, h, color } => canvas.rect(x, y, w, h, color),
+            DrawCommand::Frame {
+                x,
+                y,
+                w,
+                h,
+                thiccness,
+                color,
+            } => canvas.frame(x, y, w, h, thiccness, color),
+            DrawCommand::Circle { cx, cy, r, color } => canvas.circle(cx, cy, r, color),
+            DrawCommand::Ellipse {
+                cx,
+                cy,
+                rx,
+                ry,
+                color,
+            } => canvas.ellipse(cx, cy, rx, ry, color),
+            DrawCommand::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+            } => canvas.line(x1, y1, x2, y2, color),
+            DrawCommand::Triangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                color,
+            } => canvas.triangle(x1, y1, x2, y2, x3, y3, color),
+            DrawCommand::Triangle3c {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                c1,
+                c2,
+                c3,
+            } => canvas.triangle3c(x1, y1, x2, y2, x3, y3, c1, c2, c3),
+            DrawCommand::Triangle3z {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                z1,
+                z2,
+                z3,
+            } => canvas.triangle3z(x1, y1, x2, y2, x3, y3, z1, z2, z3),
+            DrawCommand::Triangle3uv {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                tx1,
+                ty1,
+                tx2,
+                ty2,
+                tx3,
+                ty3,
+                z1,
+                z2,
+                z3,
+                texture,
+                bilinear,
+            } => {
+                let Some(mut texture) = sprite(texture) else {
+                    return;
+                };
+
+                if bilinear {
+                    canvas.triangle3uv_bilinear(
+                        x1,
+                        y1,
+                        x2,
+                        y2,
+                        x3,
+                        y3,
+                        tx1,
+                        ty1,
+                        tx2,
+                        ty2,
+                        tx3,
+                        ty3,
+                        z1,
+                        z2,
+                        z3,
+                        &mut texture,
+                    )
+                } else {
+                    canvas.triangle3uv(
+                        x1,
+                        y1,
+                        x2,
+                        y2,
+                        x3,
+                        y3,
+                        tx1,
+                        ty1,
+                        tx2,
+                        ty2,
+                        tx3,
+                        ty3,
+                        z1,
+                        z2,
+                        z3,
+                        &mut texture,
+                    )
+                }
+            }
+            DrawCommand::Text {
+                ref text,
+                x,
+                y,
+                size,
+                color,
+            } => canvas.text(
+                text.as_str(),
+                x,
+                y,
+                &FixedFont::<6, 6>::default(),
+                size,
+                color,
+            ),
+            DrawCommand::SpriteBlend {
+                x,
+                y,
+                w,
+                h,
+                sprite: index,
+            } => {
+                if let Some(mut sprite) = sprite(index) {
+                    canvas.sprite_blend(x, y, w, h, &mut sprite)
+                }
+            }
+            DrawCommand::SpriteCopy {
+                x,
+                y,
+                w,
+                h,
+                sprite: index,
+                bilinear,
+            } => {
+                if let Some(mut sprite) = sprite(index) {
+                    if bilinear {
+                        canvas.sprite_copy_bilinear(x, y, w, h, &mut sprite)
+                    } else {
+                        canvas.sprite_copy(x, y, w, h, &mut sprite)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of the command with all of its coordinates moved by `(dx, dy)`
+    pub fn translated(&self, dx: i32, dy: i32) -> Self {
+        let mut command = self.clone();
+
+        match &mut command {
+            DrawCommand::Fill { .. } => {}
+            DrawCommand::Rect { x, y, .. }
+            | DrawCommand::Frame { x, y, .. }
+            | DrawCommand::Text { x, y, .. }
+            | DrawCommand::SpriteBlend { x, y, .. }
+            | DrawCommand::SpriteCopy { x, y, .. } => {
+                *x += dx;
+                *y += dy;
+            }
+            DrawCommand::Circle { cx, cy, .. } | DrawCommand::Ellipse { cx, cy, .. } => {
+                *cx += dx;
+                *cy += dy;
+            }
+            DrawCommand::Line { x1, y1, x2, y2, .. } => {
+                *x1 += dx;
+                *y1 += dy;
+                *x2 += dx;
+                *y2 += dy;
+            }
+            DrawCommand::Triangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | DrawCommand::Triangle3c {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | DrawCommand::Triangle3z {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | DrawCommand::Triangle3uv {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            } => {
+                *x1 += dx;
+                *y1 += dy;
+                *x2 += dx;
+                *y2 += dy;
+                *x3 += dx;
+                *y3 += dy;
+            }
+        }
+
+        command
+    }
+
+    /// Returns a copy of the command with all of its coordinates and sizes scaled by
+    /// `(sx, sy)` relative to the origin
+    ///
+    /// Circles turn into ellipses when scaled unevenly. Text and frame thickness are
+    /// scaled by the smaller of the two factors, since they can only be scaled uniformly
+    pub fn scaled(&self, sx: f32, sy: f32) -> Self {
+        let x = |v: i32| (v as f32 * sx).round() as i32;
+        let y = |v: i32| (v as f32 * sy).round() as i32;
+        let uniform = |v: usize| ((v as f32 * sx.min(sy)).round() as usize).max(1);
+
+        let mut command = self.clone();
+
+        match &mut command {
+            DrawCommand::Fill { .. } => {}
+            DrawCommand::Rect {
+                x: rx, y: ry, w, h, ..
+            }
+            | DrawCommand::SpriteBlend {
+                x: rx, y: ry, w, h, ..
+            }
+            | DrawCommand::SpriteCopy {
+                x: rx, y: ry, w, h, ..
+            } => {
+                (*rx, *ry, *w, *h) = (x(*rx), y(*ry), x(*w), y(*h));
+            }
+            DrawCommand::Frame {
+                x: rx,
+                y: ry,
+                w,
+                h,
+                thiccness,
+                ..
+            } => {
+                (*rx, *ry, *w, *h) = (x(*rx), y(*ry), x(*w), y(*h));
+                *thiccness = uniform(*thiccness);
+            }
+            DrawCommand::Circle { cx, cy, r, color } => {
+                if sx == sy {
+                    (*cx, *cy, *r) = (x(*cx), y(*cy), x(*r));
+                } else {
+                    command = DrawCommand::Ellipse {
+                        cx: x(*cx),
+                        cy: y(*cy),
+                        rx: x(*r),
+                        ry: y(*r),
+                        color: *color,
+                    };
+                }
+            }
+            DrawCommand::Ellipse { cx, cy, rx, ry, .. } => {
+                (*cx, *cy, *rx, *ry) = (x(*cx), y(*cy), x(*rx), y(*ry));
+            }
+            DrawCommand::Line { x1, y1, x2, y2, .. } => {
+                (*x1, *y1, *x2, *y2) = (x(*x1), y(*y1), x(*x2), y(*y2));
+            }
+            DrawCommand::Triangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | DrawCommand::Triangle3c {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | DrawCommand::Triangle3z {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | DrawCommand::Triangle3uv {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            } => {
+                (*x1, *y1, *x2, *y2) = (x(*x1), y(*y1), x(*x2), y(*y2));
+                (*x3, *y3) = (x(*x3), y(*y3));
+            }
+            DrawCommand::Text {
+                x: tx, y: ty, size, ..
+            } => {
+                (*tx, *ty) = (x(*tx), y(*ty));
+                *size = uniform(*size);
+            }
+        }
+
+        command
+    }
+
+    /// Returns the area of the canvas that the command may touch
+    ///
+    /// [`DrawCommand::Fill`] covers the whole target, whatever its size is, so it
+    /// returns `None`
+    pub fn bounds(&self) -> Option<Rect> {
+        let rect = match *self {
+            DrawCommand::Fill { .. } => return None,
+            DrawCommand::Rect { x, y, w, h, .. }
+            | DrawCommand::SpriteBlend { x, y, w, h, .. }
+            | DrawCommand::SpriteCopy { x, y, w, h, .. } => Rect::from_signed(x, y, w, h),
+            DrawCommand::Frame {
+                x,
+                y,
+                w,
+                h,
+                thiccness,
+                ..
+            } => frame_bounds(x, y, w, h, thiccness),
+            DrawCommand::Circle { cx, cy, r, .. } => {
+                Rect::from_corners(cx - r, cy - r, cx + r, cy + r)
+            }
+            DrawCommand::Ellipse { cx, cy, rx, ry, .. } => {
+                Rect::from_corners(cx - rx, cy - ry, cx + rx, cy + ry)
+            }
+            DrawCommand::Line { x1, y1, x2, y2, .. } => Rect::from_corners(x1, y1, x2, y2),
+            DrawCommand::Triangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | DrawCommand::Triangle3c {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | DrawCommand::Triangle3z {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | DrawCommand::Triangle3uv {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            } => triangle_bounds(x1, y1, x2, y2, x3, y3),
+            DrawCommand::Text {
+                ref text,
+                x,
+                y,
+                size,
+                ..
+            } => {
+                let glyph = DEFAULT_GLYPH_SIZE * size as i32;
+
+                Rect::new(x, y, glyph * text.chars().count() as i32, glyph)
+            }
+        };
+
+        Some(rect)
+    }
+}
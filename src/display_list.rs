@@ -0,0 +1,330 @@
+use crate::{Canvas, DrawCommand, Drawable, Rect, TileRenderer};
+
+/// A recorded sequence of draw commands that can be replayed onto any [`Drawable`]
+///
+/// The recording methods mirror the ones of [`Drawable`], except that sprites are
+/// referenced by their index into the table passed to [`DisplayList::replay`]
+///
+/// ```
+/// use olivec::{Canvas, DisplayList, Rect};
+///
+/// let mut button = DisplayList::new();
+/// button.rect(0, 0, 120, 32, 0xFF303030);
+/// button.frame(0, 0, 120, 32, 2, 0xFFA0A0A0);
+/// button.text("OK", 52, 10, 2, 0xFFFFFFFF);
+///
+/// let mut canvas = Canvas::new(400, 300);
+///
+/// // Draw the same widget twice, once at double size
+/// button.translated(20, 20).replay(&mut canvas, &[]);
+/// button.scaled(2.0, 2.0).translated(20, 80).replay(&mut canvas, &[]);
+///
+/// // The frame is centered on the edges of the rectangle
+/// assert_eq!(button.bounding_box(), Some(Rect::new(-1, -1, 122, 34)));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct DisplayList {
+    pub commands: Vec<DrawCommand>,
+}
+
+impl From<Vec<DrawCommand>> for DisplayList {
+    fn from(commands: Vec<DrawCommand>) -> Self {
+        Self { commands }
+    }
+}
+
+impl DisplayList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Appends all of the commands of another list
+    pub fn extend(&mut self, other: &DisplayList) {
+        self.commands.extend_from_slice(&other.commands);
+    }
+
+    /* Recording */
+
+    pub fn fill(&mut self, color: u32) {
+        self.push(DrawCommand::Fill { color });
+    }
+
+    pub fn rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: u32) {
+        self.push(DrawCommand::Rect { x, y, w, h, color });
+    }
+
+    pub fn frame(&mut self, x: i32, y: i32, w: i32, h: i32, thiccness: usize, color: u32) {
+        self.push(DrawCommand::Frame {
+            x,
+            y,
+            w,
+            h,
+            thiccness,
+            color,
+        });
+    }
+
+    pub fn circle(&mut self, cx: i32, cy: i32, r: i32, color: u32) {
+        self.push(DrawCommand::Circle { cx, cy, r, color });
+    }
+
+    pub fn ellipse(&mut self, cx: i32, cy: i32, rx: i32, ry: i32, color: u32) {
+        self.push(DrawCommand::Ellipse {
+            cx,
+            cy,
+            rx,
+            ry,
+            color,
+        });
+    }
+
+    pub fn line(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, color: u32) {
+        self.push(DrawCommand::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            color,
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn triangle(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, x3: i32, y3: i32, color: u32) {
+        self.push(DrawCommand::Triangle {
+            x1,
+            y1,
+            x2,
+            y2,
+            x3,
+            y3,
+            color,
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn triangle3c(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        c1: u32,
+        c2: u32,
+        c3: u32,
+    ) {
+        self.push(DrawCommand::Triangle3c {
+            x1,
+            y1,
+            x2,
+            y2,
+            x3,
+            y3,
+            c1,
+            c2,
+            c3,
+        });
+    }
+
+    /// Records text drawn with the default font
+    pub fn text(&mut self, text: impl Into<String>, x: i32, y: i32, size: usize, color: u32) {
+        self.push(DrawCommand::Text {
+            text: text.into(),
+            x,
+            y,
+            size,
+            color,
+        });
+    }
+
+    pub fn sprite_blend(&mut self, x: i32, y: i32, w: i32, h: i32, sprite: usize) {
+        self.push(DrawCommand::SpriteBlend { x, y, w, h, sprite });
+    }
+
+    pub fn sprite_copy(&mut self, x: i32, y: i32, w: i32, h: i32, sprite: usize) {
+        self.push(DrawCommand::SpriteCopy {
+            x,
+            y,
+            w,
+            h,
+            sprite,
+            bilinear: false,
+        });
+    }
+
+    pub fn sprite_copy_bilinear(&mut self, x: i32, y: i32, w: i32, h: i32, sprite: usize) {
+        self.push(DrawCommand::SpriteCopy {
+            x,
+            y,
+            w,
+            h,
+            sprite,
+            bilinear: true,
+        });
+    }
+
+    /* Playback */
+
+    /// Executes every command in order, with sprite indices referring to `sprites`
+    pub fn replay(&self, canvas: &mut impl Drawable, sprites: &[Canvas]) {
+        for command in &self.commands {
+            command.execute(canvas, sprites);
+        }
+    }
+
+    /// Same as [`DisplayList::replay`] but splits the canvas into tiles which are
+    /// rendered concurrently
+    pub fn replay_tiled(
+        &self,
+        canvas: &mut impl Drawable,
+        sprites: &[Canvas],
+        renderer: &TileRenderer,
+    ) {
+        renderer.render(canvas, &self.commands, sprites);
+    }
+
+    /* Transformations */
+
+    /// Returns a copy of the list with every command moved by `(dx, dy)`
+    pub fn translated(&self, dx: i32, dy: i32) -> Self {
+        self.commands.iter().map(|c| c.translated(dx, dy)).collect()
+    }
+
+    /// Returns a copy of the list with every command scaled by `(sx, sy)` relative
+    /// to the origin
+    pub fn scaled(&self, sx: f32, sy: f32) -> Self {
+        self.commands.iter().map(|c| c.scaled(sx, sy)).collect()
+    }
+
+    /// Moves every command of the list by `(dx, dy)` in place
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        *self = self.translated(dx, dy);
+    }
+
+    /// Scales every command of the list by `(sx, sy)` in place
+    pub fn scale(&mut self, sx: f32, sy: f32) {
+        *self = self.scaled(sx, sy);
+    }
+
+    /// Returns the smallest rectangle containing every command of the list
+    ///
+    /// Fills cover the whole target regardless of its size, so they are not taken
+    /// into account. Returns `None` if no other command was recorded
+    pub fn bounding_box(&self) -> Option<Rect> {
+        self.commands
+            .iter()
+            .filter_map(DrawCommand::bounds)
+            .reduce(|a, b| a.union(&b))
+    }
+}
+
+impl FromIterator<DrawCommand> for DisplayList {
+    fn from_iter<T: IntoIterator<Item = DrawCommand>>(iter: T) -> Self {
+        Self {
+            commands: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widget() -> DisplayList {
+        let mut list = DisplayList::new();
+        list.rect(0, 0, 10, 4, 0xFF303030);
+        list.circle(5, 2, 2, 0xFF0000FF);
+        list.line(0, 0, 9, 3, 0xFFFFFFFF);
+        list.sprite_blend(2, 1, 4, 2, 0);
+        list
+    }
+
+    #[test]
+    fn translated_lists_draw_the_same_pixels_elsewhere() {
+        let mut sprite = Canvas::new(2, 2);
+        sprite.fill(0x8000FF00);
+        let sprites = [sprite];
+
+        let mut at_origin = Canvas::new(32, 32);
+        widget().replay(&mut at_origin, &sprites);
+
+        let mut moved = Canvas::new(32, 32);
+        widget().translated(7, 11).replay(&mut moved, &sprites);
+
+        for y in 0..20 {
+            for x in 0..20 {
+                assert_eq!(at_origin.get_pixel(x, y), moved.get_pixel(x + 7, y + 11));
+            }
+        }
+    }
+
+    #[test]
+    fn scaling_unevenly_turns_circles_into_ellipses() {
+        let list = widget().scaled(2.0, 3.0);
+
+        assert_eq!(
+            list.commands[0],
+            DrawCommand::Rect {
+                x: 0,
+                y: 0,
+                w: 20,
+                h: 12,
+                color: 0xFF303030
+            }
+        );
+        assert_eq!(
+            list.commands[1],
+            DrawCommand::Ellipse {
+                cx: 10,
+                cy: 6,
+                rx: 4,
+                ry: 6,
+                color: 0xFF0000FF
+            }
+        );
+    }
+
+    #[test]
+    fn bounding_box_ignores_fills() {
+        let mut list = DisplayList::new();
+        assert_eq!(list.bounding_box(), None);
+
+        list.fill(0xFF000000);
+        assert_eq!(list.bounding_box(), None);
+
+        list.rect(10, 10, 5, 5, 0xFFFFFFFF);
+        list.line(-3, 12, 12, 30, 0xFFFFFFFF);
+        assert_eq!(list.bounding_box(), Some(Rect::new(-3, 10, 18, 21)));
+    }
+
+    #[test]
+    fn commands_with_missing_sprites_are_skipped() {
+        let mut list = DisplayList::new();
+        list.sprite_blend(0, 0, 8, 8, 0);
+        list.sprite_copy(0, 0, 8, 8, 3);
+
+        let mut canvas = Canvas::new(8, 8);
+        list.replay(&mut canvas, &[]);
+
+        assert!(canvas.pixels().iter().all(|&pixel| pixel == 0));
+    }
+}
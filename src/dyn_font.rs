@@ -0,0 +1,387 @@
+use std::{borrow::Cow, collections::HashMap, ops::RangeInclusive};
+
+use crate::{FixedFont, Font, Glyph};
+
+/// Largest width or height of the glyphs of fonts loaded from files, so that
+/// malformed fonts can't allocate arbitrarily large glyphs
+pub(crate) const MAX_FONT_CELL_SIZE: usize = 512;
+
+/// A bitmap font whose glyph size is only known at runtime, with glyphs for any set
+/// of characters
+///
+/// Every glyph is a cell of the same size, but each of them can have its own advance
+/// width for proportional fonts. Fonts can be built glyph by glyph, converted from a
+/// [`FixedFont`], or loaded from BDF and PSF files
+///
+/// ```
+/// use olivec::{ Canvas, Drawable, DynFont, FixedFont };
+///
+/// let mut font = DynFont::from(&FixedFont::<6, 6>::default());
+///
+/// // Add a diamond for the Cyrillic letter Zhe
+/// font.insert('Ж', &[
+///     0, 0, 1, 1, 0, 0,
+///     0, 1, 0, 0, 1, 0,
+///     1, 0, 0, 0, 0, 1,
+///     0, 1, 0, 0, 1, 0,
+///     0, 0, 1, 1, 0, 0,
+///     0, 0, 0, 0, 0, 0,
+/// ]);
+///
+/// let mut canvas = Canvas::new(100, 20);
+/// canvas.text("Ж!", 0, 0, &font, 2, 0xFFFFFFFF);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynFont {
+    width: usize,
+    height: usize,
+    ascent: usize,
+    glyphs: HashMap<char, DynGlyph>,
+    fallback: Option<char>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct DynGlyph {
+    /// `width * height` elements, 255 for filled pixels and 0 for empty ones
+    coverage: Vec<u8>,
+    /// Advance width in font pixels
+    advance: usize,
+}
+
+impl DynFont {
+    /// Printable ASCII characters
+    pub const BASIC_LATIN: RangeInclusive<char> = ' '..='~';
+    /// Accented letters and symbols of ISO 8859-1
+    pub const LATIN_1_SUPPLEMENT: RangeInclusive<char> = '\u{A0}'..='\u{FF}';
+    pub const GREEK: RangeInclusive<char> = '\u{370}'..='\u{3FF}';
+    pub const CYRILLIC: RangeInclusive<char> = '\u{400}'..='\u{4FF}';
+    pub const BOX_DRAWING: RangeInclusive<char> = '\u{2500}'..='\u{257F}';
+    pub const BLOCK_ELEMENTS: RangeInclusive<char> = '\u{2580}'..='\u{259F}';
+
+    /// Creates a font without any glyphs, with the baseline at the bottom of the cells
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            ascent: height,
+            glyphs: HashMap::new(),
+            fallback: None,
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    /// Sets the distance from the top of the glyph cells to the baseline
+    pub fn with_ascent(mut self, ascent: usize) -> Self {
+        self.ascent = ascent.min(self.height);
+        self
+    }
+
+    /// Sets the character drawn in place of the ones that don't have a glyph
+    pub fn with_fallback(mut self, fallback: char) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    pub fn fallback(&self) -> Option<char> {
+        self.fallback
+    }
+
+    /// Sets the glyph of a character from its pixels row by row, where every non zero
+    /// element is a filled pixel
+    ///
+    /// Missing pixels are left empty and extra ones are ignored. The glyph advances
+    /// by the width of the font
+    pub fn insert(&mut self, c: char, pixels: &[u8]) {
+        self.insert_with_advance(c, pixels, self.width);
+    }
+
+    /// Same as [`DynFont::insert`] but with the advance width of the glyph in font
+    /// pixels, for proportional fonts
+    pub fn insert_with_advance(&mut self, c: char, pixels: &[u8], advance: usize) {
+        let mut coverage: Vec<u8> = pixels
+            .iter()
+            .take(self.width * self.height)
+            .map(|&pixel| if pixel != 0 { 255 } else { 0 })
+            .collect();
+
+        coverage.resize(self.width * self.height, 0);
+        self.glyphs.insert(c, DynGlyph { coverage, advance });
+    }
+
+    /// Returns the advance width of the glyph of the character in font pixels, or
+    /// `None` if it has no glyph
+    pub fn advance_of(&self, c: char) -> Option<usize> {
+        self.glyphs.get(&c).map(|glyph| glyph.advance)
+    }
+
+    /// Changes the advance width of an existing glyph, returning false if the
+    /// character has no glyph
+    pub fn set_advance(&mut self, c: char, advance: usize) -> bool {
+        self.glyphs
+            .get_mut(&c)
+            .map(|glyph| glyph.advance = advance)
+            .is_some()
+    }
+
+    /// Returns true if every glyph advances by the width of the font
+    pub fn is_monospace(&self) -> bool {
+        self.glyphs
+            .values()
+            .all(|glyph| glyph.advance == self.width)
+    }
+
+    /// Turns the font into a proportional one, where every glyph advances by the
+    /// width of its filled pixels followed by `spacing` empty columns
+    ///
+    /// Empty glyphs such as the space keep their advance
+    ///
+    /// ```
+    /// use olivec::{ DynFont, FixedFont };
+    ///
+    /// let font = DynFont::from(&FixedFont::<6, 6>::default()).proportional(1);
+    ///
+    /// assert!(font.advance_of('i') < font.advance_of('m'));
+    /// ```
+    pub fn proportional(mut self, spacing: usize) -> Self {
+        let width = self.width;
+
+        for glyph in self.glyphs.values_mut() {
+            let used = (0..width).rev().find(|&x| {
+                glyph
+                    .coverage
+                    .iter()
+                    .skip(x)
+                    .step_by(width)
+                    .any(|&p| p != 0)
+            });
+
+            if let Some(x) = used {
+                glyph.advance = x + 1 + spacing;
+            }
+        }
+
+        self
+    }
+
+    pub fn remove(&mut self, c: char) {
+        self.glyphs.remove(&c);
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    /// Returns true if every character of the range has a glyph
+    pub fn covers(&self, range: RangeInclusive<char>) -> bool {
+        range.into_iter().all(|c| self.contains(c))
+    }
+
+    /// Returns the characters with a glyph in ascending order
+    pub fn chars(&self) -> Vec<char> {
+        let mut chars: Vec<char> = self.glyphs.keys().copied().collect();
+        chars.sort_unstable();
+        chars
+    }
+
+    /// Returns a copy of the font with only the glyphs of the characters in the
+    /// ranges, and of the fallback character
+    ///
+    /// Useful to keep large Unicode fonts small in memory
+    ///
+    /// ```no_run
+    /// use olivec::DynFont;
+    ///
+    /// let font = DynFont::load_psf("/usr/share/consolefonts/Lat2-Terminus16.psf")
+    ///     .unwrap()
+    ///     .subset(&[DynFont::BASIC_LATIN, DynFont::BOX_DRAWING]);
+    /// ```
+    pub fn subset(&self, ranges: &[RangeInclusive<char>]) -> DynFont {
+        let glyphs = self
+            .glyphs
+            .iter()
+            .filter(|(&c, _)| Some(c) == self.fallback || ranges.iter().any(|r| r.contains(&c)))
+            .map(|(&c, glyph)| (c, glyph.clone()))
+            .collect();
+
+        DynFont { glyphs, ..*self }
+    }
+
+    /// Returns the number of characters with a glyph
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Returns the pixels of the glyph of the character, with 255 for filled pixels
+    /// and 0 for empty ones
+    pub fn bitmap(&self, c: char) -> Option<&[u8]> {
+        self.glyphs.get(&c).map(|glyph| glyph.coverage.as_slice())
+    }
+
+    /// Returns the pixels of the glyph of the character, or the ones of the fallback
+    /// character if there is none
+    pub fn bitmap_or_fallback(&self, c: char) -> Option<&[u8]> {
+        self.glyph_or_fallback(c)
+            .map(|glyph| glyph.coverage.as_slice())
+    }
+
+    fn glyph_or_fallback(&self, c: char) -> Option<&DynGlyph> {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.fallback.and_then(|f| self.glyphs.get(&f)))
+    }
+}
+
+impl<const W: usize, const H: usize> From<&FixedFont<W, H>> for DynFont {
+    fn from(font: &FixedFont<W, H>) -> Self {
+        let mut dyn_font = DynFont::new(W, H).with_fallback(font.fallback());
+
+        for code in 0..128u8 {
+            let c = code as char;
+
+            if let Some(glyph) = font.glyph(c) {
+                let pixels: Vec<u8> = glyph.iter().flatten().map(|&p| p as u8).collect();
+                dyn_font.insert(c, &pixels);
+            }
+        }
+
+        dyn_font
+    }
+}
+
+impl Font for DynFont {
+    fn glyph(&self, c: char, size: usize) -> Option<Glyph<'_>> {
+        let glyph = self.glyph_or_fallback(c)?;
+
+        Some(Glyph {
+            width: self.width,
+            height: self.height,
+            left: 0,
+            top: 0,
+            advance: (glyph.advance * size) as i32,
+            scale: size,
+            coverage: Cow::Borrowed(&glyph.coverage),
+        })
+    }
+
+    fn line_height(&self, size: usize) -> i32 {
+        (self.height * size) as i32
+    }
+
+    fn ascent(&self, size: usize) -> i32 {
+        (self.ascent * size) as i32
+    }
+
+    fn advance(&self, c: char, size: usize) -> i32 {
+        let advance = self.glyph_or_fallback(c).map_or(self.width, |g| g.advance);
+
+        (advance * size) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3 by 2 font where `l` fills its first column and `-` its top row
+    fn font() -> DynFont {
+        let mut font = DynFont::new(3, 2);
+        font.insert('l', &[1, 0, 0, 1, 0, 0]);
+        font.insert('-', &[1, 1, 1]);
+        font.insert(' ', &[]);
+        font
+    }
+
+    #[test]
+    fn insert_pads_and_truncates_the_pixels() {
+        let mut font = font();
+        font.insert('x', &[1; 10]);
+
+        assert_eq!(font.bitmap('-'), Some(&[255, 255, 255, 0, 0, 0][..]));
+        assert_eq!(font.bitmap('x'), Some(&[255; 6][..]));
+        assert_eq!(font.advance_of('x'), Some(3));
+    }
+
+    #[test]
+    fn proportional_fonts_advance_by_their_filled_columns() {
+        let font = font().proportional(1);
+
+        assert_eq!(font.advance_of('l'), Some(2));
+        assert_eq!(font.advance_of('-'), Some(4));
+        assert_eq!(font.advance_of(' '), Some(3));
+        assert!(!font.is_monospace());
+        assert_eq!(Font::advance(&font, 'l', 2), 4);
+    }
+
+    #[test]
+    fn advances_can_be_changed() {
+        let mut font = font();
+
+        assert!(font.is_monospace());
+        assert!(font.set_advance('l', 1));
+        assert!(!font.set_advance('é', 1));
+        assert_eq!(font.advance_of('l'), Some(1));
+    }
+
+    #[test]
+    fn fallback_is_used_for_missing_characters() {
+        let font = font();
+
+        assert_eq!(font.bitmap_or_fallback('é'), None);
+        assert_eq!(Font::glyph(&font, 'é', 1), None);
+        assert_eq!(Font::advance(&font, 'é', 2), 6);
+
+        let font = font.with_fallback('-');
+
+        assert_eq!(font.bitmap_or_fallback('é'), font.bitmap('-'));
+        let glyph = Font::glyph(&font, 'é', 2).unwrap();
+        assert!(matches!(glyph.coverage, Cow::Borrowed(_)));
+        assert_eq!((glyph.scale, glyph.advance), (2, 6));
+    }
+
+    #[test]
+    fn subset_keeps_the_ranges_and_the_fallback() {
+        let mut font = font().with_fallback('-');
+        font.insert('Ж', &[1]);
+
+        let subset = font.subset(&[DynFont::CYRILLIC]);
+
+        assert_eq!(subset.chars(), ['-', 'Ж']);
+        assert_eq!(subset.get_width(), 3);
+        assert!(subset.covers('Ж'..='Ж'));
+        assert!(!subset.covers(DynFont::CYRILLIC));
+    }
+
+    #[test]
+    fn fixed_fonts_convert_to_the_same_glyphs() {
+        let fixed = FixedFont::<6, 6>::default().with_fallback(' ');
+        let font = DynFont::from(&fixed);
+
+        assert_eq!(font.len(), 128);
+        assert_eq!(font.fallback(), Some(' '));
+        assert!(font.covers(DynFont::BASIC_LATIN));
+
+        for c in ['A', 'g', '?', 'é'] {
+            assert_eq!(Font::glyph(&font, c, 2), Font::glyph(&fixed, c, 2));
+        }
+    }
+
+    #[test]
+    fn ascent_is_clamped_to_the_height() {
+        let font = DynFont::new(4, 8);
+
+        assert_eq!(Font::ascent(&font, 1), 8);
+        assert_eq!(Font::ascent(&font.clone().with_ascent(6), 2), 12);
+        assert_eq!(Font::ascent(&font.with_ascent(20), 1), 8);
+    }
+}
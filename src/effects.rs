@@ -0,0 +1,212 @@
+use crate::{
+    filters::{self, EdgeMode},
+    Canvas, Drawable, Rect,
+};
+
+/// Implementation of [`Drawable::with_shadow`]
+pub(crate) fn draw_with_shadow(
+    canvas: &mut impl Drawable,
+    (dx, dy): (i32, i32),
+    blur_radius: f32,
+    color: u32,
+    draw: impl FnMut(&mut Canvas),
+) {
+    let (width, height) = (canvas.get_width(), canvas.get_height());
+    let mut layer = draw_layer(width, height, draw);
+
+    let Some(bounds) = layer
+        .pixels()
+        .iter()
+        .enumerate()
+        .filter(|(_, &pixel)| pixel >> 24 != 0)
+        .map(|(i, _)| Rect::new((i % width) as i32, (i / width) as i32, 1, 1))
+        .reduce(|a, b| a.union(&b))
+    else {
+        return;
+    };
+
+    // The blur is treated as twice the standard deviation, like CSS does, and the
+    // kernel reaches 3 standard deviations out
+    let sigma = blur_radius.max(0.0) / 2.0;
+    let pad = (sigma * 3.0).ceil() as i32;
+
+    // Tint the alpha of the layer, leaving room around it for the blur to spread
+    let mut shadow = Canvas::new((bounds.w + 2 * pad) as usize, (bounds.h + 2 * pad) as usize);
+    let shadow_alpha = color >> 24;
+
+    for y in 0..bounds.h {
+        for x in 0..bounds.w {
+            let i = (bounds.y + y) as usize * width + (bounds.x + x) as usize;
+            let alpha = shadow_alpha * (layer.pixels()[i] >> 24) / 255;
+
+            shadow.set_pixel(x + pad, y + pad, (color & 0x00FFFFFF) | (alpha << 24));
+        }
+    }
+
+    filters::gaussian_blur(&mut shadow, sigma, EdgeMode::Transparent);
+
+    canvas.sprite_blend(
+        bounds.x - pad + dx,
+        bounds.y - pad + dy,
+        shadow.get_width() as i32,
+        shadow.get_height() as i32,
+        &mut shadow,
+    );
+
+    if let Some(mut drawing) = layer.subcanvas(bounds.x, bounds.y, bounds.w, bounds.h) {
+        canvas.sprite_blend(bounds.x, bounds.y, bounds.w, bounds.h, &mut drawing);
+    }
+}
+
+/// Renders `draw` into a transparent layer
+///
+/// olive.c keeps the alpha of the pixel underneath when blending, so the closure
+/// draws onto opaque black and opaque white instead. How much of the background
+/// shows through a pixel gives its alpha, and the black render its color
+fn draw_layer(width: usize, height: usize, mut draw: impl FnMut(&mut Canvas)) -> Canvas {
+    let mut black = Canvas::new(width, height);
+    black.fill(0xFF000000);
+    draw(&mut black);
+
+    let mut white = Canvas::new(width, height);
+    white.fill(0xFFFFFFFF);
+    draw(&mut white);
+
+    let mut layer = Canvas::new(width, height);
+
+    for ((pixel, &b), &w) in layer
+        .pixels_mut()
+        .iter_mut()
+        .zip(black.pixels())
+        .zip(white.pixels())
+    {
+        *pixel = unblend(b, w);
+    }
+
+    layer
+}
+
+/// Recovers the color that was blended onto opaque black and white to give `black`
+/// and `white`
+fn unblend(black: u32, white: u32) -> u32 {
+    // Pixels that were overwritten rather than blended come out the same
+    if black == white {
+        return black;
+    }
+
+    let channel = |pixel: u32, i: u32| (pixel >> (i * 8)) & 0xFF;
+
+    // The more of the white shows through, the more transparent the pixel is
+    let alpha = (0..3)
+        .map(|i| 255 - channel(white, i).saturating_sub(channel(black, i)))
+        .min()
+        .unwrap_or(0);
+
+    if alpha == 0 {
+        return 0;
+    }
+
+    let color = (0..3)
+        .map(|i| ((channel(black, i) * 255 + alpha / 2) / alpha).min(255) << (i * 8))
+        .fold(0, |color, c| color | c);
+
+    color | (alpha << 24)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: u32 = 0xFFFFFFFF;
+    const BLACK: u32 = 0xFF000000;
+
+    #[test]
+    fn shadow_and_content_are_drawn() {
+        let mut canvas = Canvas::new(16, 16);
+        canvas.fill(0xFFE0E0E0);
+
+        canvas.with_shadow((4, 4), 0.0, BLACK, |layer| {
+            layer.rect(2, 2, 4, 4, WHITE);
+        });
+
+        assert_eq!(canvas.get_pixel(3, 3), Some(WHITE));
+        assert_eq!(canvas.get_pixel(8, 8), Some(BLACK));
+        assert_eq!(canvas.get_pixel(0, 0), Some(0xFFE0E0E0));
+        assert_eq!(canvas.get_pixel(12, 12), Some(0xFFE0E0E0));
+    }
+
+    #[test]
+    fn content_matching_the_background_casts_a_shadow() {
+        let mut canvas = Canvas::new(16, 16);
+        canvas.fill(WHITE);
+
+        canvas.with_shadow((2, 0), 0.0, BLACK, |layer| {
+            layer.rect(4, 4, 4, 4, WHITE);
+        });
+
+        assert_eq!(canvas.get_pixel(5, 5), Some(WHITE));
+        assert_eq!(canvas.get_pixel(9, 5), Some(BLACK));
+    }
+
+    #[test]
+    fn translucent_content_casts_a_fainter_shadow() {
+        let mut canvas = Canvas::new(16, 8);
+        canvas.fill(WHITE);
+
+        canvas.with_shadow((8, 0), 0.0, 0xFF0000FF, |layer| {
+            layer.rect(0, 0, 4, 4, 0x80000000);
+        });
+
+        assert_eq!(canvas.get_pixel(1, 1), Some(0xFF7F7F7F));
+        assert_eq!(canvas.get_pixel(9, 1), Some(0xFF7F7FFF));
+        assert_eq!(canvas.get_pixel(9, 6), Some(WHITE));
+    }
+
+    #[test]
+    fn layers_are_recovered_from_black_and_white_renders() {
+        assert_eq!(unblend(BLACK, WHITE), 0);
+        assert_eq!(unblend(0x80123456, 0x80123456), 0x80123456);
+        assert_eq!(unblend(0xFF402000, 0xFFBF9F7F), 0x80804000);
+    }
+
+    #[test]
+    fn blurred_shadow_fades_out() {
+        let mut canvas = Canvas::new(32, 32);
+        canvas.fill(WHITE);
+
+        canvas.with_glow(6.0, BLACK, |layer| {
+            layer.rect(12, 12, 8, 8, 0xFF0000FF);
+        });
+
+        let red = |x| canvas.get_pixel(x, 16).unwrap() & 0xFF;
+
+        assert_eq!(canvas.get_pixel(16, 16), Some(0xFF0000FF));
+        assert!(red(11) < red(9) && red(9) < red(3));
+        assert_eq!(red(0), 0xFF);
+    }
+
+    #[test]
+    fn drawing_nothing_leaves_the_canvas_untouched() {
+        let mut canvas = Canvas::new(8, 8);
+        canvas.fill(0xFF102030);
+
+        canvas.with_shadow((1, 1), 4.0, BLACK, |_| {});
+
+        assert!(canvas.pixels().iter().all(|&p| p == 0xFF102030));
+    }
+
+    #[test]
+    fn shadow_is_drawn_into_subcanvases() {
+        let mut canvas = Canvas::new(12, 12);
+        canvas.fill(WHITE);
+
+        let mut sub = canvas.subcanvas(4, 4, 8, 8).unwrap();
+        sub.with_shadow((1, 1), 0.0, BLACK, |layer| {
+            layer.rect(0, 0, 2, 2, 0xFF0000FF);
+        });
+
+        assert_eq!(canvas.get_pixel(4, 4), Some(0xFF0000FF));
+        assert_eq!(canvas.get_pixel(6, 6), Some(BLACK));
+        assert_eq!(canvas.get_pixel(3, 3), Some(WHITE));
+    }
+}
@@ -0,0 +1,453 @@
+//! Image filters operating on the pixels of a [`Drawable`]
+//!
+//! Every filter processes the whole canvas it is given, so pass a [`crate::SubCanvas`]
+//! to limit it to a region. Pixels outside of the canvas are provided by the
+//! [`EdgeMode`], which means that filtering a subcanvas never reads the pixels of
+//! its parent around it
+//!
+//! ```
+//! use olivec::{ filters::{self, EdgeMode}, Canvas, Drawable };
+//!
+//! let mut canvas = Canvas::new(200, 200);
+//! canvas.fill(0xFF181818);
+//! canvas.circle(100, 100, 50, 0xFF2020FF);
+//!
+//! // Frosted glass panel over the bottom half
+//! let mut panel = canvas.subcanvas(0, 100, 200, 100).unwrap();
+//! filters::gaussian_blur(&mut panel, 8.0, EdgeMode::Clamp);
+//! ```
+
+use crate::{alpha, color_luma, premultiply_color, unpremultiply_color, Drawable, WrapMode};
+
+/// How pixels outside of the filtered canvas are read
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeMode {
+    /// Uses the nearest pixel on the edge
+    #[default]
+    Clamp,
+    /// Wraps around to the opposite edge
+    Repeat,
+    /// Mirrors the canvas at its edges
+    Mirror,
+    /// Treats every pixel outside as transparent black
+    Transparent,
+}
+
+impl EdgeMode {
+    /// Maps an index onto the range `0..size`, or returns `None` for transparent pixels
+    fn resolve(self, index: i32, size: i32) -> Option<i32> {
+        let wrap = match self {
+            EdgeMode::Clamp => WrapMode::Clamp,
+            EdgeMode::Repeat => WrapMode::Repeat,
+            EdgeMode::Mirror => WrapMode::Mirror,
+            EdgeMode::Transparent => {
+                return (0..size).contains(&index).then_some(index);
+            }
+        };
+
+        Some(wrap.wrap(index, size))
+    }
+}
+
+/// A convolution matrix applied to the color channels of a canvas
+///
+/// The weighted sum of the pixels under the kernel is divided by `divisor` and
+/// offset by `bias`, on a scale of 0 to 255 per channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    width: usize,
+    height: usize,
+    weights: Vec<f32>,
+    pub divisor: f32,
+    pub bias: f32,
+}
+
+impl Kernel {
+    /// Creates a kernel from its weights in row order, centered on the middle element
+    ///
+    /// Returns `None` if the width or height is even or the number of weights
+    /// doesn't match them. The divisor is the sum of the weights, or 1 if they sum
+    /// to zero
+    pub fn new(width: usize, height: usize, weights: Vec<f32>) -> Option<Self> {
+        if width % 2 != 1 || height % 2 != 1 || weights.len() != width * height {
+            return None;
+        }
+
+        let sum: f32 = weights.iter().sum();
+
+        Some(Self {
+            width,
+            height,
+            weights,
+            divisor: if sum == 0.0 { 1.0 } else { sum },
+            bias: 0.0,
+        })
+    }
+
+    pub fn from_3x3(weights: [[f32; 3]; 3]) -> Self {
+        Self::new(3, 3, weights.concat()).unwrap()
+    }
+
+    pub fn with_divisor(mut self, divisor: f32) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
+    pub fn with_bias(mut self, bias: f32) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    pub fn sharpen() -> Self {
+        Self::from_3x3([[0.0, -1.0, 0.0], [-1.0, 5.0, -1.0], [0.0, -1.0, 0.0]])
+    }
+
+    pub fn emboss() -> Self {
+        Self::from_3x3([[-2.0, -1.0, 0.0], [-1.0, 1.0, 1.0], [0.0, 1.0, 2.0]])
+    }
+
+    /// Horizontal gradient, positive where the image gets brighter to the right
+    pub fn sobel_x() -> Self {
+        Self::from_3x3([[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]])
+    }
+
+    /// Vertical gradient, positive where the image gets brighter downwards
+    pub fn sobel_y() -> Self {
+        Self::from_3x3([[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]])
+    }
+}
+
+/// The pixels of a canvas as floating point channels
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Image {
+    fn read(canvas: &impl Drawable, decode: impl Fn(u32) -> [f32; 4]) -> Self {
+        let (width, height) = (canvas.get_width(), canvas.get_height());
+
+        let pixels = (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| (x, y)))
+            .map(|(x, y)| decode(canvas.get_pixel(x, y).unwrap_or(0)))
+            .collect();
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn write(&self, canvas: &mut impl Drawable, encode: impl Fn([f32; 4]) -> u32 + Sync) {
+        canvas.shade(|x, y| encode(self.pixels[y as usize * self.width + x as usize]));
+    }
+
+    fn get(&self, x: i32, y: i32, edges: EdgeMode) -> [f32; 4] {
+        let x = edges.resolve(x, self.width as i32);
+        let y = edges.resolve(y, self.height as i32);
+
+        match (x, y) {
+            (Some(x), Some(y)) => self.pixels[y as usize * self.width + x as usize],
+            _ => [0.0; 4],
+        }
+    }
+
+    /// Convolves the image with a one dimensional kernel of odd length along one axis
+    fn convolve_1d(&self, weights: &[f32], horizontal: bool, edges: EdgeMode) -> Image {
+        let radius = (weights.len() / 2) as i32;
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let mut acc = [0.0; 4];
+
+                for (i, weight) in (-radius..=radius).zip(weights) {
+                    let pixel = if horizontal {
+                        self.get(x + i, y, edges)
+                    } else {
+                        self.get(x, y + i, edges)
+                    };
+
+                    for (a, p) in acc.iter_mut().zip(pixel) {
+                        *a += p * weight;
+                    }
+                }
+
+                pixels.push(acc);
+            }
+        }
+
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+fn is_empty(canvas: &impl Drawable) -> bool {
+    canvas.get_width() == 0 || canvas.get_height() == 0
+}
+
+/// Blurs the canvas by convolving it with the same one dimensional kernel
+/// horizontally and then vertically, with premultiplied alpha
+fn separable_blur(canvas: &mut impl Drawable, weights: &[f32], edges: EdgeMode) {
+    if is_empty(canvas) {
+        return;
+    }
+
+    Image::read(canvas, premultiply_color)
+        .convolve_1d(weights, true, edges)
+        .convolve_1d(weights, false, edges)
+        .write(canvas, unpremultiply_color);
+}
+
+/// Blurs the canvas with a Gaussian of the given standard deviation in pixels
+///
+/// The kernel extends to three standard deviations on each side of a pixel
+pub fn gaussian_blur(canvas: &mut impl Drawable, sigma: f32, edges: EdgeMode) {
+    if sigma <= 0.0 {
+        return;
+    }
+
+    let radius = (sigma * 3.0).ceil() as i32;
+    let mut weights: Vec<f32> = (-radius..=radius)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+
+    let sum: f32 = weights.iter().sum();
+    weights.iter_mut().for_each(|w| *w /= sum);
+
+    separable_blur(canvas, &weights, edges);
+}
+
+/// Replaces every pixel with the average of the square of `2 * radius + 1` pixels
+/// around it
+pub fn box_blur(canvas: &mut impl Drawable, radius: usize, edges: EdgeMode) {
+    if radius == 0 {
+        return;
+    }
+
+    let size = 2 * radius + 1;
+    separable_blur(canvas, &vec![1.0 / size as f32; size], edges);
+}
+
+/// Applies a convolution kernel to the color channels of the canvas, keeping the
+/// alpha of every pixel
+///
+/// ```
+/// use olivec::{ filters::{self, EdgeMode, Kernel}, Canvas };
+///
+/// let mut canvas = Canvas::new(100, 100);
+///
+/// let outline = Kernel::from_3x3([[-1.0, -1.0, -1.0], [-1.0, 8.0, -1.0], [-1.0, -1.0, -1.0]]);
+/// filters::convolve(&mut canvas, &outline, EdgeMode::Clamp);
+/// ```
+pub fn convolve(canvas: &mut impl Drawable, kernel: &Kernel, edges: EdgeMode) {
+    if is_empty(canvas) {
+        return;
+    }
+
+    let image = Image::read(canvas, |c| c.to_le_bytes().map(f32::from));
+    let (rx, ry) = ((kernel.width / 2) as i32, (kernel.height / 2) as i32);
+
+    let mut pixels = Vec::with_capacity(image.pixels.len());
+
+    for y in 0..image.height as i32 {
+        for x in 0..image.width as i32 {
+            let mut acc = [0.0; 3];
+            let mut weights = kernel.weights.iter();
+
+            for ky in -ry..=ry {
+                for kx in -rx..=rx {
+                    let weight = weights.next().unwrap();
+                    let pixel = image.get(x + kx, y + ky, edges);
+
+                    for (a, p) in acc.iter_mut().zip(pixel) {
+                        *a += p * weight;
+                    }
+                }
+            }
+
+            let [r, g, b] = acc.map(|c| c / kernel.divisor + kernel.bias);
+            let alpha = image.pixels[y as usize * image.width + x as usize][3];
+
+            pixels.push([r, g, b, alpha]);
+        }
+    }
+
+    Image { pixels, ..image }.write(canvas, |p| {
+        u32::from_le_bytes(p.map(|c| c.round().clamp(0.0, 255.0) as u8))
+    });
+}
+
+pub fn sharpen(canvas: &mut impl Drawable, edges: EdgeMode) {
+    convolve(canvas, &Kernel::sharpen(), edges);
+}
+
+pub fn emboss(canvas: &mut impl Drawable, edges: EdgeMode) {
+    convolve(canvas, &Kernel::emboss(), edges);
+}
+
+/// Replaces the canvas with the magnitude of its Sobel gradient, computed on the
+/// luminance of the pixels
+///
+/// Edges come out bright on a black background, while the alpha of every pixel
+/// is kept
+pub fn sobel(canvas: &mut impl Drawable, edges: EdgeMode) {
+    if is_empty(canvas) {
+        return;
+    }
+
+    let image = Image::read(canvas, |c| {
+        let luma = color_luma(c);
+
+        [luma, luma, luma, alpha!(c) as f32]
+    });
+
+    let (kx, ky) = (Kernel::sobel_x(), Kernel::sobel_y());
+    let mut pixels = Vec::with_capacity(image.pixels.len());
+
+    for y in 0..image.height as i32 {
+        for x in 0..image.width as i32 {
+            let (mut gx, mut gy) = (0.0, 0.0);
+
+            for (i, (wx, wy)) in kx.weights.iter().zip(&ky.weights).enumerate() {
+                let luma = image.get(x + i as i32 % 3 - 1, y + i as i32 / 3 - 1, edges)[0];
+
+                gx += luma * wx;
+                gy += luma * wy;
+            }
+
+            let magnitude = f32::hypot(gx, gy);
+            let alpha = image.pixels[y as usize * image.width + x as usize][3];
+
+            pixels.push([magnitude, magnitude, magnitude, alpha]);
+        }
+    }
+
+    Image { pixels, ..image }.write(canvas, |p| {
+        u32::from_le_bytes(p.map(|c| c.round().clamp(0.0, 255.0) as u8))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Canvas;
+
+    const WHITE: u32 = 0xFFFFFFFF;
+    const BLACK: u32 = 0xFF000000;
+
+    fn row(pixels: &[u32]) -> Canvas {
+        let mut canvas = Canvas::new(pixels.len(), 1);
+        canvas.shade(|x, _| pixels[x as usize]);
+        canvas
+    }
+
+    fn reds(canvas: &Canvas) -> Vec<u32> {
+        canvas.pixels().iter().map(|&p| p & 0xFF).collect()
+    }
+
+    #[test]
+    fn kernels_must_be_odd_and_complete() {
+        assert!(Kernel::new(2, 3, vec![1.0; 6]).is_none());
+        assert!(Kernel::new(3, 3, vec![1.0; 8]).is_none());
+
+        let kernel = Kernel::new(3, 1, vec![1.0, 2.0, 1.0]).unwrap();
+        assert_eq!(kernel.divisor, 4.0);
+        assert_eq!(Kernel::sobel_x().divisor, 1.0);
+    }
+
+    #[test]
+    fn blurs_keep_uniform_canvases() {
+        let mut canvas = Canvas::new(6, 5);
+        canvas.fill(0xC0804020);
+
+        gaussian_blur(&mut canvas, 1.5, EdgeMode::Clamp);
+        box_blur(&mut canvas, 2, EdgeMode::Mirror);
+        sharpen(&mut canvas, EdgeMode::Repeat);
+
+        assert!(canvas.pixels().iter().all(|&p| p == 0xC0804020));
+    }
+
+    #[test]
+    fn box_blur_reads_outside_pixels_from_the_edge_mode() {
+        let mut clamp = row(&[WHITE, BLACK, BLACK]);
+        box_blur(&mut clamp, 1, EdgeMode::Clamp);
+        assert_eq!(reds(&clamp), [170, 85, 0]);
+
+        let mut repeat = row(&[WHITE, BLACK, BLACK]);
+        box_blur(&mut repeat, 1, EdgeMode::Repeat);
+        assert_eq!(reds(&repeat), [85, 85, 85]);
+    }
+
+    #[test]
+    fn transparent_edges_fade_the_border() {
+        let mut canvas = Canvas::new(3, 3);
+        canvas.fill(0xFF0000FF);
+
+        box_blur(&mut canvas, 1, EdgeMode::Transparent);
+
+        // A corner pixel only has 4 of its 9 neighbours inside the canvas
+        assert_eq!(canvas.pixels()[0], 0x710000FF);
+        assert_eq!(canvas.pixels()[4], 0xFF0000FF);
+    }
+
+    #[test]
+    fn non_positive_blur_sizes_do_nothing() {
+        let mut canvas = row(&[WHITE, BLACK]);
+
+        gaussian_blur(&mut canvas, 0.0, EdgeMode::Clamp);
+        box_blur(&mut canvas, 0, EdgeMode::Clamp);
+
+        assert_eq!(canvas.pixels(), &[WHITE, BLACK]);
+    }
+
+    #[test]
+    fn convolve_applies_the_bias_and_keeps_alpha() {
+        let mut canvas = row(&[0x80102030]);
+        let identity = Kernel::new(1, 1, vec![1.0]).unwrap().with_bias(10.0);
+
+        convolve(&mut canvas, &identity, EdgeMode::Clamp);
+
+        assert_eq!(canvas.pixels(), &[0x801A2A3A]);
+    }
+
+    #[test]
+    fn sobel_highlights_edges() {
+        let mut canvas = Canvas::new(4, 3);
+        canvas.shade(|x, _| if x < 2 { BLACK } else { WHITE });
+
+        sobel(&mut canvas, EdgeMode::Clamp);
+
+        for y in 0..3 {
+            let line = &canvas.pixels()[y * 4..y * 4 + 4];
+            assert_eq!(line, [BLACK, WHITE, WHITE, BLACK]);
+        }
+    }
+
+    #[test]
+    fn filtering_a_subcanvas_ignores_its_parent() {
+        let mut canvas = row(&[WHITE, BLACK, BLACK, BLACK, WHITE]);
+
+        let mut sub = canvas.subcanvas(1, 0, 3, 1).unwrap();
+        box_blur(&mut sub, 1, EdgeMode::Clamp);
+
+        assert_eq!(canvas.pixels(), &[WHITE, BLACK, BLACK, BLACK, WHITE]);
+    }
+}
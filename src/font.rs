@@ -1,31 +1,378 @@
-use olivec_sys::{olivec_default_font, olivec_default_glyphs, size_t, Olivec_Font};
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Display},
+    io,
+};
+
+use olivec_sys::olivec_default_glyphs;
+
+use crate::{alpha, Drawable};
 
 const FONT_GLYPH_COUNT: usize = 128;
 const DEFAULT_FONT_WIDTH: usize = 6;
 const DEFAULT_FONT_HEIGHT: usize = 6;
 
-pub struct Font<const W: usize, const H: usize> {
-    /// Must own the underlying memory that is referenced by the Olivec_Font struct
-    _glyphs: [[[i8; W]; H]; FONT_GLYPH_COUNT],
-    pub(crate) font: Olivec_Font,
+/// Error produced while loading a font
+#[derive(Debug)]
+pub enum FontError {
+    Io(io::Error),
+    /// The font data was malformed or uses an unsupported format
+    Parse(String),
+}
+
+impl Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(err) => write!(f, "{err}"),
+            FontError::Parse(message) => write!(f, "invalid font: {message}"),
+        }
+    }
+}
+
+impl Error for FontError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FontError::Io(err) => Some(err),
+            FontError::Parse(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for FontError {
+    fn from(value: io::Error) -> Self {
+        FontError::Io(value)
+    }
+}
+
+/// The pixels of a single character, positioned relative to the pen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glyph<'a> {
+    pub width: usize,
+    pub height: usize,
+    /// Horizontal distance from the pen to the left edge of the glyph
+    pub left: i32,
+    /// Vertical distance from the top of the line to the top edge of the glyph
+    pub top: i32,
+    /// Horizontal distance the pen moves after the glyph
+    pub advance: i32,
+    /// Size of the square each pixel of the glyph covers on the canvas
+    pub scale: usize,
+    /// Coverage of each pixel of the glyph row by row, from 0 for empty to 255 for
+    /// fully covered
+    pub coverage: Cow<'a, [u8]>,
 }
 
-impl<const W: usize, const H: usize> Font<W, H> {
-    pub fn new(glyphs: [[[i8; W]; H]; FONT_GLYPH_COUNT]) -> Self {
+impl Glyph<'_> {
+    /// Draws the glyph for a pen at `x` on a line whose top is at `y`, blending the
+    /// coverage of its pixels multiplied by the alpha of the color
+    pub fn draw(&self, canvas: &mut impl Drawable, x: i32, y: i32, color: u32) {
+        let scale = self.scale as i32;
+        let (rgb, color_alpha) = (color & 0x00FFFFFF, alpha!(color) as u32);
+
+        for (i, &coverage) in self.coverage.iter().enumerate() {
+            if coverage == 0 {
+                continue;
+            }
+
+            let (dx, dy) = ((i % self.width) as i32, (i / self.width) as i32);
+            let alpha = coverage as u32 * color_alpha / 255;
+
+            canvas.rect(
+                x + self.left + dx * scale,
+                y + self.top + dy * scale,
+                scale,
+                scale,
+                rgb | alpha << 24,
+            );
+        }
+    }
+}
+
+/// A source of glyphs that [`crate::Drawable::text`] can draw with
+///
+/// The meaning of `size` depends on the font: bitmap fonts scale each of their
+/// pixels to a `size` by `size` square, while outline fonts use it as their height
+/// in pixels. All of the returned distances are in canvas pixels
+pub trait Font {
+    /// Returns the glyph drawn for the character, which is the fallback glyph of the
+    /// font if it doesn't support the character, or `None` if nothing gets drawn
+    fn glyph(&self, c: char, size: usize) -> Option<Glyph<'_>>;
+
+    /// Returns the height of a line of text
+    fn line_height(&self, size: usize) -> i32;
+
+    /// Returns the distance from the top of a line to its baseline
+    fn ascent(&self, size: usize) -> i32;
+
+    /// Returns the horizontal distance the pen moves after the character
+    fn advance(&self, c: char, size: usize) -> i32 {
+        self.glyph(c, size).map_or(0, |glyph| glyph.advance)
+    }
+
+    /// Returns the adjustment to the pen position between two consecutive characters
+    fn kerning(&self, _left: char, _right: char, _size: usize) -> i32 {
+        0
+    }
+}
+
+/// A bitmap font with one `W` by `H` glyph for each of the 128 ASCII characters
+#[derive(Clone)]
+pub struct FixedFont<const W: usize, const H: usize> {
+    glyphs: [[[i8; W]; H]; FONT_GLYPH_COUNT],
+    /// The glyphs as [`Glyph::coverage`], so that drawing doesn't convert them
+    coverage: [[[u8; W]; H]; FONT_GLYPH_COUNT],
+    fallback: char,
+}
+
+impl<const W: usize, const H: usize> FixedFont<W, H> {
+    /// Creates a font from its glyphs, indexed by ASCII code, where every non zero
+    /// element is a filled pixel
+    ///
+    /// The fallback glyph defaults to `?`
+    pub const fn new(glyphs: [[[i8; W]; H]; FONT_GLYPH_COUNT]) -> Self {
+        let mut coverage = [[[0; W]; H]; FONT_GLYPH_COUNT];
+        let mut i = 0;
+
+        while i < FONT_GLYPH_COUNT {
+            let mut y = 0;
+
+            while y < H {
+                let mut x = 0;
+
+                while x < W {
+                    if glyphs[i][y][x] != 0 {
+                        coverage[i][y][x] = 255;
+                    }
+
+                    x += 1;
+                }
+
+                y += 1;
+            }
+
+            i += 1;
+        }
+
         Self {
-            _glyphs: glyphs,
-            font: Olivec_Font {
-                width: W as size_t,
-                height: H as size_t,
-                glyphs: glyphs.as_ptr() as *const i8,
-            },
+            glyphs,
+            coverage,
+            fallback: '?',
+        }
+    }
+
+    /// Creates a font from the rows of its glyphs, indexed by ASCII code, where each
+    /// row holds its `W` pixels in its lowest bits with the leftmost pixel first
+    ///
+    /// ```
+    /// use olivec::FixedFont;
+    ///
+    /// let mut rows = [[0u8; 3]; 128];
+    /// rows['+' as usize] = [0b010, 0b111, 0b010];
+    ///
+    /// let font = FixedFont::<3, 3>::from_bitmasks(rows);
+    ///
+    /// assert_eq!(font.glyph('+'), Some(&[[0, 1, 0], [1, 1, 1], [0, 1, 0]]));
+    /// ```
+    pub const fn from_bitmasks(rows: [[u8; H]; FONT_GLYPH_COUNT]) -> Self {
+        assert!(W <= 8, "glyphs made of bitmasks are at most 8 pixels wide");
+
+        let mut glyphs = [[[0; W]; H]; FONT_GLYPH_COUNT];
+        let mut i = 0;
+
+        while i < FONT_GLYPH_COUNT {
+            let mut y = 0;
+
+            while y < H {
+                let mut x = 0;
+
+                while x < W {
+                    glyphs[i][y][x] = ((rows[i][y] >> (W - 1 - x)) & 1) as i8;
+                    x += 1;
+                }
+
+                y += 1;
+            }
+
+            i += 1;
+        }
+
+        Self::new(glyphs)
+    }
+
+    /// Returns the font with every filled pixel also filling the pixel to its right,
+    /// which emulates a bold weight for fonts with an empty column between glyphs
+    pub const fn bold(mut self) -> Self {
+        let mut i = 0;
+
+        while i < FONT_GLYPH_COUNT {
+            let mut y = 0;
+
+            while y < H {
+                // Right to left so that the widened pixels don't widen again
+                let mut x = W;
+
+                while x > 1 {
+                    x -= 1;
+
+                    if self.glyphs[i][y][x - 1] != 0 {
+                        self.glyphs[i][y][x] = 1;
+                    }
+                }
+
+                y += 1;
+            }
+
+            i += 1;
         }
+
+        Self::new(self.glyphs).with_fallback(self.fallback)
+    }
+
+    pub fn default() -> FixedFont<DEFAULT_FONT_WIDTH, DEFAULT_FONT_HEIGHT> {
+        FixedFont::new(unsafe { olivec_default_glyphs })
+    }
+
+    /// Sets the character drawn in place of the ones that don't have a glyph
+    ///
+    /// ```
+    /// use olivec::FixedFont;
+    ///
+    /// let font = FixedFont::<6, 6>::default().with_fallback(' ');
+    ///
+    /// assert_eq!(font.glyph_or_fallback('é'), font.glyph(' '));
+    /// ```
+    pub const fn with_fallback(mut self, fallback: char) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    pub fn fallback(&self) -> char {
+        self.fallback
+    }
+
+    /// Returns the glyph of the character, or `None` if it isn't ASCII
+    pub fn glyph(&self, c: char) -> Option<&[[i8; W]; H]> {
+        self.glyphs.get(c as usize)
+    }
+
+    /// Returns the glyph of the character, or the one of the fallback character if
+    /// there is none
+    pub fn glyph_or_fallback(&self, c: char) -> Option<&[[i8; W]; H]> {
+        self.glyph(c).or_else(|| self.glyph(self.fallback))
+    }
+
+    fn coverage_or_fallback(&self, c: char) -> Option<&[u8]> {
+        self.coverage
+            .get(c as usize)
+            .or_else(|| self.coverage.get(self.fallback as usize))
+            .map(|rows| rows.as_flattened())
+    }
+}
+
+impl<const W: usize, const H: usize> Font for FixedFont<W, H> {
+    fn glyph(&self, c: char, size: usize) -> Option<Glyph<'_>> {
+        Some(Glyph {
+            width: W,
+            height: H,
+            left: 0,
+            top: 0,
+            advance: (W * size) as i32,
+            scale: size,
+            coverage: Cow::Borrowed(self.coverage_or_fallback(c)?),
+        })
+    }
+
+    fn line_height(&self, size: usize) -> i32 {
+        (H * size) as i32
     }
 
-    pub fn default() -> Font<DEFAULT_FONT_WIDTH, DEFAULT_FONT_HEIGHT> {
-        Font {
-            _glyphs: unsafe { olivec_default_glyphs },
-            font: unsafe { olivec_default_font },
+    fn ascent(&self, size: usize) -> i32 {
+        (H * size) as i32
+    }
+
+    fn advance(&self, _c: char, size: usize) -> i32 {
+        (W * size) as i32
+    }
+}
+
+/// Draws the characters side by side with their top at `y`, and returns the position
+/// of the pen after the last one
+pub(crate) fn draw_chars(
+    canvas: &mut impl Drawable,
+    chars: impl Iterator<Item = char>,
+    x: i32,
+    y: i32,
+    font: &(impl Font + ?Sized),
+    size: usize,
+    color: u32,
+) -> i32 {
+    let mut pen = x;
+    let mut previous = None;
+
+    for c in chars {
+        if let Some(previous) = previous {
+            pen += font.kerning(previous, c, size);
         }
+
+        if let Some(glyph) = font.glyph(c, size) {
+            glyph.draw(canvas, pen, y, color);
+        }
+
+        pen += font.advance(c, size);
+        previous = Some(c);
+    }
+
+    pen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plus() -> FixedFont<3, 3> {
+        let mut rows = [[0u8; 3]; FONT_GLYPH_COUNT];
+        rows['+' as usize] = [0b010, 0b111, 0b010];
+        rows['?' as usize] = [0b100, 0b000, 0b000];
+
+        FixedFont::from_bitmasks(rows)
+    }
+
+    #[test]
+    fn glyphs_borrow_their_coverage() {
+        let font = plus();
+        let glyph = Font::glyph(&font, '+', 2).unwrap();
+
+        assert!(matches!(glyph.coverage, Cow::Borrowed(_)));
+        assert_eq!(*glyph.coverage, [0, 255, 0, 255, 255, 255, 0, 255, 0]);
+        assert_eq!(
+            (glyph.width, glyph.height, glyph.scale, glyph.advance),
+            (3, 3, 2, 6)
+        );
+    }
+
+    #[test]
+    fn unsupported_characters_use_the_fallback() {
+        let font = plus();
+        let fallback = Font::glyph(&font, '?', 1).unwrap();
+
+        assert_eq!(Font::glyph(&font, 'é', 1), Some(fallback));
+        assert_eq!(font.glyph('é'), None);
+        assert_eq!(font.glyph_or_fallback('é'), font.glyph('?'));
+
+        let spaced = plus().with_fallback(' ');
+        let blank = Font::glyph(&spaced, 'é', 1).unwrap();
+        assert!(blank.coverage.iter().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn bold_widens_the_coverage_too() {
+        let font = plus().bold().with_fallback('+');
+
+        assert_eq!(font.glyph('?'), Some(&[[1, 1, 0], [0, 0, 0], [0, 0, 0]]));
+        assert_eq!(
+            *Font::glyph(&font, 'é', 1).unwrap().coverage,
+            [0, 255, 255, 255, 255, 255, 0, 255, 255]
+        );
     }
 }
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+
+use crate::{alpha, Canvas, Drawable, Font};
+
+const DEFAULT_ATLAS_SIZE: usize = 256;
+
+/// Position of a pre-rendered glyph in the atlas
+#[derive(Debug, Clone, Copy)]
+struct AtlasGlyph {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    left: i32,
+    top: i32,
+    advance: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: usize,
+    c: char,
+    size: usize,
+    color: u32,
+}
+
+/// Pre-renders glyphs into an atlas canvas the first time they are drawn, so that
+/// drawing them again is a single sprite blend instead of one rect per pixel
+///
+/// Glyphs are cached per font, size and color. The atlas grows as needed, and the
+/// fonts are borrowed for as long as the cache lives so their glyphs stay valid
+///
+/// ```
+/// use olivec::{ Canvas, FixedFont, GlyphCache };
+///
+/// let font = FixedFont::<6, 6>::default();
+/// let mut cache = GlyphCache::new();
+/// let mut canvas = Canvas::new(400, 300);
+///
+/// for frame in 0..60 {
+///     cache.text(&mut canvas, &format!("Frame {frame}"), 10, 10, &font, 2, 0xFFFFFFFF);
+/// }
+///
+/// // The letters of "Frame ", its space and the ten digits
+/// assert_eq!(cache.len(), 16);
+/// ```
+pub struct GlyphCache<'a> {
+    atlas: Canvas,
+    fonts: Vec<&'a dyn Font>,
+    glyphs: HashMap<GlyphKey, AtlasGlyph>,
+    /// Position of the next glyph on the current shelf of the atlas
+    cursor: (i32, i32),
+    shelf_height: i32,
+}
+
+impl Default for GlyphCache<'_> {
+    fn default() -> Self {
+        Self::with_atlas_size(DEFAULT_ATLAS_SIZE, DEFAULT_ATLAS_SIZE)
+    }
+}
+
+impl<'a> GlyphCache<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a cache whose atlas starts with the given size, its height doubles
+    /// whenever it runs out of space
+    pub fn with_atlas_size(width: usize, height: usize) -> Self {
+        Self {
+            atlas: Canvas::new(width.max(1), height.max(1)),
+            fonts: Vec::new(),
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            shelf_height: 0,
+        }
+    }
+
+    /// Returns the canvas holding every pre-rendered glyph
+    pub fn atlas(&self) -> &Canvas {
+        &self.atlas
+    }
+
+    /// Returns the number of cached glyphs
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Removes every cached glyph and font, keeping the memory of the atlas
+    pub fn clear(&mut self) {
+        self.atlas.fill(0);
+        self.fonts.clear();
+        self.glyphs.clear();
+        self.cursor = (0, 0);
+        self.shelf_height = 0;
+    }
+
+    fn font_index(&mut self, font: &'a dyn Font) -> usize {
+        match self.fonts.iter().position(|&f| std::ptr::addr_eq(f, font)) {
+            Some(index) => index,
+            None => {
+                self.fonts.push(font);
+                self.fonts.len() - 1
+            }
+        }
+    }
+
+    /// Reserves a `w` by `h` area of the atlas, placing glyphs on shelves from left
+    /// to right and growing the atlas when it is full
+    fn allocate(&mut self, w: i32, h: i32) -> (i32, i32) {
+        let atlas_width = self.atlas.get_width() as i32;
+
+        if self.cursor.0 + w > atlas_width {
+            self.cursor = (0, self.cursor.1 + self.shelf_height);
+            self.shelf_height = 0;
+        }
+
+        let (mut width, mut height) = (self.atlas.get_width(), self.atlas.get_height());
+
+        while w as usize > width || (self.cursor.1 + h) as usize > height {
+            width = width.max(w as usize);
+            height *= 2;
+        }
+
+        if (width, height) != (self.atlas.get_width(), self.atlas.get_height()) {
+            self.atlas.resize(width, height);
+        }
+
+        let position = self.cursor;
+        self.cursor.0 += w;
+        self.shelf_height = self.shelf_height.max(h);
+
+        position
+    }
+
+    fn glyph(&mut self, font: &'a dyn Font, c: char, size: usize, color: u32) -> AtlasGlyph {
+        let key = GlyphKey {
+            font: self.font_index(font),
+            c,
+            size,
+            color,
+        };
+
+        if let Some(&glyph) = self.glyphs.get(&key) {
+            return glyph;
+        }
+
+        let advance = font.advance(c, size);
+        let Some(glyph) = font.glyph(c, size) else {
+            let empty = AtlasGlyph {
+                x: 0,
+                y: 0,
+                w: 0,
+                h: 0,
+                left: 0,
+                top: 0,
+                advance,
+            };
+
+            self.glyphs.insert(key, empty);
+            return empty;
+        };
+
+        let scale = glyph.scale.max(1);
+        let (w, h) = ((glyph.width * scale) as i32, (glyph.height * scale) as i32);
+        let (x, y) = self.allocate(w, h);
+
+        // Blending onto the transparent atlas would keep it transparent, so the
+        // coverage is written as the alpha of the pixels directly
+        let (rgb, color_alpha) = (color & 0x00FFFFFF, alpha!(color) as u32);
+        let stride = self.atlas.get_width();
+        let pixels = self.atlas.pixels_mut();
+
+        for (i, &coverage) in glyph.coverage.iter().enumerate() {
+            let pixel = rgb | (coverage as u32 * color_alpha / 255) << 24;
+            let (gx, gy) = ((i % glyph.width) * scale, (i / glyph.width) * scale);
+
+            for dy in 0..scale {
+                let row = (y as usize + gy + dy) * stride + x as usize + gx;
+                pixels[row..row + scale].fill(pixel);
+            }
+        }
+
+        let cached = AtlasGlyph {
+            x,
+            y,
+            w,
+            h,
+            left: glyph.left,
+            top: glyph.top,
+            advance,
+        };
+
+        self.glyphs.insert(key, cached);
+        cached
+    }
+
+    /// Draws a line of text the same way as [`Drawable::text`], rendering the glyphs
+    /// into the atlas first if they aren't cached yet
+    ///
+    /// Returns the position of the pen after the last character
+    #[allow(clippy::too_many_arguments)]
+    pub fn text(
+        &mut self,
+        canvas: &mut impl Drawable,
+        text: &str,
+        x: i32,
+        y: i32,
+        font: &'a dyn Font,
+        size: usize,
+        color: u32,
+    ) -> i32 {
+        let mut pen = x;
+        let mut previous = None;
+
+        for c in text.chars() {
+            if let Some(previous) = previous {
+                pen += font.kerning(previous, c, size);
+            }
+
+            let glyph = self.glyph(font, c, size, color);
+
+            if glyph.w > 0 && glyph.h > 0 {
+                if let Some(mut sprite) = self.atlas.subcanvas(glyph.x, glyph.y, glyph.w, glyph.h) {
+                    let (gx, gy) = (pen + glyph.left, y + glyph.top);
+                    canvas.sprite_blend(gx, gy, glyph.w, glyph.h, &mut sprite);
+                }
+            }
+
+            pen += glyph.advance;
+            previous = Some(c);
+        }
+
+        pen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FixedFont, FONT_8X8};
+
+    const W: u32 = 0xFFFFFFFF;
+    const B: u32 = 0xFF000000;
+
+    fn canvas() -> Canvas {
+        let mut canvas = Canvas::new(120, 40);
+        canvas.fill(B);
+        canvas
+    }
+
+    #[test]
+    fn draws_the_same_as_text() {
+        let font = FixedFont::<6, 6>::default();
+        let mut cache = GlyphCache::new();
+
+        let mut cached = canvas();
+        let pen = cache.text(&mut cached, "Hello, 42!", 3, 5, &font, 2, 0xFF40C0FF);
+
+        let mut expected = canvas();
+        expected.text("Hello, 42!", 3, 5, &font, 2, 0xFF40C0FF);
+
+        assert_eq!(pen, 3 + 10 * 12);
+        assert_eq!(cached.pixels(), expected.pixels());
+    }
+
+    #[test]
+    fn glyphs_are_cached_per_font_size_and_color() {
+        let font = FixedFont::<6, 6>::default();
+        let mut cache = GlyphCache::new();
+        let mut target = canvas();
+
+        assert!(cache.is_empty());
+
+        cache.text(&mut target, "abba", 0, 0, &font, 1, W);
+        assert_eq!(cache.len(), 2);
+
+        cache.text(&mut target, "ab", 0, 0, &font, 1, W);
+        assert_eq!(cache.len(), 2);
+
+        cache.text(&mut target, "a", 0, 0, &font, 2, W);
+        cache.text(&mut target, "a", 0, 0, &font, 1, 0xFF0000FF);
+        cache.text(&mut target, "a", 0, 0, &FONT_8X8, 1, W);
+        assert_eq!(cache.len(), 5);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(cache.atlas().pixels().iter().all(|&p| p == 0));
+    }
+
+    #[test]
+    fn atlas_grows_to_fit_the_glyphs() {
+        let mut cache = GlyphCache::with_atlas_size(16, 16);
+        let text = "abcdefghijklmnopqrstuvwxyz";
+
+        let mut cached = Canvas::new(26 * 24, 24);
+        cached.fill(B);
+        cache.text(&mut cached, text, 0, 0, &FONT_8X8, 3, W);
+
+        assert_eq!(cache.len(), 26);
+        assert_eq!(cache.atlas().get_width(), 24);
+        assert!(cache.atlas().get_height() >= 26 * 24);
+
+        let mut expected = Canvas::new(26 * 24, 24);
+        expected.fill(B);
+        expected.text(text, 0, 0, &FONT_8X8, 3, W);
+
+        assert_eq!(cached.pixels(), expected.pixels());
+    }
+}
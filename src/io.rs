@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use image::{ImageResult, RgbaImage};
+
+use crate::{Canvas, Drawable};
+
+impl Canvas {
+    /// Loads an image file of any format supported by the `image` crate into a new Canvas
+    pub fn load_image(path: impl AsRef<Path>) -> ImageResult<Self> {
+        Ok(Self::from_image(&image::open(path)?.into_rgba8()))
+    }
+
+    /// Creates a new Canvas with a copy of the pixels in the provided image
+    pub fn from_image(image: &RgbaImage) -> Self {
+        let pixels = image
+            .as_raw()
+            .chunks_exact(4)
+            .map(|p| u32::from_le_bytes([p[0], p[1], p[2], p[3]]))
+            .collect();
+
+        Self::from_buffer(pixels, image.width() as usize, image.height() as usize).unwrap()
+    }
+
+    /// Saves the canvas to disk, with the format picked from the file extension
+    pub fn save_image(&self, path: impl AsRef<Path>) -> ImageResult<()> {
+        image::save_buffer(
+            path,
+            self.get_data(),
+            self.get_width() as u32,
+            self.get_height() as u32,
+            image::ColorType::Rgba8,
+        )
+    }
+}
@@ -1,9 +1,61 @@
+mod bdf;
+mod builtin_fonts;
+mod camera;
 mod canvas;
+mod color_ops;
 mod colors;
+mod command;
+mod display_list;
+mod dyn_font;
+mod effects;
+pub mod filters;
 mod font;
+mod glyph_cache;
+#[cfg(feature = "image")]
+mod io;
+mod lighting;
+mod math;
+mod mesh;
+mod orientation;
+mod pipeline;
+mod psf;
+mod resample;
+mod rich_text;
+#[cfg(feature = "serde")]
+mod scene;
+mod shader;
+mod text_layout;
+mod text_style;
+mod texture;
+mod tiles;
+#[cfg(feature = "truetype")]
+mod truetype;
 mod util;
 
+pub use builtin_fonts::*;
+pub use camera::*;
 pub use canvas::*;
+pub use color_ops::*;
 pub use colors::*;
+pub use command::*;
+pub use display_list::*;
+pub use dyn_font::*;
 pub use font::*;
+pub use glyph_cache::*;
+pub use lighting::*;
+pub use math::*;
+pub use mesh::*;
+pub use orientation::*;
+pub use pipeline::*;
+pub use resample::*;
+pub use rich_text::*;
+#[cfg(feature = "serde")]
+pub use scene::*;
+pub use shader::*;
+pub use text_layout::*;
+pub use text_style::*;
+pub use texture::*;
+pub use tiles::*;
+#[cfg(feature = "truetype")]
+pub use truetype::*;
 pub use util::*;
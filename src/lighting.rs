@@ -0,0 +1,453 @@
+use crate::{
+    alpha, blue, green, modulate_color, red, rgba, Drawable, FilterMode, Pipeline, Sampler,
+    ScreenVertex, ToOlivecCanvas, Vec3, Vertex, WrapMode,
+};
+
+/// Converts a packed RGBA color into an RGB vector with components between 0 and 1
+pub fn color_to_vec3(color: u32) -> Vec3 {
+    Vec3::new(
+        red!(color) as f32 / 255.0,
+        green!(color) as f32 / 255.0,
+        blue!(color) as f32 / 255.0,
+    )
+}
+
+/// Converts an RGB vector back into a packed RGBA color, clamping each component
+/// between 0 and 1
+pub fn vec3_to_color(rgb: Vec3, alpha: u8) -> u32 {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u32;
+
+    rgba!(channel(rgb.x), channel(rgb.y), channel(rgb.z), alpha as u32)
+}
+
+/// A light source in world space
+///
+/// Light colors are stored premultiplied by their intensity, so components may
+/// be larger than 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Light {
+    /// Light reaching every surface equally regardless of its orientation
+    Ambient { color: Vec3 },
+    /// Light arriving from infinitely far away, travelling along `direction`
+    Directional { direction: Vec3, color: Vec3 },
+    /// Light emitted from `position` in every direction, fading out completely
+    /// at a distance of `range`
+    Point {
+        position: Vec3,
+        color: Vec3,
+        range: f32,
+    },
+}
+
+impl Light {
+    pub fn ambient(color: u32, intensity: f32) -> Self {
+        Light::Ambient {
+            color: color_to_vec3(color) * intensity,
+        }
+    }
+
+    pub fn directional(direction: Vec3, color: u32, intensity: f32) -> Self {
+        Light::Directional {
+            direction: direction.normalize(),
+            color: color_to_vec3(color) * intensity,
+        }
+    }
+
+    pub fn point(position: Vec3, color: u32, intensity: f32, range: f32) -> Self {
+        Light::Point {
+            position,
+            color: color_to_vec3(color) * intensity,
+            range,
+        }
+    }
+}
+
+/// How a surface responds to light
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Surface {
+    /// Diffuse (Lambert) color of the surface
+    pub albedo: u32,
+    /// Strength of the Blinn-Phong specular highlight, 0 disables it
+    pub specular: f32,
+    /// Blinn-Phong exponent, larger values make the highlight smaller and sharper
+    pub shininess: f32,
+}
+
+impl Surface {
+    /// Creates a matte surface with no specular highlight
+    pub fn matte(albedo: u32) -> Self {
+        Self {
+            albedo,
+            specular: 0.0,
+            shininess: 1.0,
+        }
+    }
+
+    /// Creates a surface with a white Blinn-Phong highlight
+    pub fn glossy(albedo: u32, specular: f32, shininess: f32) -> Self {
+        Self {
+            albedo,
+            specular,
+            shininess,
+        }
+    }
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Self::matte(0xFFFFFFFF)
+    }
+}
+
+/// Whether lighting is evaluated once per triangle or at each of its vertices
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ShadingMode {
+    /// One color per face, computed at the centroid with the face normal
+    Flat,
+    /// Colors computed per vertex and interpolated across the face by `triangle3c`
+    #[default]
+    Gouraud,
+}
+
+/// The set of lights illuminating a scene
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Lighting {
+    pub lights: Vec<Light>,
+}
+
+impl Lighting {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_light(mut self, light: Light) -> Self {
+        self.lights.push(light);
+        self
+    }
+
+    /// Sums the diffuse and specular light arriving at a point
+    ///
+    /// `eye` is the position of the viewer, used for the specular term. Returns
+    /// the `(diffuse, specular)` light colors
+    pub fn illuminate(
+        &self,
+        position: Vec3,
+        normal: Vec3,
+        eye: Vec3,
+        surface: &Surface,
+    ) -> (Vec3, Vec3) {
+        let normal = normal.normalize();
+        let view = (eye - position).normalize();
+
+        let mut diffuse = Vec3::ZERO;
+        let mut specular = Vec3::ZERO;
+
+        for light in &self.lights {
+            // Direction towards the light along with its attenuated color
+            let (to_light, color) = match *light {
+                Light::Ambient { color } => {
+                    diffuse += color;
+                    continue;
+                }
+                Light::Directional { direction, color } => (-direction, color),
+                Light::Point {
+                    position: light_position,
+                    color,
+                    range,
+                } => {
+                    let offset = light_position - position;
+                    let falloff = (1.0 - offset.length() / range).clamp(0.0, 1.0);
+
+                    (offset.normalize(), color * (falloff * falloff))
+                }
+            };
+
+            let lambert = normal.dot(to_light);
+
+            if lambert <= 0.0 {
+                continue;
+            }
+
+            diffuse += color * lambert;
+
+            if surface.specular > 0.0 {
+                let half = (to_light + view).normalize();
+                let highlight = normal.dot(half).max(0.0).powf(surface.shininess);
+
+                specular += color * (highlight * surface.specular);
+            }
+        }
+
+        (diffuse, specular)
+    }
+
+    /// Computes the lit color of a surface at a point
+    pub fn shade(&self, position: Vec3, normal: Vec3, eye: Vec3, surface: &Surface) -> u32 {
+        let (diffuse, specular) = self.illuminate(position, normal, eye, surface);
+        let rgb = color_to_vec3(surface.albedo) * diffuse + specular;
+
+        vec3_to_color(rgb, alpha!(surface.albedo))
+    }
+
+    /// Computes the lit color of each vertex of a world space triangle
+    ///
+    /// With [`ShadingMode::Flat`] all three colors are the same
+    pub fn shade_triangle(
+        &self,
+        positions: &[Vec3; 3],
+        normals: &[Vec3; 3],
+        eye: Vec3,
+        surface: &Surface,
+        mode: ShadingMode,
+    ) -> [u32; 3] {
+        match mode {
+            ShadingMode::Flat => {
+                let [a, b, c] = *positions;
+                let centroid = (a + b + c) / 3.0;
+                let face_normal = (b - a).cross(c - a);
+
+                [self.shade(centroid, face_normal, eye, surface); 3]
+            }
+            ShadingMode::Gouraud => {
+                [0, 1, 2].map(|i| self.shade(positions[i], normals[i], eye, surface))
+            }
+        }
+    }
+}
+
+impl Pipeline {
+    /// Transforms the triangle positions and normals into world space
+    fn world_space(&self, triangle: &[Vertex; 3], normals: &[Vec3; 3]) -> ([Vec3; 3], [Vec3; 3]) {
+        let normal_matrix = self.model.normal_matrix();
+
+        (
+            triangle.map(|v| self.model.transform_point(v.position).truncate()),
+            normals.map(|n| normal_matrix.transform_vector(n)),
+        )
+    }
+
+    /// Draws a lit triangle through `triangle3c`
+    ///
+    /// `normals` are the model space normals of each vertex, the vertex colors of
+    /// `triangle` are ignored
+    pub fn draw_triangle_lit(
+        &self,
+        canvas: &mut impl Drawable,
+        triangle: &[Vertex; 3],
+        normals: &[Vec3; 3],
+        lighting: &Lighting,
+        surface: &Surface,
+        mode: ShadingMode,
+    ) {
+        let (positions, normals) = self.world_space(triangle, normals);
+        let colors =
+            lighting.shade_triangle(&positions, &normals, self.camera.position, surface, mode);
+
+        let mut lit = *triangle;
+        for (vertex, color) in lit.iter_mut().zip(colors) {
+            vertex.color = color;
+        }
+
+        self.draw_triangle(canvas, &lit);
+    }
+
+    /// Draws a textured triangle with each texel multiplied by the light intensity
+    /// interpolated between the vertices
+    ///
+    /// The texture takes the place of the surface albedo and is sampled the same way
+    /// as `triangle3uv`, with perspective correction and the nearest texel. Light
+    /// intensities are clamped to 1, so lighting can only darken the texture. The lit
+    /// texels are blended onto the canvas
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_triangle_lit_textured(
+        &self,
+        canvas: &mut impl Drawable,
+        triangle: &[Vertex; 3],
+        normals: &[Vec3; 3],
+        lighting: &Lighting,
+        surface: &Surface,
+        mode: ShadingMode,
+        texture: &mut impl ToOlivecCanvas,
+    ) {
+        let (positions, normals) = self.world_space(triangle, normals);
+        let white = Surface {
+            albedo: 0xFFFFFFFF,
+            ..*surface
+        };
+        let intensities =
+            lighting.shade_triangle(&positions, &normals, self.camera.position, &white, mode);
+
+        let mut lit = *triangle;
+        for (vertex, intensity) in lit.iter_mut().zip(intensities) {
+            vertex.color = intensity;
+        }
+
+        let (width, height) = (canvas.get_width(), canvas.get_height());
+        let sampler = Sampler::new(WrapMode::Clamp, FilterMode::Nearest);
+        let texture = &*texture;
+
+        for [a, b, c] in self.project(width, height, &lit) {
+            // Texture coordinates are divided by `w` to interpolate them perspective
+            // correctly, the intensities are interpolated linearly like `triangle3c`
+            let point = |v: &ScreenVertex| {
+                let (x, y) = v.pixel();
+                (x, y, (v.uv * v.inv_w, v.inv_w, color_to_vec3(v.color)))
+            };
+
+            canvas.triangle_shaded(point(&a), point(&b), point(&c), |fragment| {
+                let (uv, inv_w, intensity) = fragment.varying;
+                let texel = sampler.sample(texture, uv / inv_w);
+
+                modulate_color(texel, vec3_to_color(intensity, 0xFF))
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Camera, Canvas, Mat4, Vec2};
+
+    /// Counter-clockwise when seen from the camera of [`pipeline`], covering the
+    /// center of the canvas
+    fn triangle() -> [Vertex; 3] {
+        [
+            Vertex::new(Vec3::new(-1.0, -1.0, 0.0)).with_uv(Vec2::new(0.0, 1.0)),
+            Vertex::new(Vec3::new(1.0, -1.0, 0.0)).with_uv(Vec2::new(1.0, 1.0)),
+            Vertex::new(Vec3::new(0.0, 1.0, 0.0)).with_uv(Vec2::new(0.5, 0.0)),
+        ]
+    }
+
+    fn lit_textured(lighting: &Lighting) -> Canvas {
+        let mut canvas = Canvas::new(20, 20);
+        canvas.fill(0xFF00FF00);
+
+        let mut texture = Canvas::new(2, 2);
+        texture.fill(0xFF8080FF);
+
+        pipeline().draw_triangle_lit_textured(
+            &mut canvas,
+            &triangle(),
+            &[Vec3::Z; 3],
+            lighting,
+            &Surface::default(),
+            ShadingMode::Gouraud,
+            &mut texture,
+        );
+
+        canvas
+    }
+
+    #[test]
+    fn illuminate_follows_the_angle_of_the_light() {
+        let lighting = Lighting::new()
+            .with_light(Light::ambient(0xFFFFFFFF, 0.25))
+            .with_light(Light::directional(-Vec3::Y, 0xFFFFFFFF, 1.0));
+        let surface = Surface::default();
+        let eye = Vec3::new(0.0, 5.0, 0.0);
+
+        let (up, _) = lighting.illuminate(Vec3::ZERO, Vec3::Y, eye, &surface);
+        let (away, _) = lighting.illuminate(Vec3::ZERO, -Vec3::Y, eye, &surface);
+
+        assert_eq!(up, Vec3::ONE * 1.25);
+        assert_eq!(away, Vec3::ONE * 0.25);
+    }
+
+    #[test]
+    fn point_lights_fade_out_at_their_range() {
+        let lighting = Lighting::new().with_light(Light::point(
+            Vec3::new(0.0, 4.0, 0.0),
+            0xFFFFFFFF,
+            1.0,
+            8.0,
+        ));
+        let surface = Surface::default();
+
+        let (diffuse, _) = lighting.illuminate(Vec3::ZERO, Vec3::Y, Vec3::Y, &surface);
+        assert_eq!(diffuse, Vec3::ONE * 0.25);
+
+        let (diffuse, _) =
+            lighting.illuminate(Vec3::new(0.0, -5.0, 0.0), Vec3::Y, Vec3::Y, &surface);
+        assert_eq!(diffuse, Vec3::ZERO);
+    }
+
+    #[test]
+    fn shade_clamps_and_keeps_the_albedo_alpha() {
+        let lighting = Lighting::new().with_light(Light::ambient(0xFFFFFFFF, 3.0));
+        let surface = Surface::matte(0x80204080);
+
+        assert_eq!(
+            lighting.shade(Vec3::ZERO, Vec3::Y, Vec3::Y, &surface),
+            0x8060C0FF
+        );
+    }
+
+    #[test]
+    fn flat_shading_uses_one_color_per_face() {
+        let lighting = Lighting::new().with_light(Light::directional(-Vec3::Z, 0xFFFFFFFF, 1.0));
+        let positions = triangle().map(|v| v.position);
+        let normals = [Vec3::Z, Vec3::X, -Vec3::Z];
+        let surface = Surface::default();
+
+        let flat =
+            lighting.shade_triangle(&positions, &normals, Vec3::Z, &surface, ShadingMode::Flat);
+        let gouraud = lighting.shade_triangle(
+            &positions,
+            &normals,
+            Vec3::Z,
+            &surface,
+            ShadingMode::Gouraud,
+        );
+
+        assert_eq!(flat, [0xFFFFFFFF; 3]);
+        assert_eq!(gouraud, [0xFFFFFFFF, 0xFF000000, 0xFF000000]);
+    }
+
+    #[test]
+    fn lit_textured_triangles_are_opaque() {
+        let canvas = lit_textured(&Lighting::new().with_light(Light::ambient(0xFFFFFFFF, 1.0)));
+
+        assert_eq!(canvas.get_pixel(10, 12), Some(0xFF8080FF));
+        assert_eq!(canvas.get_pixel(0, 0), Some(0xFF00FF00));
+    }
+
+    #[test]
+    fn unlit_textured_triangles_are_black() {
+        let canvas = lit_textured(&Lighting::new());
+
+        assert_eq!(canvas.get_pixel(10, 12), Some(0xFF000000));
+    }
+
+    #[test]
+    fn lit_textured_triangles_are_darkened_by_the_light() {
+        let canvas = lit_textured(&Lighting::new().with_light(Light::ambient(0xFFFFFFFF, 0.5)));
+
+        assert_eq!(canvas.get_pixel(10, 12), Some(0xFF404080));
+    }
+
+    fn pipeline() -> Pipeline {
+        let camera =
+            Camera::orthographic(2.0, 0.1, 10.0).look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO);
+
+        Pipeline::new(camera)
+    }
+
+    #[test]
+    fn world_space_normals_stay_perpendicular_under_non_uniform_scale() {
+        let pipeline = pipeline().with_model(Mat4::scaling(Vec3::new(4.0, 1.0, 1.0)));
+
+        // A triangle in the plane x + y = 1
+        let triangle = [
+            Vertex::new(Vec3::new(1.0, 0.0, 0.0)),
+            Vertex::new(Vec3::new(0.0, 1.0, 0.0)),
+            Vertex::new(Vec3::new(1.0, 0.0, 1.0)),
+        ];
+        let normal = Vec3::new(1.0, 1.0, 0.0);
+
+        let ([a, b, c], [n, _, _]) = pipeline.world_space(&triangle, &[normal; 3]);
+
+        assert!(n.dot(b - a).abs() < 1e-5);
+        assert!(n.dot(c - a).abs() < 1e-5);
+    }
+}
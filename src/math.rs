@@ -0,0 +1,517 @@
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// A 2D vector, mostly used for texture coordinates
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A 3D vector used for positions, directions and normals
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// A 4D vector used for homogeneous (clip space) coordinates
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Linearly interpolates between `self` and `other` where `t = 0` returns `self`
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Vec3 {
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0);
+    pub const ONE: Self = Self::new(1.0, 1.0, 1.0);
+    pub const X: Self = Self::new(1.0, 0.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0);
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns a vector pointing in the same direction with a length of 1
+    ///
+    /// The zero vector is returned unchanged
+    pub fn normalize(self) -> Self {
+        let len = self.length();
+
+        if len == 0.0 {
+            self
+        } else {
+            self / len
+        }
+    }
+
+    /// Linearly interpolates between `self` and `other` where `t = 0` returns `self`
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    /// Extends the vector into homogeneous coordinates with the provided `w`
+    pub fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+}
+
+impl Vec4 {
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    /// Linearly interpolates between `self` and `other` where `t = 0` returns `self`
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    /// Drops the `w` component without performing the perspective divide
+    pub fn truncate(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+macro_rules! impl_vec_ops {
+    ($type:ident, $($field:ident),+) => {
+        impl Add for $type {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl AddAssign for $type {
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$field += rhs.$field;)+
+            }
+        }
+
+        impl Sub for $type {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        impl SubAssign for $type {
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$field -= rhs.$field;)+
+            }
+        }
+
+        impl Mul<f32> for $type {
+            type Output = Self;
+
+            fn mul(self, rhs: f32) -> Self {
+                Self { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl Mul for $type {
+            type Output = Self;
+
+            /// Component-wise multiplication
+            fn mul(self, rhs: Self) -> Self {
+                Self { $($field: self.$field * rhs.$field),+ }
+            }
+        }
+
+        impl Div<f32> for $type {
+            type Output = Self;
+
+            fn div(self, rhs: f32) -> Self {
+                Self { $($field: self.$field / rhs),+ }
+            }
+        }
+
+        impl Neg for $type {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+    };
+}
+
+impl_vec_ops!(Vec2, x, y);
+impl_vec_ops!(Vec3, x, y, z);
+impl_vec_ops!(Vec4, x, y, z, w);
+
+/// A 4x4 matrix stored in row-major order which transforms column vectors
+///
+/// Transformations compose right to left, so `a * b` applies `b` first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub rows: [[f32; 4]; 4],
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat4 {
+    pub const IDENTITY: Self = Self {
+        rows: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub const fn from_rows(rows: [[f32; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        Self::from_rows([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scaling(scale: Vec3) -> Self {
+        Self::from_rows([
+            [scale.x, 0.0, 0.0, 0.0],
+            [0.0, scale.y, 0.0, 0.0],
+            [0.0, 0.0, scale.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Rotation of `angle` radians around the x axis
+    pub fn rotation_x(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+
+        Self::from_rows([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, c, -s, 0.0],
+            [0.0, s, c, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Rotation of `angle` radians around the y axis
+    pub fn rotation_y(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+
+        Self::from_rows([
+            [c, 0.0, s, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [-s, 0.0, c, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Rotation of `angle` radians around the z axis
+    pub fn rotation_z(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+
+        Self::from_rows([
+            [c, -s, 0.0, 0.0],
+            [s, c, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Right handed view matrix looking from `eye` towards `target`
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let f = (target - eye).normalize();
+        let s = f.cross(up).normalize();
+        let u = s.cross(f);
+
+        Self::from_rows([
+            [s.x, s.y, s.z, -s.dot(eye)],
+            [u.x, u.y, u.z, -u.dot(eye)],
+            [-f.x, -f.y, -f.z, f.dot(eye)],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Right handed perspective projection which maps the view frustum into the
+    /// `[-1, 1]` clip volume
+    ///
+    /// `fov_y` is the vertical field of view in radians
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        let nf = 1.0 / (near - far);
+
+        Self::from_rows([
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, f, 0.0, 0.0],
+            [0.0, 0.0, (far + near) * nf, 2.0 * far * near * nf],
+            [0.0, 0.0, -1.0, 0.0],
+        ])
+    }
+
+    /// Right handed orthographic projection of the box bounded by the provided planes
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let rl = 1.0 / (right - left);
+        let tb = 1.0 / (top - bottom);
+        let fnr = 1.0 / (far - near);
+
+        Self::from_rows([
+            [2.0 * rl, 0.0, 0.0, -(right + left) * rl],
+            [0.0, 2.0 * tb, 0.0, -(top + bottom) * tb],
+            [0.0, 0.0, -2.0 * fnr, -(far + near) * fnr],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transpose(self) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+
+        Self::from_rows(rows)
+    }
+
+    /// Returns the inverse of the matrix, or `None` if it is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let mut rows = self.rows;
+        let mut inverse = Self::IDENTITY.rows;
+
+        // Gauss-Jordan elimination, applying every row operation to the identity too
+        for col in 0..4 {
+            // Pivoting on the largest value keeps the elimination numerically stable
+            let pivot = (col..4)
+                .max_by(|&a, &b| rows[a][col].abs().total_cmp(&rows[b][col].abs()))
+                .unwrap_or(col);
+
+            if rows[pivot][col] == 0.0 {
+                return None;
+            }
+
+            rows.swap(col, pivot);
+            inverse.swap(col, pivot);
+
+            let scale = 1.0 / rows[col][col];
+            rows[col] = rows[col].map(|value| value * scale);
+            inverse[col] = inverse[col].map(|value| value * scale);
+
+            for row in (0..4).filter(|&row| row != col) {
+                let factor = rows[row][col];
+
+                for k in 0..4 {
+                    rows[row][k] -= factor * rows[col][k];
+                    inverse[row][k] -= factor * inverse[col][k];
+                }
+            }
+        }
+
+        Some(Self::from_rows(inverse))
+    }
+
+    /// Returns the matrix transforming normals, the inverse transpose of this one
+    ///
+    /// Unlike the matrix itself, it keeps normals perpendicular to their surface under
+    /// non-uniform scaling. Singular matrices are returned unchanged
+    pub fn normal_matrix(&self) -> Self {
+        self.inverse().map_or(*self, Self::transpose)
+    }
+
+    /// Transforms a point, treating it as having a `w` of 1
+    pub fn transform_point(&self, point: Vec3) -> Vec4 {
+        *self * point.extend(1.0)
+    }
+
+    /// Transforms a direction, treating it as having a `w` of 0 so that
+    /// translations do not apply
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        (*self * vector.extend(0.0)).truncate()
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.rows[i][k] * rhs.rows[k][j]).sum();
+            }
+        }
+
+        Self::from_rows(rows)
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    fn mul(self, rhs: Vec4) -> Vec4 {
+        let row = |[x, y, z, w]: [f32; 4]| Vec4::new(x, y, z, w);
+
+        Vec4::new(
+            row(self.rows[0]).dot(rhs),
+            row(self.rows[1]).dot(rhs),
+            row(self.rows[2]).dot(rhs),
+            row(self.rows[3]).dot(rhs),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn cross_follows_the_right_hand_rule() {
+        assert_eq!(Vec3::X.cross(Vec3::Y), Vec3::Z);
+        assert_eq!(Vec3::Y.cross(Vec3::X), -Vec3::Z);
+    }
+
+    #[test]
+    fn normalize_keeps_the_zero_vector() {
+        assert_eq!(Vec3::ZERO.normalize(), Vec3::ZERO);
+        assert_close(
+            Vec3::new(3.0, 0.0, 4.0).normalize(),
+            Vec3::new(0.6, 0.0, 0.8),
+        );
+    }
+
+    #[test]
+    fn matrices_compose_right_to_left() {
+        let m = Mat4::translation(Vec3::X) * Mat4::scaling(Vec3::new(2.0, 2.0, 2.0));
+
+        assert_close(
+            m.transform_point(Vec3::ONE).truncate(),
+            Vec3::new(3.0, 2.0, 2.0),
+        );
+        assert_eq!(m * Mat4::IDENTITY, m);
+    }
+
+    #[test]
+    fn vectors_ignore_translation() {
+        let m = Mat4::translation(Vec3::new(5.0, 6.0, 7.0));
+
+        assert_eq!(m.transform_vector(Vec3::Y), Vec3::Y);
+    }
+
+    #[test]
+    fn rotations_turn_counter_clockwise() {
+        let m = Mat4::rotation_z(std::f32::consts::FRAC_PI_2);
+
+        assert_close(m.transform_vector(Vec3::X), Vec3::Y);
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0))
+            * Mat4::rotation_y(0.7)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, 4.0));
+        let inverse = m.inverse().unwrap();
+        let p = Vec3::new(-3.0, 5.0, 0.25);
+
+        assert_close(
+            inverse
+                .transform_point(m.transform_point(p).truncate())
+                .truncate(),
+            p,
+        );
+        assert_eq!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse(), None);
+    }
+
+    #[test]
+    fn normal_matrix_keeps_normals_perpendicular() {
+        let m = Mat4::scaling(Vec3::new(4.0, 1.0, 1.0));
+
+        // A surface along the (1, -1) diagonal before scaling
+        let tangent = m.transform_vector(Vec3::new(1.0, -1.0, 0.0));
+        let normal = m.normal_matrix().transform_vector(Vec3::new(1.0, 1.0, 0.0));
+
+        assert!(tangent.dot(normal).abs() < 1e-5);
+        assert!(
+            tangent
+                .dot(m.transform_vector(Vec3::new(1.0, 1.0, 0.0)))
+                .abs()
+                > 1.0
+        );
+    }
+
+    #[test]
+    fn perspective_maps_near_and_far_planes_to_the_clip_volume() {
+        let m = Mat4::perspective(90f32.to_radians(), 1.0, 1.0, 10.0);
+
+        let near = m.transform_point(Vec3::new(0.0, 0.0, -1.0));
+        let far = m.transform_point(Vec3::new(0.0, 0.0, -10.0));
+
+        assert!((near.z / near.w + 1.0).abs() < 1e-5);
+        assert!((far.z / far.w - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn look_at_moves_the_target_onto_the_negative_z_axis() {
+        let view = Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+
+        assert_close(
+            view.transform_point(Vec3::ZERO).truncate(),
+            Vec3::new(0.0, 0.0, -5.0),
+        );
+    }
+}
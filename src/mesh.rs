@@ -0,0 +1,778 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+    str::SplitWhitespace,
+};
+
+use crate::{
+    modulate_color, rgba, Camera, Canvas, DepthBuffer, Drawable, Pipeline, ScreenVertex,
+    ToOlivecCanvas, Vec2, Vec3, Vertex,
+};
+
+/// Error produced while loading Wavefront OBJ and MTL files
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    /// The file contents were malformed at the given (1-based) line
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "{err}"),
+            ObjError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl Error for ObjError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ObjError::Io(err) => Some(err),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ObjError {
+    fn from(value: io::Error) -> Self {
+        ObjError::Io(value)
+    }
+}
+
+/// Surface properties of a mesh, parsed from an MTL file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// Diffuse color (`Kd` and `d`) as a packed RGBA color
+    pub diffuse: u32,
+    /// Path of the diffuse texture map (`map_Kd`), relative paths are resolved
+    /// against the directory of the MTL file
+    pub diffuse_texture: Option<PathBuf>,
+}
+
+impl Material {
+    /// Creates a plain white material
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            diffuse: 0xFFFFFFFF,
+            diffuse_texture: None,
+        }
+    }
+
+    /// Parses the materials defined in the contents of an MTL file
+    ///
+    /// Texture paths are resolved against `base_dir`
+    pub fn parse_mtl(source: &str, base_dir: &Path) -> Result<Vec<Material>, ObjError> {
+        let mut materials: Vec<Material> = Vec::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let line_no = i + 1;
+            let mut tokens = line.split_whitespace();
+
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+
+            if keyword == "newmtl" {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                materials.push(Material::new(name));
+                continue;
+            }
+
+            if keyword.starts_with('#') {
+                continue;
+            }
+
+            let Some(material) = materials.last_mut() else {
+                continue;
+            };
+
+            match keyword {
+                "Kd" => {
+                    let [r, g, b] = parse_floats::<3>(&mut tokens, line_no)?;
+                    let alpha = material.diffuse >> 24;
+
+                    material.diffuse = rgba!(to_channel(r), to_channel(g), to_channel(b), alpha);
+                }
+                "d" => {
+                    let [d] = parse_floats::<1>(&mut tokens, line_no)?;
+                    material.diffuse = (material.diffuse & 0x00FFFFFF) | (to_channel(d) << 24);
+                }
+                "map_Kd" => {
+                    // Options such as `-s 1 1 1` may precede the file name
+                    let file = tokens.last().ok_or_else(|| ObjError::Parse {
+                        line: line_no,
+                        message: "missing texture path".into(),
+                    })?;
+
+                    material.diffuse_texture = Some(base_dir.join(file));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(materials)
+    }
+
+    /// Loads the diffuse texture of the material, if it has one
+    #[cfg(feature = "image")]
+    pub fn load_diffuse_texture(&self) -> Option<image::ImageResult<Canvas>> {
+        self.diffuse_texture.as_ref().map(Canvas::load_image)
+    }
+}
+
+/// A corner of a mesh triangle, made up of indices into the attribute arrays of the mesh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeshIndex {
+    pub position: usize,
+    pub uv: Option<usize>,
+    pub normal: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeshTriangle {
+    pub indices: [MeshIndex; 3],
+    /// Index into [`Mesh::materials`]
+    pub material: Option<usize>,
+}
+
+/// An indexed triangle mesh
+///
+/// Polygons with more than three corners are triangulated as fans when loaded.
+/// Texture coordinates are flipped vertically on load so that `v` grows downwards,
+/// matching the row order of a [`Canvas`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<Vec2>,
+    pub triangles: Vec<MeshTriangle>,
+    pub materials: Vec<Material>,
+}
+
+impl Mesh {
+    /// Loads a Wavefront OBJ file along with the MTL libraries it references
+    pub fn load_obj(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+        let path = path.as_ref();
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+
+        let (mut mesh, libraries) = Self::parse(&fs::read_to_string(path)?)?;
+
+        for library in libraries {
+            let library = base_dir.join(library);
+            let library_dir = library.parent().unwrap_or(Path::new(""));
+
+            for loaded in Material::parse_mtl(&fs::read_to_string(&library)?, library_dir)? {
+                if let Some(material) = mesh.materials.iter_mut().find(|m| m.name == loaded.name) {
+                    *material = loaded;
+                }
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    /// Parses the contents of an OBJ file
+    ///
+    /// Referenced MTL libraries are not loaded, so every material used by the mesh
+    /// is left with its default properties
+    pub fn parse_obj(source: &str) -> Result<Self, ObjError> {
+        Self::parse(source).map(|(mesh, _)| mesh)
+    }
+
+    fn parse(source: &str) -> Result<(Self, Vec<String>), ObjError> {
+        let mut mesh = Mesh::default();
+        let mut libraries = Vec::new();
+        let mut material = None;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_no = i + 1;
+            let mut tokens = line.split_whitespace();
+
+            match tokens.next() {
+                Some("v") => {
+                    let [x, y, z] = parse_floats::<3>(&mut tokens, line_no)?;
+                    mesh.positions.push(Vec3::new(x, y, z));
+                }
+                Some("vn") => {
+                    let [x, y, z] = parse_floats::<3>(&mut tokens, line_no)?;
+                    mesh.normals.push(Vec3::new(x, y, z));
+                }
+                Some("vt") => {
+                    let [u] = parse_floats::<1>(&mut tokens, line_no)?;
+                    let v = match tokens.next() {
+                        Some(token) => parse_float(token, line_no)?,
+                        None => 0.0,
+                    };
+
+                    mesh.uvs.push(Vec2::new(u, 1.0 - v));
+                }
+                Some("f") => {
+                    let corners = tokens
+                        .map(|token| mesh.parse_index(token, line_no))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    if corners.len() < 3 {
+                        return Err(ObjError::Parse {
+                            line: line_no,
+                            message: "face has fewer than 3 vertices".into(),
+                        });
+                    }
+
+                    for i in 1..corners.len() - 1 {
+                        mesh.triangles.push(MeshTriangle {
+                            indices: [corners[0], corners[i], corners[i + 1]],
+                            material,
+                        });
+                    }
+                }
+                Some("usemtl") => {
+                    let name = tokens.collect::<Vec<_>>().join(" ");
+
+                    material = Some(match mesh.materials.iter().position(|m| m.name == name) {
+                        Some(index) => index,
+                        None => {
+                            mesh.materials.push(Material::new(name));
+                            mesh.materials.len() - 1
+                        }
+                    });
+                }
+                Some("mtllib") => libraries.extend(tokens.map(String::from)),
+                _ => {}
+            }
+        }
+
+        Ok((mesh, libraries))
+    }
+
+    /// Parses a face corner in the `v`, `v/vt`, `v//vn` or `v/vt/vn` form
+    fn parse_index(&self, token: &str, line: usize) -> Result<MeshIndex, ObjError> {
+        let mut parts = token.split('/');
+
+        let mut resolve = |count: usize| -> Result<Option<usize>, ObjError> {
+            match parts.next() {
+                None | Some("") => Ok(None),
+                Some(part) => {
+                    let index: isize = part.parse().map_err(|_| ObjError::Parse {
+                        line,
+                        message: format!("invalid index `{part}`"),
+                    })?;
+
+                    // Negative indices are relative to the end of the list so far
+                    let resolved = match index {
+                        1.. => index - 1,
+                        ..=-1 => count as isize + index,
+                        0 => -1,
+                    };
+
+                    if resolved < 0 || resolved as usize >= count {
+                        return Err(ObjError::Parse {
+                            line,
+                            message: format!("index `{part}` out of range"),
+                        });
+                    }
+
+                    Ok(Some(resolved as usize))
+                }
+            }
+        };
+
+        let position = resolve(self.positions.len())?.ok_or_else(|| ObjError::Parse {
+            line,
+            message: "missing position index".into(),
+        })?;
+
+        Ok(MeshIndex {
+            position,
+            uv: resolve(self.uvs.len())?,
+            normal: resolve(self.normals.len())?,
+        })
+    }
+
+    /// Builds the pipeline vertices of a triangle, colored by its material
+    pub fn vertices(&self, triangle: &MeshTriangle) -> [Vertex; 3] {
+        let color = triangle
+            .material
+            .map_or(0xFFFFFFFF, |m| self.materials[m].diffuse);
+
+        triangle.indices.map(|index| {
+            Vertex::new(self.positions[index.position])
+                .with_uv(index.uv.map_or(Vec2::ZERO, |uv| self.uvs[uv]))
+                .with_color(color)
+        })
+    }
+
+    /// Returns the normal of each corner of the triangle, falling back to the face
+    /// normal for corners that don't reference one
+    pub fn normals(&self, triangle: &MeshTriangle) -> [Vec3; 3] {
+        let [a, b, c] = triangle.indices.map(|i| self.positions[i.position]);
+        let face_normal = (b - a).cross(c - a).normalize();
+
+        triangle
+            .indices
+            .map(|i| i.normal.map_or(face_normal, |n| self.normals[n]))
+    }
+
+    /// Returns the minimum and maximum corners of the axis aligned bounding box
+    /// of the mesh, or `None` if it has no vertices
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let first = *self.positions.first()?;
+
+        Some(self.positions.iter().fold((first, first), |(min, max), p| {
+            (
+                Vec3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                Vec3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+            )
+        }))
+    }
+}
+
+fn parse_float(token: &str, line: usize) -> Result<f32, ObjError> {
+    token.parse().map_err(|_| ObjError::Parse {
+        line,
+        message: format!("invalid number `{token}`"),
+    })
+}
+
+fn parse_floats<const N: usize>(
+    tokens: &mut SplitWhitespace,
+    line: usize,
+) -> Result<[f32; N], ObjError> {
+    let mut values = [0.0; N];
+
+    for value in values.iter_mut() {
+        let token = tokens.next().ok_or_else(|| ObjError::Parse {
+            line,
+            message: format!("expected {N} numbers"),
+        })?;
+
+        *value = parse_float(token, line)?;
+    }
+
+    Ok(values)
+}
+
+fn to_channel(value: f32) -> u32 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u32
+}
+
+/// Draws textured meshes with depth testing
+///
+/// olive.c has no depth buffer of its own, so every triangle is rasterized into
+/// offscreen canvases with `triangle3z` and `triangle3uv_bilinear`, and only the
+/// pixels that pass the depth test are blended onto the target. The depth buffer is
+/// kept between draws so that several meshes occlude each other correctly
+pub struct MeshRenderer {
+    pub pipeline: Pipeline,
+    depth: DepthBuffer,
+    color_scratch: Canvas,
+    depth_scratch: Canvas,
+    /// Diffuse textures of materials, keyed by their path
+    textures: HashMap<PathBuf, Canvas>,
+}
+
+impl MeshRenderer {
+    pub fn new(pipeline: Pipeline) -> Self {
+        Self {
+            pipeline,
+            depth: DepthBuffer::new(0, 0),
+            color_scratch: Canvas::new(0, 0),
+            depth_scratch: Canvas::new(0, 0),
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Sets the texture drawn on the triangles whose material has `path` as its
+    /// diffuse texture
+    pub fn insert_texture(&mut self, path: impl Into<PathBuf>, texture: Canvas) {
+        self.textures.insert(path.into(), texture);
+    }
+
+    /// Loads the diffuse textures of the materials of the mesh that aren't loaded yet
+    #[cfg(feature = "image")]
+    pub fn load_textures(&mut self, mesh: &Mesh) -> image::ImageResult<()> {
+        for material in &mesh.materials {
+            if let Some(path) = &material.diffuse_texture {
+                if !self.textures.contains_key(path) {
+                    self.insert_texture(path.clone(), Canvas::load_image(path)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Clears the depth buffer, usually at the start of every frame
+    pub fn clear_depth(&mut self) {
+        self.depth.clear();
+    }
+
+    /// Draws every triangle of the mesh, tinted by the diffuse color of its material
+    ///
+    /// Triangles are textured with the diffuse texture of their material once it is
+    /// loaded into the renderer, and with the provided texture otherwise
+    pub fn draw(
+        &mut self,
+        canvas: &mut impl Drawable,
+        mesh: &Mesh,
+        texture: &mut impl ToOlivecCanvas,
+    ) {
+        let (width, height) = (canvas.get_width(), canvas.get_height());
+        let mvp = self.pipeline.mvp(width, height);
+
+        self.resize(width, height);
+
+        // Taken out of the renderer so that they can be borrowed while rasterizing
+        let mut textures = std::mem::take(&mut self.textures);
+
+        for triangle in &mesh.triangles {
+            let material = triangle.material.map(|m| &mesh.materials[m]);
+            let diffuse = material.map_or(0xFFFFFFFF, |m| m.diffuse);
+            let mut loaded = material
+                .and_then(|m| m.diffuse_texture.as_ref())
+                .and_then(|path| textures.get_mut(path));
+
+            let vertices = mesh.vertices(triangle);
+
+            for screen in self.pipeline.project_with(&mvp, width, height, &vertices) {
+                match loaded.as_deref_mut() {
+                    Some(loaded) => self.rasterize(canvas, &screen, loaded, diffuse),
+                    None => self.rasterize(canvas, &screen, texture, diffuse),
+                }
+            }
+        }
+
+        self.textures = textures;
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        if self.depth.get_width() != width || self.depth.get_height() != height {
+            self.depth = DepthBuffer::new(width, height);
+            self.color_scratch = Canvas::new(width, height);
+            self.depth_scratch = Canvas::new(width, height);
+        }
+    }
+
+    fn rasterize(
+        &mut self,
+        canvas: &mut impl Drawable,
+        [a, b, c]: &[ScreenVertex; 3],
+        texture: &mut impl ToOlivecCanvas,
+        diffuse: u32,
+    ) {
+        let ((x1, y1), (x2, y2), (x3, y3)) = (a.pixel(), b.pixel(), c.pixel());
+
+        let lx = x1.min(x2).min(x3).max(0);
+        let hx = x1.max(x2).max(x3).min(canvas.get_width() as i32 - 1);
+        let ly = y1.min(y2).min(y3).max(0);
+        let hy = y1.max(y2).max(y3).min(canvas.get_height() as i32 - 1);
+
+        let Some(mut region) = self
+            .depth_scratch
+            .subcanvas(lx, ly, hx - lx + 1, hy - ly + 1)
+        else {
+            return;
+        };
+
+        // A zeroed pixel marks that the triangle did not cover it. Depths are encoded
+        // as `2 - depth` which is always positive inside the clip volume
+        region.fill(0);
+
+        self.depth_scratch.triangle3z(
+            x1,
+            y1,
+            x2,
+            y2,
+            x3,
+            y3,
+            2.0 - a.depth,
+            2.0 - b.depth,
+            2.0 - c.depth,
+        );
+
+        self.color_scratch.triangle3uv_bilinear(
+            x1,
+            y1,
+            x2,
+            y2,
+            x3,
+            y3,
+            a.uv.x * a.inv_w,
+            a.uv.y * a.inv_w,
+            b.uv.x * b.inv_w,
+            b.uv.y * b.inv_w,
+            c.uv.x * c.inv_w,
+            c.uv.y * c.inv_w,
+            a.inv_w,
+            b.inv_w,
+            c.inv_w,
+            texture,
+        );
+
+        for y in ly..=hy {
+            for x in lx..=hx {
+                let bits = self.depth_scratch.get_pixel(x, y).unwrap_or(0);
+
+                if bits == 0 {
+                    continue;
+                }
+
+                let depth = 2.0 - f32::from_bits(bits);
+
+                if self.depth.test_and_set(x as usize, y as usize, depth) {
+                    if let Some(color) = self.color_scratch.get_pixel(x, y) {
+                        canvas.blend_pixel(x, y, modulate_color(color, diffuse));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draws a textured mesh as seen from the camera, with back faces culled and
+/// depth testing between its triangles
+///
+/// Triangles are tinted by the diffuse color of their material. Use a
+/// [`MeshRenderer`] directly to draw the diffuse textures of the materials, to draw
+/// several meshes into a shared depth buffer or to apply a model transform
+pub fn draw_mesh(
+    canvas: &mut impl Drawable,
+    mesh: &Mesh,
+    camera: &Camera,
+    texture: &mut impl ToOlivecCanvas,
+) {
+    MeshRenderer::new(Pipeline::new(*camera)).draw(canvas, mesh, texture);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mat4;
+
+    const QUAD: &str = "
+# A unit quad with texture coordinates
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+usemtl red
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+    #[test]
+    fn parse_obj_triangulates_polygons_as_fans() {
+        let mesh = Mesh::parse_obj(QUAD).unwrap();
+
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.triangles.len(), 2);
+
+        let corners = |t: &MeshTriangle| t.indices.map(|i| i.position);
+        assert_eq!(corners(&mesh.triangles[0]), [0, 1, 2]);
+        assert_eq!(corners(&mesh.triangles[1]), [0, 2, 3]);
+        assert_eq!(mesh.triangles[0].indices[1].uv, Some(1));
+        assert_eq!(mesh.triangles[0].indices[1].normal, Some(0));
+    }
+
+    #[test]
+    fn parse_obj_flips_texture_coordinates() {
+        let mesh = Mesh::parse_obj("vt 0.25 0.75\nvt 0.5").unwrap();
+
+        assert_eq!(mesh.uvs, vec![Vec2::new(0.25, 0.25), Vec2::new(0.5, 1.0)]);
+    }
+
+    #[test]
+    fn parse_obj_accepts_every_index_form() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\nf 1 2//1 3/1";
+        let mesh = Mesh::parse_obj(source).unwrap();
+        let [a, b, c] = mesh.triangles[0].indices;
+
+        assert_eq!((a.uv, a.normal), (None, None));
+        assert_eq!((b.uv, b.normal), (None, Some(0)));
+        assert_eq!((c.uv, c.normal), (Some(0), None));
+    }
+
+    #[test]
+    fn parse_obj_resolves_negative_indices_from_the_end() {
+        let mesh = Mesh::parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1").unwrap();
+
+        assert_eq!(mesh.triangles[0].indices.map(|i| i.position), [0, 1, 2]);
+    }
+
+    #[test]
+    fn parse_obj_assigns_materials_by_name() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\nusemtl a\nf 1 2 3\nusemtl b\nf 1 2 3\nusemtl a\nf 1 2 3";
+        let mesh = Mesh::parse_obj(source).unwrap();
+
+        let materials: Vec<_> = mesh.triangles.iter().map(|t| t.material).collect();
+        assert_eq!(materials, vec![None, Some(0), Some(1), Some(0)]);
+        assert_eq!(mesh.materials.len(), 2);
+    }
+
+    #[test]
+    fn parse_obj_reports_the_line_of_errors() {
+        let errors = [
+            ("v 0 0 0\nv 1 0\n", 2),
+            ("v 0 0 0\nv 1 0 x\n", 2),
+            ("v 0 0 0\nv 1 0 0\n\nf 1 2\n", 4),
+            ("v 0 0 0\nf 1 2 3\n", 2),
+            ("v 0 0 0\nf 0 1 1\n", 2),
+            ("v 0 0 0\nf 1/2 1 1\n", 2),
+        ];
+
+        for (source, expected) in errors {
+            match Mesh::parse_obj(source) {
+                Err(ObjError::Parse { line, .. }) => assert_eq!(line, expected, "{source:?}"),
+                other => panic!("expected a parse error for {source:?}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_mtl_reads_colors_opacity_and_textures() {
+        let source = "
+# Comment
+newmtl red
+Kd 1 0 0
+d 0.5
+newmtl textured
+Kd 0 0 1
+map_Kd -s 1 1 1 wood.png
+";
+        let materials = Material::parse_mtl(source, Path::new("models")).unwrap();
+
+        assert_eq!(materials.len(), 2);
+        assert_eq!(materials[0].name, "red");
+        assert_eq!(materials[0].diffuse, 0x800000FF);
+        assert_eq!(materials[0].diffuse_texture, None);
+        assert_eq!(materials[1].diffuse, 0xFFFF0000);
+        assert_eq!(
+            materials[1].diffuse_texture,
+            Some(Path::new("models").join("wood.png"))
+        );
+    }
+
+    #[test]
+    fn normals_fall_back_to_the_face_normal() {
+        let mesh = Mesh::parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 1 0 0\nf 1//1 2 3").unwrap();
+
+        assert_eq!(
+            mesh.normals(&mesh.triangles[0]),
+            [Vec3::X, Vec3::Z, Vec3::Z]
+        );
+    }
+
+    #[test]
+    fn bounds_cover_every_position() {
+        let mesh = Mesh::parse_obj("v -1 2 0\nv 3 -4 5\nv 0 0 -6").unwrap();
+
+        assert_eq!(Mesh::default().bounds(), None);
+        assert_eq!(
+            mesh.bounds(),
+            Some((Vec3::new(-1.0, -4.0, -6.0), Vec3::new(3.0, 2.0, 5.0)))
+        );
+    }
+
+    #[test]
+    fn mesh_renderer_keeps_the_closest_surface() {
+        let camera = Camera::orthographic(2.0, 0.1, 10.0)
+            .look_at(Vec3::new(0.5, 0.5, 5.0), Vec3::new(0.5, 0.5, 0.0));
+        let mesh = Mesh::parse_obj(QUAD).unwrap();
+
+        let near = Pipeline::new(camera).with_model(Mat4::translation(Vec3::Z));
+        let far = Pipeline::new(camera);
+
+        // The order of the draws must not matter
+        for order in [
+            [(near, 0xFF0000FF), (far, 0xFF00FF00)],
+            [(far, 0xFF00FF00), (near, 0xFF0000FF)],
+        ] {
+            let mut canvas = Canvas::new(20, 20);
+            canvas.fill(0xFF000000);
+            let mut renderer = MeshRenderer::new(near);
+
+            for (pipeline, color) in order {
+                let mut texture = Canvas::new(1, 1);
+                texture.fill(color);
+
+                renderer.pipeline = pipeline;
+                renderer.draw(&mut canvas, &mesh, &mut texture);
+            }
+
+            assert_eq!(canvas.get_pixel(12, 8), Some(0xFF0000FF));
+            assert_eq!(canvas.get_pixel(2, 2), Some(0xFF000000));
+        }
+    }
+
+    fn draw_quad(mesh: &Mesh, renderer: &mut MeshRenderer) -> Canvas {
+        let mut canvas = Canvas::new(20, 20);
+        canvas.fill(0xFF000000);
+
+        let mut texture = Canvas::new(1, 1);
+        texture.fill(0xFFFFFFFF);
+
+        renderer.draw(&mut canvas, mesh, &mut texture);
+        canvas
+    }
+
+    fn quad_renderer() -> MeshRenderer {
+        let camera = Camera::orthographic(2.0, 0.1, 10.0)
+            .look_at(Vec3::new(0.5, 0.5, 5.0), Vec3::new(0.5, 0.5, 0.0));
+
+        MeshRenderer::new(Pipeline::new(camera))
+    }
+
+    #[test]
+    fn mesh_renderer_tints_triangles_with_their_material() {
+        let mut mesh = Mesh::parse_obj(QUAD).unwrap();
+
+        mesh.materials[0].diffuse = 0xFF00FFFF;
+        assert_eq!(
+            draw_quad(&mesh, &mut quad_renderer()).get_pixel(12, 8),
+            Some(0xFF00FFFF)
+        );
+
+        // Translucent materials are blended
+        mesh.materials[0].diffuse = 0x800000FF;
+        assert_eq!(
+            draw_quad(&mesh, &mut quad_renderer()).get_pixel(12, 8),
+            Some(0xFF000080)
+        );
+    }
+
+    #[test]
+    fn mesh_renderer_uses_the_loaded_material_textures() {
+        let mut mesh = Mesh::parse_obj(QUAD).unwrap();
+        mesh.materials[0].diffuse_texture = Some(PathBuf::from("green.png"));
+
+        // Falls back to the provided texture until the material texture is loaded
+        let mut renderer = quad_renderer();
+        assert_eq!(
+            draw_quad(&mesh, &mut renderer).get_pixel(12, 8),
+            Some(0xFFFFFFFF)
+        );
+
+        let mut green = Canvas::new(1, 1);
+        green.fill(0xFF00FF00);
+        renderer.insert_texture("green.png", green);
+        renderer.clear_depth();
+
+        assert_eq!(
+            draw_quad(&mesh, &mut renderer).get_pixel(12, 8),
+            Some(0xFF00FF00)
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{Canvas, Drawable, Rect, ToOlivecCanvas};
+
+/// A lossless transformation of a canvas by mirroring it or rotating it by a multiple
+/// of 90 degrees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    /// Mirrors the canvas around its vertical axis
+    FlipHorizontal,
+    /// Mirrors the canvas around its horizontal axis
+    FlipVertical,
+    /// Rotates the canvas by 90 degrees clockwise
+    Rotate90,
+    Rotate180,
+    /// Rotates the canvas by 90 degrees counter clockwise
+    Rotate270,
+    /// Mirrors the canvas around its main diagonal, swapping rows and columns
+    Transpose,
+}
+
+impl Orientation {
+    /// Returns whether the width and height are swapped by the transformation
+    pub fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Orientation::Rotate90 | Orientation::Rotate270 | Orientation::Transpose
+        )
+    }
+
+    /// Returns the size of a canvas of `width` by `height` pixels once transformed
+    pub fn output_size(self, width: usize, height: usize) -> (usize, usize) {
+        if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Maps a pixel of the transformed canvas back to the pixel of the source canvas
+    /// of `width` by `height` pixels it comes from
+    fn source(self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Orientation::FlipHorizontal => (width - 1 - x, y),
+            Orientation::FlipVertical => (x, height - 1 - y),
+            Orientation::Rotate90 => (y, height - 1 - x),
+            Orientation::Rotate180 => (width - 1 - x, height - 1 - y),
+            Orientation::Rotate270 => (width - 1 - y, x),
+            Orientation::Transpose => (y, x),
+        }
+    }
+
+    /// Writes the transformed pixels of `src` into `dst`, which can be a [`Canvas`]
+    /// or a [`crate::SubCanvas`] of any stride
+    ///
+    /// `dst` should have the size returned by [`Orientation::output_size`], otherwise
+    /// only the area both canvases have in common is written. The source and
+    /// destination must not share any pixels
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable, Orientation };
+    ///
+    /// let mut sprite = Canvas::new(16, 32);
+    /// sprite.set_pixel(0, 0, 0xFF0000FF);
+    ///
+    /// let mut sheet = Canvas::new(128, 128);
+    /// let mut cell = sheet.subcanvas(32, 0, 32, 16).unwrap();
+    ///
+    /// Orientation::Rotate90.apply(&sprite, &mut cell);
+    ///
+    /// assert_eq!(sheet.get_pixel(32 + 31, 0), Some(0xFF0000FF));
+    /// ```
+    pub fn apply(self, src: &impl ToOlivecCanvas, dst: &mut impl ToOlivecCanvas) {
+        let (soc, doc) = (src.get_olivec_canvas(), dst.get_olivec_canvas());
+        let (src_width, src_height) = (soc.width as usize, soc.height as usize);
+        let (out_width, out_height) = self.output_size(src_width, src_height);
+
+        let width = out_width.min(doc.width as usize);
+        let height = out_height.min(doc.height as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = self.source(x, y, src_width, src_height);
+
+                // Safety: Both coordinates lie within the bounds of their canvas, and
+                // the caller guarantees that the canvases don't overlap
+                unsafe {
+                    *doc.pixels.add(y * doc.stride as usize + x) =
+                        *soc.pixels.add(sy * soc.stride as usize + sx);
+                }
+            }
+        }
+
+        dst.record_damage(Rect::new(0, 0, width as i32, height as i32));
+    }
+}
+
+impl Canvas {
+    /// Returns a transformed copy of the canvas
+    pub fn oriented(&self, orientation: Orientation) -> Canvas {
+        let (width, height) = orientation.output_size(self.get_width(), self.get_height());
+
+        let mut oriented = Canvas::new(width, height);
+        orientation.apply(self, &mut oriented);
+
+        oriented
+    }
+
+    /// Transforms the canvas in place
+    ///
+    /// Flips and 180 degree rotations reuse the pixel buffer, while the other
+    /// transformations allocate a new one
+    pub fn orient(&mut self, orientation: Orientation) {
+        let (width, height) = (self.get_width(), self.get_height());
+
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        match orientation {
+            Orientation::FlipHorizontal => self
+                .pixels_mut()
+                .chunks_exact_mut(width)
+                .for_each(|row| row.reverse()),
+            Orientation::FlipVertical => {
+                let pixels = self.pixels_mut();
+
+                for y in 0..height / 2 {
+                    let (top, bottom) = pixels.split_at_mut((height - 1 - y) * width);
+                    top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+                }
+            }
+            Orientation::Rotate180 => self.pixels_mut().reverse(),
+            Orientation::Rotate90 | Orientation::Rotate270 | Orientation::Transpose => {
+                let tracking = self.is_tracking_damage();
+                *self = self.oriented(orientation);
+                self.track_damage(tracking);
+            }
+        }
+
+        self.mark_damage(Rect::new(
+            0,
+            0,
+            self.get_width() as i32,
+            self.get_height() as i32,
+        ));
+    }
+
+    pub fn flip_horizontal(&mut self) {
+        self.orient(Orientation::FlipHorizontal);
+    }
+
+    pub fn flip_vertical(&mut self) {
+        self.orient(Orientation::FlipVertical);
+    }
+
+    /// Rotates the canvas by 90 degrees clockwise
+    pub fn rotate90(&mut self) {
+        self.orient(Orientation::Rotate90);
+    }
+
+    pub fn rotate180(&mut self) {
+        self.orient(Orientation::Rotate180);
+    }
+
+    /// Rotates the canvas by 90 degrees counter clockwise
+    pub fn rotate270(&mut self) {
+        self.orient(Orientation::Rotate270);
+    }
+
+    pub fn transpose(&mut self) {
+        self.orient(Orientation::Transpose);
+    }
+
+    pub fn flipped_horizontal(&self) -> Canvas {
+        self.oriented(Orientation::FlipHorizontal)
+    }
+
+    pub fn flipped_vertical(&self) -> Canvas {
+        self.oriented(Orientation::FlipVertical)
+    }
+
+    /// Returns a copy of the canvas rotated by 90 degrees clockwise
+    pub fn rotated90(&self) -> Canvas {
+        self.oriented(Orientation::Rotate90)
+    }
+
+    pub fn rotated180(&self) -> Canvas {
+        self.oriented(Orientation::Rotate180)
+    }
+
+    /// Returns a copy of the canvas rotated by 90 degrees counter clockwise
+    pub fn rotated270(&self) -> Canvas {
+        self.oriented(Orientation::Rotate270)
+    }
+
+    pub fn transposed(&self) -> Canvas {
+        self.oriented(Orientation::Transpose)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIENTATIONS: [Orientation; 6] = [
+        Orientation::FlipHorizontal,
+        Orientation::FlipVertical,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+        Orientation::Transpose,
+    ];
+
+    /// A 3 by 2 canvas whose pixels hold their index
+    fn numbered() -> Canvas {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.shade(|x, y| (y * 3 + x) as u32);
+        canvas
+    }
+
+    #[test]
+    fn every_orientation_moves_the_pixels() {
+        let expected: [(Orientation, (usize, usize), [u32; 6]); 6] = [
+            (Orientation::FlipHorizontal, (3, 2), [2, 1, 0, 5, 4, 3]),
+            (Orientation::FlipVertical, (3, 2), [3, 4, 5, 0, 1, 2]),
+            (Orientation::Rotate90, (2, 3), [3, 0, 4, 1, 5, 2]),
+            (Orientation::Rotate180, (3, 2), [5, 4, 3, 2, 1, 0]),
+            (Orientation::Rotate270, (2, 3), [2, 5, 1, 4, 0, 3]),
+            (Orientation::Transpose, (2, 3), [0, 3, 1, 4, 2, 5]),
+        ];
+
+        for (orientation, size, pixels) in expected {
+            let copy = numbered().oriented(orientation);
+            assert_eq!(
+                (copy.get_width(), copy.get_height()),
+                size,
+                "{orientation:?}"
+            );
+            assert_eq!(copy.pixels(), &pixels, "{orientation:?}");
+
+            let mut in_place = numbered();
+            in_place.orient(orientation);
+            assert_eq!(in_place.pixels(), &pixels, "{orientation:?}");
+        }
+    }
+
+    #[test]
+    fn inverse_orientations_restore_the_canvas() {
+        let mut canvas = Canvas::new(5, 3);
+        canvas.shade(|x, y| (y * 5 + x) as u32);
+        let original = canvas.pixels().to_vec();
+
+        canvas.rotate90();
+        canvas.rotate270();
+        assert_eq!(canvas.pixels(), original);
+
+        canvas.rotate180();
+        canvas.rotate180();
+        canvas.flip_vertical();
+        canvas.flip_vertical();
+        canvas.flip_horizontal();
+        canvas.flip_horizontal();
+        canvas.transpose();
+        canvas.transpose();
+        assert_eq!(canvas.pixels(), original);
+        assert_eq!(
+            canvas.rotated90().rotated90().pixels(),
+            canvas.rotated180().pixels()
+        );
+    }
+
+    #[test]
+    fn apply_writes_into_a_subcanvas() {
+        let src = numbered();
+        let mut sheet = Canvas::new(4, 4);
+        sheet.fill(9);
+
+        let mut cell = sheet.subcanvas(1, 1, 2, 3).unwrap();
+        Orientation::Transpose.apply(&src, &mut cell);
+
+        #[rustfmt::skip]
+        assert_eq!(sheet.pixels(), &[
+            9, 9, 9, 9,
+            9, 0, 3, 9,
+            9, 1, 4, 9,
+            9, 2, 5, 9,
+        ]);
+    }
+
+    #[test]
+    fn apply_clips_to_the_destination() {
+        let mut dst = Canvas::new(1, 1);
+        dst.track_damage(true);
+
+        Orientation::Rotate90.apply(&numbered(), &mut dst);
+
+        assert_eq!(dst.pixels(), &[3]);
+        assert_eq!(dst.take_damage(), [Rect::new(0, 0, 1, 1)]);
+    }
+
+    #[test]
+    fn orient_keeps_damage_tracking() {
+        for orientation in ORIENTATIONS {
+            let mut canvas = numbered();
+            canvas.track_damage(true);
+            canvas.orient(orientation);
+
+            let (w, h) = orientation.output_size(3, 2);
+            assert_eq!(
+                canvas.take_damage(),
+                [Rect::new(0, 0, w as i32, h as i32)],
+                "{orientation:?}"
+            );
+        }
+    }
+}
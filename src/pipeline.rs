@@ -0,0 +1,465 @@
+use crate::{lerp_color, Camera, Drawable, Mat4, ToOlivecCanvas, Vec2, Vec3, Vec4};
+
+/// Clipping against the side planes happens on a band this many times larger
+/// than the visible area. olive.c already clips triangles against the canvas, so
+/// this only exists to keep the projected coordinates from overflowing an `i32`
+const GUARD_BAND: f32 = 4.0;
+
+/// A vertex in model space with the attributes that the pipeline interpolates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    pub position: Vec3,
+    pub color: u32,
+    pub uv: Vec2,
+}
+
+impl Vertex {
+    /// Creates a white vertex with zeroed texture coordinates
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            color: 0xFFFFFFFF,
+            uv: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_color(mut self, color: u32) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_uv(mut self, uv: Vec2) -> Self {
+        self.uv = uv;
+        self
+    }
+}
+
+/// A vertex after projection and viewport mapping
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenVertex {
+    /// Horizontal position in canvas pixels
+    pub x: f32,
+    /// Vertical position in canvas pixels, growing downwards
+    pub y: f32,
+    /// Normalized device depth in the range `[-1, 1]` where `-1` is the near plane
+    pub depth: f32,
+    /// Reciprocal of the clip space `w`, used for perspective correct interpolation
+    pub inv_w: f32,
+    pub color: u32,
+    pub uv: Vec2,
+}
+
+impl ScreenVertex {
+    /// Returns the position rounded to whole pixels, as expected by the olive.c
+    /// triangle functions
+    pub fn pixel(&self) -> (i32, i32) {
+        (self.x.round() as i32, self.y.round() as i32)
+    }
+}
+
+/// Which faces get discarded based on their winding order on screen
+///
+/// Front faces are the ones whose vertices appear counter-clockwise when viewed
+/// from the camera
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    #[default]
+    Back,
+    Front,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ClipVertex {
+    position: Vec4,
+    color: u32,
+    uv: Vec2,
+}
+
+impl ClipVertex {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(other.position, t),
+            color: lerp_color(self.color, other.color, t),
+            uv: self.uv.lerp(other.uv, t),
+        }
+    }
+}
+
+/// Transforms triangles from model space onto a canvas and feeds them into the
+/// olive.c triangle rasterizers
+///
+/// Triangles are clipped against the near and far planes so geometry behind the
+/// camera never gets projected, and are optionally culled based on their winding
+///
+/// ```
+/// use olivec::{Camera, Canvas, Drawable, Pipeline, Vec3, Vertex};
+///
+/// let mut canvas = Canvas::new(320, 240);
+/// let camera = Camera::perspective(60f32.to_radians(), 0.1, 100.0)
+///     .look_at(Vec3::new(0.0, 0.0, 3.0), Vec3::ZERO);
+///
+/// let pipeline = Pipeline::new(camera);
+///
+/// pipeline.draw_triangle(
+///     &mut canvas,
+///     &[
+///         Vertex::new(Vec3::new(-1.0, -1.0, 0.0)).with_color(0xFF0000FF),
+///         Vertex::new(Vec3::new(1.0, -1.0, 0.0)).with_color(0xFF00FF00),
+///         Vertex::new(Vec3::new(0.0, 1.0, 0.0)).with_color(0xFFFF0000),
+///     ],
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Pipeline {
+    pub camera: Camera,
+    /// Transforms model space into world space
+    pub model: Mat4,
+    pub cull_mode: CullMode,
+}
+
+impl Pipeline {
+    pub fn new(camera: Camera) -> Self {
+        Self {
+            camera,
+            model: Mat4::IDENTITY,
+            cull_mode: CullMode::default(),
+        }
+    }
+
+    pub fn with_model(mut self, model: Mat4) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_cull_mode(mut self, cull_mode: CullMode) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    /// Returns the combined model-view-projection matrix for a target of the given size
+    pub fn mvp(&self, width: usize, height: usize) -> Mat4 {
+        let aspect = width as f32 / height.max(1) as f32;
+
+        self.camera.view_projection(aspect) * self.model
+    }
+
+    /// Runs a triangle through the vertex stage, clipping, culling and viewport mapping
+    ///
+    /// Clipping may split the triangle, so this returns between zero and several
+    /// screen space triangles
+    pub fn project(
+        &self,
+        width: usize,
+        height: usize,
+        triangle: &[Vertex; 3],
+    ) -> Vec<[ScreenVertex; 3]> {
+        self.project_with(&self.mvp(width, height), width, height, triangle)
+    }
+
+    /// Same as [`Pipeline::project`] but with a precomputed model-view-projection matrix,
+    /// which avoids rebuilding it for every triangle of a larger mesh
+    pub fn project_with(
+        &self,
+        mvp: &Mat4,
+        width: usize,
+        height: usize,
+        triangle: &[Vertex; 3],
+    ) -> Vec<[ScreenVertex; 3]> {
+        let polygon: Vec<ClipVertex> = triangle
+            .iter()
+            .map(|v| ClipVertex {
+                position: mvp.transform_point(v.position),
+                color: v.color,
+                uv: v.uv,
+            })
+            .collect();
+
+        let polygon = clip_polygon(polygon, |p| p.z + p.w);
+        let polygon = clip_polygon(polygon, |p| p.w - p.z);
+        let polygon = clip_polygon(polygon, |p| GUARD_BAND * p.w + p.x);
+        let polygon = clip_polygon(polygon, |p| GUARD_BAND * p.w - p.x);
+        let polygon = clip_polygon(polygon, |p| GUARD_BAND * p.w + p.y);
+        let polygon = clip_polygon(polygon, |p| GUARD_BAND * p.w - p.y);
+
+        if polygon.len() < 3 {
+            return Vec::new();
+        }
+
+        let screen: Vec<ScreenVertex> = polygon
+            .iter()
+            .map(|v| {
+                let inv_w = 1.0 / v.position.w;
+
+                ScreenVertex {
+                    x: (v.position.x * inv_w + 1.0) * 0.5 * width as f32,
+                    y: (1.0 - v.position.y * inv_w) * 0.5 * height as f32,
+                    depth: v.position.z * inv_w,
+                    inv_w,
+                    color: v.color,
+                    uv: v.uv,
+                }
+            })
+            .collect();
+
+        (1..screen.len() - 1)
+            .map(|i| [screen[0], screen[i], screen[i + 1]])
+            .filter(|t| !self.is_culled(t))
+            .collect()
+    }
+
+    fn is_culled(&self, [a, b, c]: &[ScreenVertex; 3]) -> bool {
+        // The y axis points down on screen, so counter-clockwise triangles have a
+        // negative signed area
+        let area = (b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y);
+
+        match self.cull_mode {
+            _ if area == 0.0 => true,
+            CullMode::None => false,
+            CullMode::Back => area > 0.0,
+            CullMode::Front => area < 0.0,
+        }
+    }
+
+    /// Draws a triangle with its vertex colors interpolated using `triangle3c`
+    pub fn draw_triangle(&self, canvas: &mut impl Drawable, triangle: &[Vertex; 3]) {
+        let (width, height) = (canvas.get_width(), canvas.get_height());
+
+        for [a, b, c] in self.project(width, height, triangle) {
+            let ((x1, y1), (x2, y2), (x3, y3)) = (a.pixel(), b.pixel(), c.pixel());
+
+            canvas.triangle3c(x1, y1, x2, y2, x3, y3, a.color, b.color, c.color);
+        }
+    }
+
+    /// Draws a textured triangle using `triangle3uv`
+    ///
+    /// Texture coordinates are passed pre-divided by `w` along with `1 / w` as the
+    /// depth, which is what olive.c expects for perspective correct mapping
+    pub fn draw_triangle_textured(
+        &self,
+        canvas: &mut impl Drawable,
+        triangle: &[Vertex; 3],
+        texture: &mut impl ToOlivecCanvas,
+    ) {
+        let (width, height) = (canvas.get_width(), canvas.get_height());
+
+        for [a, b, c] in self.project(width, height, triangle) {
+            let ((x1, y1), (x2, y2), (x3, y3)) = (a.pixel(), b.pixel(), c.pixel());
+
+            canvas.triangle3uv(
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                a.uv.x * a.inv_w,
+                a.uv.y * a.inv_w,
+                b.uv.x * b.inv_w,
+                b.uv.y * b.inv_w,
+                c.uv.x * c.inv_w,
+                c.uv.y * c.inv_w,
+                a.inv_w,
+                b.inv_w,
+                c.inv_w,
+                texture,
+            );
+        }
+    }
+
+    /// Draws every triangle in the slice with [`Pipeline::draw_triangle`]
+    pub fn draw_triangles(&self, canvas: &mut impl Drawable, triangles: &[[Vertex; 3]]) {
+        for triangle in triangles {
+            self.draw_triangle(canvas, triangle);
+        }
+    }
+}
+
+/// Sutherland–Hodgman clipping of a convex polygon against a single plane, where
+/// `distance` is positive on the inside of the plane
+fn clip_polygon(polygon: Vec<ClipVertex>, distance: impl Fn(Vec4) -> f32) -> Vec<ClipVertex> {
+    if polygon.iter().all(|v| distance(v.position) >= 0.0) {
+        return polygon;
+    }
+
+    let mut out = Vec::with_capacity(polygon.len() + 1);
+
+    for (i, current) in polygon.iter().enumerate() {
+        let next = &polygon[(i + 1) % polygon.len()];
+        let (dc, dn) = (distance(current.position), distance(next.position));
+
+        if dc >= 0.0 {
+            out.push(*current);
+        }
+
+        if (dc >= 0.0) != (dn >= 0.0) {
+            out.push(current.lerp(next, dc / (dc - dn)));
+        }
+    }
+
+    out
+}
+
+/// Per-pixel depth storage used to resolve visibility between triangles
+///
+/// Depths are in normalized device coordinates, so smaller values are closer to
+/// the camera
+#[derive(Debug, Clone)]
+pub struct DepthBuffer {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+impl DepthBuffer {
+    /// Creates a cleared depth buffer
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![f32::INFINITY; width * height],
+        }
+    }
+
+    /// Resets every pixel so that any depth passes the test
+    pub fn clear(&mut self) {
+        self.values.fill(f32::INFINITY);
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the stored depth at `(x, y)` or `None` if the coordinates are out of bounds
+    pub fn get(&self, x: usize, y: usize) -> Option<f32> {
+        (x < self.width && y < self.height).then(|| self.values[y * self.width + x])
+    }
+
+    /// Stores `depth` at `(x, y)` if it is closer than the current value
+    ///
+    /// Returns whether the test passed, i.e. whether the pixel should be drawn
+    pub fn test_and_set(&mut self, x: usize, y: usize, depth: f32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+
+        let value = &mut self.values[y * self.width + x];
+
+        if depth < *value {
+            *value = depth;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Canvas;
+
+    fn pipeline() -> Pipeline {
+        let camera = Camera::perspective(90f32.to_radians(), 0.1, 100.0)
+            .look_at(Vec3::new(0.0, 0.0, 2.0), Vec3::ZERO);
+
+        Pipeline::new(camera)
+    }
+
+    /// Counter-clockwise when seen from the camera of [`pipeline`]
+    fn triangle(z: f32) -> [Vertex; 3] {
+        [
+            Vertex::new(Vec3::new(-1.0, -1.0, z)),
+            Vertex::new(Vec3::new(1.0, -1.0, z)),
+            Vertex::new(Vec3::new(0.0, 1.0, z)),
+        ]
+    }
+
+    #[test]
+    fn project_maps_the_view_axis_to_the_canvas_center() {
+        let [[a, b, c]] = pipeline().project(100, 100, &triangle(0.0))[..] else {
+            panic!("expected a single triangle");
+        };
+
+        // With a 90 degree field of view, x = ±1 at a distance of 2 lands halfway
+        // between the center and the edges
+        assert_eq!(a.pixel(), (25, 75));
+        assert_eq!(b.pixel(), (75, 75));
+        assert_eq!(c.pixel(), (50, 25));
+        assert!(a.depth > -1.0 && a.depth < 1.0);
+    }
+
+    #[test]
+    fn triangles_behind_the_camera_are_discarded() {
+        assert!(pipeline().project(100, 100, &triangle(5.0)).is_empty());
+    }
+
+    #[test]
+    fn triangles_crossing_the_near_plane_are_split() {
+        let triangle = [
+            Vertex::new(Vec3::new(-1.0, -1.0, 0.0)),
+            Vertex::new(Vec3::new(1.0, -1.0, 0.0)),
+            Vertex::new(Vec3::new(0.0, -1.0, 5.0)),
+        ];
+        let pipeline = pipeline().with_cull_mode(CullMode::None);
+        let projected = pipeline.project(100, 100, &triangle);
+
+        // Cutting a corner off a triangle leaves a quad
+        assert_eq!(projected.len(), 2);
+        assert!(projected.iter().flatten().all(|v| v.depth >= -1.0 - 1e-5));
+    }
+
+    #[test]
+    fn culling_depends_on_the_winding() {
+        let [a, b, c] = triangle(0.0);
+        let clockwise = [a, c, b];
+
+        assert_eq!(pipeline().project(100, 100, &clockwise).len(), 0);
+        assert_eq!(
+            pipeline()
+                .with_cull_mode(CullMode::Front)
+                .project(100, 100, &clockwise)
+                .len(),
+            1
+        );
+        assert_eq!(
+            pipeline()
+                .with_cull_mode(CullMode::Front)
+                .project(100, 100, &triangle(0.0))
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn draw_triangle_fills_the_projected_area() {
+        let mut canvas = Canvas::new(100, 100);
+        canvas.fill(0xFF000000);
+
+        let red = triangle(0.0).map(|v| v.with_color(0xFF0000FF));
+        pipeline().draw_triangle(&mut canvas, &red);
+
+        assert_eq!(canvas.get_pixel(50, 60), Some(0xFF0000FF));
+        assert_eq!(canvas.get_pixel(5, 5), Some(0xFF000000));
+    }
+
+    #[test]
+    fn depth_buffer_keeps_the_closest_depth() {
+        let mut depth = DepthBuffer::new(2, 2);
+
+        assert!(depth.test_and_set(1, 1, 0.5));
+        assert!(!depth.test_and_set(1, 1, 0.7));
+        assert!(depth.test_and_set(1, 1, 0.2));
+        assert!(!depth.test_and_set(2, 0, 0.0));
+        assert_eq!(depth.get(1, 1), Some(0.2));
+        assert_eq!(depth.get(0, 2), None);
+
+        depth.clear();
+        assert_eq!(depth.get(1, 1), Some(f32::INFINITY));
+    }
+}
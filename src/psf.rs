@@ -0,0 +1,342 @@
+use std::path::Path;
+
+use crate::{DynFont, FontError, MAX_FONT_CELL_SIZE};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_MODE_512: u8 = 0x01;
+const PSF1_MODE_HAS_TABLE: u8 = 0x02;
+const PSF1_MODE_HAS_SEQ: u8 = 0x04;
+const PSF1_SEPARATOR: u16 = 0xFFFF;
+const PSF1_START_SEQ: u16 = 0xFFFE;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xB5, 0x4A, 0x86];
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xFF;
+const PSF2_START_SEQ: u8 = 0xFE;
+
+fn invalid(message: &str) -> FontError {
+    FontError::Parse(format!("invalid PSF font: {message}"))
+}
+
+/// The layout of the glyphs in a PSF file, common to both versions
+struct PsfLayout<'a> {
+    width: usize,
+    height: usize,
+    glyph_count: usize,
+    bytes_per_glyph: usize,
+    glyphs: &'a [u8],
+    /// The characters of every glyph, or `None` if the file has no unicode table
+    table: Option<Vec<Vec<char>>>,
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32, FontError> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| invalid("truncated header"))
+}
+
+fn parse_psf1(data: &[u8]) -> Result<PsfLayout<'_>, FontError> {
+    let (mode, height) = match data.get(2..4) {
+        Some(&[mode, height]) => (mode, height as usize),
+        _ => return Err(invalid("truncated header")),
+    };
+
+    let glyph_count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+    let glyphs_end = 4 + glyph_count * height;
+    let glyphs = data
+        .get(4..glyphs_end)
+        .ok_or_else(|| invalid("truncated glyphs"))?;
+
+    let table = (mode & (PSF1_MODE_HAS_TABLE | PSF1_MODE_HAS_SEQ) != 0).then(|| {
+        let mut table = vec![Vec::new(); glyph_count];
+        let mut entries = data[glyphs_end..]
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]));
+
+        for chars in table.iter_mut() {
+            let mut in_sequence = false;
+
+            for entry in entries.by_ref() {
+                match entry {
+                    PSF1_SEPARATOR => break,
+                    PSF1_START_SEQ => in_sequence = true,
+                    // Sequences of combining characters can't be drawn as one glyph
+                    _ if in_sequence => {}
+                    code => chars.extend(char::from_u32(code as u32)),
+                }
+            }
+        }
+
+        table
+    });
+
+    Ok(PsfLayout {
+        width: 8,
+        height,
+        glyph_count,
+        bytes_per_glyph: height,
+        glyphs,
+        table,
+    })
+}
+
+fn parse_psf2(data: &[u8]) -> Result<PsfLayout<'_>, FontError> {
+    let header_size = u32_at(data, 8)? as usize;
+    let flags = u32_at(data, 12)?;
+    let glyph_count = u32_at(data, 16)? as usize;
+    let bytes_per_glyph = u32_at(data, 20)? as usize;
+    let height = u32_at(data, 24)? as usize;
+    let width = u32_at(data, 28)? as usize;
+
+    if !(1..=MAX_FONT_CELL_SIZE).contains(&width) || !(1..=MAX_FONT_CELL_SIZE).contains(&height) {
+        return Err(invalid("glyph size out of range"));
+    }
+
+    if bytes_per_glyph < height * width.div_ceil(8) {
+        return Err(invalid("glyph size doesn't match its dimensions"));
+    }
+
+    let glyphs_end = glyph_count
+        .checked_mul(bytes_per_glyph)
+        .and_then(|size| size.checked_add(header_size))
+        .ok_or_else(|| invalid("truncated glyphs"))?;
+
+    let glyphs = data
+        .get(header_size..glyphs_end)
+        .ok_or_else(|| invalid("truncated glyphs"))?;
+
+    let table = (flags & PSF2_HAS_UNICODE_TABLE != 0).then(|| {
+        let mut entries = data[glyphs_end..].split(|&b| b == PSF2_SEPARATOR);
+
+        (0..glyph_count)
+            .map(|_| {
+                let entry = entries.next().unwrap_or_default();
+
+                // Everything after the first sequence marker describes sequences of
+                // combining characters, which can't be drawn as one glyph
+                let singles = entry.split(|&b| b == PSF2_START_SEQ).next().unwrap();
+
+                String::from_utf8_lossy(singles)
+                    .chars()
+                    .filter(|&c| c != char::REPLACEMENT_CHARACTER)
+                    .collect()
+            })
+            .collect()
+    });
+
+    Ok(PsfLayout {
+        width,
+        height,
+        glyph_count,
+        bytes_per_glyph,
+        glyphs,
+        table,
+    })
+}
+
+impl DynFont {
+    /// Parses a PC Screen Font, as used by the Linux console, in either the PSF1 or
+    /// PSF2 format
+    ///
+    /// Characters are mapped to glyphs with the unicode table of the font. Fonts
+    /// without one map each glyph index directly to the code point of the same value.
+    /// Fonts whose glyphs are wider or taller than 512 pixels are rejected
+    pub fn parse_psf(data: &[u8]) -> Result<DynFont, FontError> {
+        let layout = if data.starts_with(&PSF2_MAGIC) {
+            parse_psf2(data)?
+        } else if data.starts_with(&PSF1_MAGIC) {
+            parse_psf1(data)?
+        } else {
+            return Err(invalid("unknown magic number"));
+        };
+
+        let (width, height) = (layout.width, layout.height);
+        let row_bytes = width.div_ceil(8);
+        let mut font = DynFont::new(width, height);
+
+        for index in 0..layout.glyph_count {
+            let bitmap = &layout.glyphs[index * layout.bytes_per_glyph..][..layout.bytes_per_glyph];
+
+            let pixels: Vec<u8> = (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| bitmap[y * row_bytes + x / 8] & (0x80 >> (x % 8)))
+                .collect();
+
+            match &layout.table {
+                Some(table) => {
+                    for &c in &table[index] {
+                        font.insert(c, &pixels);
+                    }
+                }
+                None => {
+                    if let Some(c) = char::from_u32(index as u32) {
+                        font.insert(c, &pixels);
+                    }
+                }
+            }
+        }
+
+        // Fonts usually map the replacement character to a glyph for this purpose
+        if font.contains(char::REPLACEMENT_CHARACTER) {
+            font = font.with_fallback(char::REPLACEMENT_CHARACTER);
+        } else if font.contains('?') {
+            font = font.with_fallback('?');
+        }
+
+        Ok(font)
+    }
+
+    /// Loads a PSF font from disk, see [`DynFont::parse_psf`]
+    pub fn load_psf(path: impl AsRef<Path>) -> Result<DynFont, FontError> {
+        Self::parse_psf(&std::fs::read(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A PSF1 font with glyphs 2 pixels tall, where the glyph at index `i` has its
+    /// first row set to `i` and its second row empty
+    fn psf1(mode: u8, table: &[u16]) -> Vec<u8> {
+        let glyph_count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+        let mut data = vec![PSF1_MAGIC[0], PSF1_MAGIC[1], mode, 2];
+
+        for i in 0..glyph_count {
+            data.extend([i as u8, 0]);
+        }
+
+        data.extend(table.iter().flat_map(|entry| entry.to_le_bytes()));
+        data
+    }
+
+    /// A PSF2 font with `glyph_count` glyphs of 10 by 2 pixels, where the glyph at
+    /// index `i` only has its pixel at `(i, 1)` set
+    fn psf2(glyph_count: u32, table: Option<&[u8]>) -> Vec<u8> {
+        let flags = table.map_or(0, |_| PSF2_HAS_UNICODE_TABLE);
+        let header = [0, 32, flags, glyph_count, 4, 2, 10];
+
+        let mut data = PSF2_MAGIC.to_vec();
+        data.extend(header.iter().flat_map(|value| value.to_le_bytes()));
+
+        for i in 0..glyph_count {
+            data.extend([
+                0,
+                0,
+                (0x8000u16 >> i).to_be_bytes()[0],
+                (0x8000u16 >> i) as u8,
+            ]);
+        }
+
+        data.extend(table.unwrap_or_default());
+        data
+    }
+
+    fn pixels_set(font: &DynFont, c: char) -> Vec<usize> {
+        let bitmap = font.bitmap(c).unwrap();
+
+        (0..bitmap.len()).filter(|&i| bitmap[i] != 0).collect()
+    }
+
+    #[test]
+    fn psf1_without_table_maps_indices_to_code_points() {
+        let font = DynFont::parse_psf(&psf1(0, &[])).unwrap();
+
+        assert_eq!((font.get_width(), font.get_height()), (8, 2));
+        assert_eq!(font.len(), 256);
+        assert_eq!(pixels_set(&font, 'A'), [1, 7]);
+        assert_eq!(
+            pixels_set(&font, 'é'),
+            (0..8)
+                .filter(|x| 0xE9 & (0x80 >> x) != 0)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(font.fallback(), Some('?'));
+    }
+
+    #[test]
+    fn psf1_table_maps_characters_and_skips_sequences() {
+        #[rustfmt::skip]
+        let table = [
+            0x416, 'X' as u16, PSF1_SEPARATOR,
+            0xE9, PSF1_START_SEQ, 'e' as u16, 0x301, PSF1_SEPARATOR,
+            0xFFFD, PSF1_SEPARATOR,
+        ];
+        let font = DynFont::parse_psf(&psf1(PSF1_MODE_HAS_TABLE, &table)).unwrap();
+
+        assert_eq!(font.chars(), ['X', 'é', 'Ж', '\u{FFFD}']);
+        assert_eq!(font.bitmap('Ж'), font.bitmap('X'));
+        assert_eq!(pixels_set(&font, 'é'), [7]);
+        assert_eq!(font.fallback(), Some('\u{FFFD}'));
+    }
+
+    #[test]
+    fn psf1_512_glyph_fonts_are_read() {
+        let mut table = vec![PSF1_SEPARATOR; 511];
+        table.extend(['Z' as u16, PSF1_SEPARATOR]);
+
+        let font = DynFont::parse_psf(&psf1(PSF1_MODE_512 | PSF1_MODE_HAS_SEQ, &table)).unwrap();
+
+        assert_eq!(font.chars(), ['Z']);
+        assert_eq!(pixels_set(&font, 'Z'), [0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn psf2_table_maps_characters_and_skips_sequences() {
+        let mut table = "Ж".as_bytes().to_vec();
+        table.push(PSF2_SEPARATOR);
+        table.extend("é".as_bytes());
+        table.push(PSF2_START_SEQ);
+        table.extend("e\u{301}".as_bytes());
+        table.push(PSF2_SEPARATOR);
+
+        let font = DynFont::parse_psf(&psf2(2, Some(&table))).unwrap();
+
+        assert_eq!((font.get_width(), font.get_height()), (10, 2));
+        assert_eq!(font.chars(), ['é', 'Ж']);
+        assert_eq!(pixels_set(&font, 'Ж'), [10]);
+        assert_eq!(pixels_set(&font, 'é'), [11]);
+        assert_eq!(font.fallback(), None);
+    }
+
+    #[test]
+    fn psf2_without_table_maps_indices_to_code_points() {
+        let font = DynFont::parse_psf(&psf2(3, None)).unwrap();
+
+        assert_eq!(font.chars(), ['\0', '\u{1}', '\u{2}']);
+        assert_eq!(pixels_set(&font, '\u{2}'), [12]);
+    }
+
+    #[test]
+    fn malformed_fonts_are_parse_errors() {
+        let psf2_font = psf2(2, None);
+
+        let mut too_small = psf2_font.clone();
+        too_small[20] = 3;
+
+        let mut too_wide = psf2_font.clone();
+        too_wide[28..32].copy_from_slice(&100_000u32.to_le_bytes());
+
+        let mut too_many = psf2_font.clone();
+        too_many[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let invalid = [
+            b"not a font".to_vec(),
+            PSF1_MAGIC.to_vec(),
+            psf1(0, &[])[..300].to_vec(),
+            psf1(PSF1_MODE_512, &[])[..600].to_vec(),
+            psf2_font[..20].to_vec(),
+            psf2_font[..psf2_font.len() - 1].to_vec(),
+            too_small,
+            too_wide,
+            too_many,
+        ];
+
+        for data in invalid {
+            assert!(matches!(
+                DynFont::parse_psf(&data),
+                Err(FontError::Parse(_))
+            ));
+        }
+    }
+}
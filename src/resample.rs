@@ -0,0 +1,371 @@
+use std::f32::consts::PI;
+
+use crate::{premultiply_color, unpremultiply_color, Canvas, Drawable, Rect};
+
+/// The filter used to compute the pixels of a resampled canvas
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResampleFilter {
+    /// Picks the closest source pixel, keeping hard edges
+    Nearest,
+    /// Linear interpolation between the two closest source pixels along each axis
+    #[default]
+    Bilinear,
+    /// Catmull-Rom cubic interpolation, sharper than bilinear
+    Bicubic,
+    /// Windowed sinc with 3 lobes, the sharpest and slowest of the filters
+    Lanczos3,
+}
+
+impl ResampleFilter {
+    /// Distance from the center beyond which the kernel is zero
+    fn support(self) -> f32 {
+        match self {
+            ResampleFilter::Nearest => 0.5,
+            ResampleFilter::Bilinear => 1.0,
+            ResampleFilter::Bicubic => 2.0,
+            ResampleFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    fn kernel(self, x: f32) -> f32 {
+        let x = x.abs();
+
+        match self {
+            ResampleFilter::Nearest => {
+                if x <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ResampleFilter::Bilinear => (1.0 - x).max(0.0),
+            ResampleFilter::Bicubic => {
+                // Catmull-Rom spline, i.e. the cubic convolution kernel with a = -0.5
+                if x < 1.0 {
+                    1.5 * x * x * x - 2.5 * x * x + 1.0
+                } else if x < 2.0 {
+                    -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            ResampleFilter::Lanczos3 => {
+                if x < 1e-5 {
+                    1.0
+                } else if x < 3.0 {
+                    let px = PI * x;
+                    3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// The source pixels contributing to a destination pixel along one axis
+struct Contribution {
+    start: usize,
+    weights: Vec<f32>,
+}
+
+/// Computes the normalized filter weights mapping `src_len` pixels onto `dst_len`
+///
+/// When shrinking, the kernel is widened by the scale factor so that every source
+/// pixel contributes to the result
+fn contributions(src_len: usize, dst_len: usize, filter: ResampleFilter) -> Vec<Contribution> {
+    let scale = src_len as f32 / dst_len as f32;
+    let filter_scale = scale.max(1.0);
+    let support = filter.support() * filter_scale;
+
+    (0..dst_len)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale;
+
+            if filter == ResampleFilter::Nearest {
+                return Contribution {
+                    start: (center as usize).min(src_len - 1),
+                    weights: vec![1.0],
+                };
+            }
+
+            let start = (center - support).floor().max(0.0) as usize;
+            let end = ((center + support).ceil() as usize).min(src_len);
+
+            let mut weights: Vec<f32> = (start..end)
+                .map(|j| filter.kernel((j as f32 + 0.5 - center) / filter_scale))
+                .collect();
+
+            let sum: f32 = weights.iter().sum();
+
+            if sum != 0.0 {
+                weights.iter_mut().for_each(|w| *w /= sum);
+            }
+
+            Contribution { start, weights }
+        })
+        .collect()
+}
+
+impl Canvas {
+    /// Changes the size of the canvas, keeping the existing pixels anchored to the top
+    /// left corner
+    ///
+    /// Pixels outside of the old bounds are transparent black, and pixels outside of
+    /// the new bounds are discarded
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable };
+    ///
+    /// let mut canvas = Canvas::new(10, 10);
+    /// canvas.set_pixel(2, 3, 0xFF0000FF);
+    ///
+    /// canvas.resize(20, 5);
+    ///
+    /// assert_eq!(canvas.get_width(), 20);
+    /// assert_eq!(canvas.get_pixel(2, 3), Some(0xFF0000FF));
+    /// assert_eq!(canvas.get_pixel(15, 3), Some(0));
+    /// ```
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let mut resized = Canvas::new(width, height);
+        resized.copy_from(self, Rect::new(0, 0, width as i32, height as i32));
+
+        let tracking = self.is_tracking_damage();
+        *self = resized;
+
+        if tracking {
+            self.track_damage(true);
+            self.mark_damage(Rect::new(0, 0, width as i32, height as i32));
+        }
+    }
+
+    /// Returns a copy of the part of the canvas covered by the rectangle
+    ///
+    /// The rectangle is clipped to the bounds of the canvas, so the result may be
+    /// smaller than requested, or empty if they don't overlap
+    pub fn crop(&self, rect: Rect) -> Canvas {
+        let bounds = Rect::new(0, 0, self.get_width() as i32, self.get_height() as i32);
+        let rect = bounds.intersection(&rect).unwrap_or_default();
+
+        let mut cropped = Canvas::new(rect.w as usize, rect.h as usize);
+        cropped.copy_from(self, rect);
+
+        cropped
+    }
+
+    /// Copies the pixels of `src` inside of `rect` to the top left corner of the canvas
+    fn copy_from(&mut self, src: &Canvas, rect: Rect) {
+        let (src_width, dst_width) = (src.get_width(), self.get_width());
+
+        let x = rect.x.max(0) as usize;
+        let w = (rect.w.max(0) as usize)
+            .min(src_width.saturating_sub(x))
+            .min(dst_width);
+        let h = (rect.h.max(0) as usize)
+            .min(src.get_height().saturating_sub(rect.y.max(0) as usize))
+            .min(self.get_height());
+
+        if w == 0 {
+            return;
+        }
+
+        let src_rows = src
+            .pixels()
+            .chunks_exact(src_width)
+            .skip(rect.y.max(0) as usize);
+        let dst_rows = self.pixels_mut().chunks_exact_mut(dst_width);
+
+        for (dst, src) in dst_rows.zip(src_rows).take(h) {
+            dst[..w].copy_from_slice(&src[x..x + w]);
+        }
+    }
+
+    /// Returns a copy of the canvas scaled to the given size with the provided filter
+    ///
+    /// Colors are filtered with premultiplied alpha, and the filter is widened when
+    /// shrinking so that thumbnails don't alias
+    ///
+    /// ```
+    /// use olivec::{ Canvas, Drawable, ResampleFilter };
+    ///
+    /// let mut canvas = Canvas::new(400, 300);
+    /// canvas.fill(0xFF181818);
+    /// canvas.circle(200, 150, 100, 0xFF2020FF);
+    ///
+    /// let thumbnail = canvas.resample(100, 75, ResampleFilter::Lanczos3);
+    ///
+    /// assert_eq!(thumbnail.get_pixel(0, 0), Some(0xFF181818));
+    /// ```
+    pub fn resample(&self, width: usize, height: usize, filter: ResampleFilter) -> Canvas {
+        let (src_width, src_height) = (self.get_width(), self.get_height());
+        let mut resampled = Canvas::new(width, height);
+
+        if src_width == 0 || src_height == 0 || width == 0 || height == 0 {
+            return resampled;
+        }
+
+        let horizontal = contributions(src_width, width, filter);
+        let vertical = contributions(src_height, height, filter);
+
+        // Horizontal pass into a buffer of `width` by `src_height` pixels
+        let source: Vec<[f32; 4]> = self
+            .pixels()
+            .iter()
+            .map(|&c| premultiply_color(c))
+            .collect();
+        let mut buffer = Vec::with_capacity(width * src_height);
+
+        for row in source.chunks_exact(src_width) {
+            for c in &horizontal {
+                let mut acc = [0.0; 4];
+
+                for (pixel, weight) in row[c.start..].iter().zip(&c.weights) {
+                    for (a, p) in acc.iter_mut().zip(pixel) {
+                        *a += p * weight;
+                    }
+                }
+
+                buffer.push(acc);
+            }
+        }
+
+        // Vertical pass into the final canvas
+        let pixels = resampled.pixels_mut();
+
+        for (y, c) in vertical.iter().enumerate() {
+            for x in 0..width {
+                let mut acc = [0.0; 4];
+
+                for (j, weight) in c.weights.iter().enumerate() {
+                    let pixel = buffer[(c.start + j) * width + x];
+
+                    for (a, p) in acc.iter_mut().zip(pixel) {
+                        *a += p * weight;
+                    }
+                }
+
+                pixels[y * width + x] = unpremultiply_color(acc);
+            }
+        }
+
+        resampled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILTERS: [ResampleFilter; 4] = [
+        ResampleFilter::Nearest,
+        ResampleFilter::Bilinear,
+        ResampleFilter::Bicubic,
+        ResampleFilter::Lanczos3,
+    ];
+
+    fn numbered(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        canvas.shade(|x, y| 0xFF000000 | (y as u32 * width as u32 + x as u32));
+        canvas
+    }
+
+    #[test]
+    fn resize_keeps_the_top_left_pixels() {
+        let mut canvas = numbered(3, 3);
+        canvas.resize(2, 4);
+
+        assert_eq!(
+            canvas.pixels(),
+            &[0xFF000000, 0xFF000001, 0xFF000003, 0xFF000004, 0xFF000006, 0xFF000007, 0, 0]
+        );
+    }
+
+    #[test]
+    fn resize_marks_the_whole_canvas_as_damaged() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.track_damage(true);
+        canvas.resize(6, 2);
+
+        assert!(canvas.is_tracking_damage());
+        assert_eq!(canvas.take_damage(), [Rect::new(0, 0, 6, 2)]);
+    }
+
+    #[test]
+    fn crop_is_clipped_to_the_canvas() {
+        let canvas = numbered(4, 4);
+
+        let cropped = canvas.crop(Rect::new(1, 2, 2, 2));
+        assert_eq!(
+            cropped.pixels(),
+            &[0xFF000009, 0xFF00000A, 0xFF00000D, 0xFF00000E]
+        );
+
+        let clipped = canvas.crop(Rect::new(-1, 3, 3, 5));
+        assert_eq!((clipped.get_width(), clipped.get_height()), (2, 1));
+        assert_eq!(clipped.pixels(), &[0xFF00000C, 0xFF00000D]);
+
+        let outside = canvas.crop(Rect::new(10, 10, 2, 2));
+        assert_eq!((outside.get_width(), outside.get_height()), (0, 0));
+    }
+
+    #[test]
+    fn nearest_upscaling_repeats_pixels() {
+        let canvas = numbered(2, 1).resample(4, 2, ResampleFilter::Nearest);
+
+        let (a, b) = (0xFF000000, 0xFF000001);
+        assert_eq!(canvas.pixels(), &[a, a, b, b, a, a, b, b]);
+    }
+
+    #[test]
+    fn every_filter_keeps_uniform_colors() {
+        let mut canvas = Canvas::new(7, 5);
+        canvas.fill(0x80402010);
+
+        for filter in FILTERS {
+            for (w, h) in [(3, 2), (7, 5), (20, 11)] {
+                let resampled = canvas.resample(w, h, filter);
+
+                assert!(
+                    resampled.pixels().iter().all(|&p| p == 0x80402010),
+                    "{filter:?} to {w}x{h}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shrinking_averages_every_source_pixel() {
+        let mut canvas = Canvas::new(4, 1);
+        canvas.shade(|x, _| if x % 2 == 0 { 0xFF000000 } else { 0xFFFFFFFF });
+
+        let pixel = canvas.resample(1, 1, ResampleFilter::Bilinear).pixels()[0];
+        assert_eq!(pixel & 0xFF, 0x80);
+    }
+
+    #[test]
+    fn transparent_pixels_do_not_bleed_their_color() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel(0, 0, 0xFF0000FF);
+        canvas.set_pixel(1, 0, 0x00FFFFFF);
+
+        for filter in [ResampleFilter::Bilinear, ResampleFilter::Bicubic] {
+            let resampled = canvas.resample(1, 1, filter);
+            assert_eq!(resampled.pixels()[0] & 0x00FFFFFF, 0x0000FF, "{filter:?}");
+        }
+    }
+
+    #[test]
+    fn resampling_to_or_from_nothing_is_empty() {
+        assert_eq!(
+            numbered(4, 4)
+                .resample(0, 3, ResampleFilter::Bicubic)
+                .get_width(),
+            0
+        );
+        assert!(Canvas::new(0, 0)
+            .resample(2, 2, ResampleFilter::Lanczos3)
+            .pixels()
+            .iter()
+            .all(|&p| p == 0));
+    }
+}
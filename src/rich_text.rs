@@ -0,0 +1,574 @@
+use crate::{draw_chars, text_width, Drawable, Font, Rect, TextAlign, TextLayout, VerticalAlign};
+
+/// A run of text sharing the same font, size, color and decorations
+#[derive(Clone, Copy)]
+pub struct TextSpan<'a> {
+    pub text: &'a str,
+    pub font: &'a dyn Font,
+    pub size: usize,
+    pub color: u32,
+    /// Color filling the line box behind the span
+    pub background: Option<u32>,
+    pub underline: bool,
+    pub strikethrough: bool,
+}
+
+impl<'a> TextSpan<'a> {
+    pub fn new(text: &'a str, font: &'a dyn Font, size: usize, color: u32) -> Self {
+        Self {
+            text,
+            font,
+            size,
+            color,
+            background: None,
+            underline: false,
+            strikethrough: false,
+        }
+    }
+
+    pub fn with_background(mut self, background: u32) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    pub fn with_underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn with_strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    /// Returns the same span with only a slice of its text
+    fn slice(&self, start: usize, end: usize) -> Self {
+        Self {
+            text: &self.text[start..end],
+            ..*self
+        }
+    }
+
+    fn ascent(&self) -> i32 {
+        self.font.ascent(self.size)
+    }
+
+    fn descent(&self) -> i32 {
+        self.font.line_height(self.size) - self.ascent()
+    }
+
+    /// Thickness of the underline and strikethrough
+    fn stroke(&self) -> i32 {
+        (self.font.line_height(self.size) / 10).max(1)
+    }
+}
+
+/// A span placed on a line by [`RichText::layout`]
+#[derive(Clone, Copy)]
+pub struct PlacedSpan<'a> {
+    pub span: TextSpan<'a>,
+    /// Horizontal position of the pen at the start of the span on the canvas
+    pub x: i32,
+    pub width: i32,
+}
+
+/// A line of [`PlacedSpan`]s sharing the same baseline
+#[derive(Clone)]
+pub struct RichLine<'a> {
+    pub spans: Vec<PlacedSpan<'a>>,
+    /// Position of the top of the line on the canvas
+    pub y: i32,
+    /// Distance from the top of the line to the baseline, the largest ascent of its
+    /// spans
+    pub ascent: i32,
+    pub height: i32,
+    pub width: i32,
+}
+
+impl RichLine<'_> {
+    pub fn baseline(&self) -> i32 {
+        self.y + self.ascent
+    }
+
+    pub fn draw(&self, canvas: &mut impl Drawable) {
+        let baseline = self.baseline();
+
+        // Backgrounds first so they don't cover the glyphs of neighbouring spans
+        for placed in &self.spans {
+            if let Some(background) = placed.span.background {
+                canvas.rect(placed.x, self.y, placed.width, self.height, background);
+            }
+        }
+
+        for &PlacedSpan { span, x, width } in &self.spans {
+            let top = baseline - span.ascent();
+            draw_chars(
+                canvas,
+                span.text.chars(),
+                x,
+                top,
+                span.font,
+                span.size,
+                span.color,
+            );
+
+            // Fonts without a descent have their baseline at the bottom of the line
+            if span.underline {
+                let y = baseline.min(self.y + self.height - span.stroke());
+                canvas.rect(x, y, width, span.stroke(), span.color);
+            }
+
+            if span.strikethrough {
+                let y = baseline - span.ascent() * 3 / 8 - span.stroke() / 2;
+                canvas.rect(x, y, width, span.stroke(), span.color);
+            }
+        }
+    }
+}
+
+/// Lines of spans positioned within a box by [`RichText::layout`]
+#[derive(Clone)]
+pub struct RichBlock<'a> {
+    pub lines: Vec<RichLine<'a>>,
+    /// Width of the widest line
+    pub width: i32,
+    /// Distance from the top of the first line to the bottom of the last one
+    pub height: i32,
+    /// Whether lines had to be left out to fit the box
+    pub truncated: bool,
+}
+
+impl RichBlock<'_> {
+    pub fn draw(&self, canvas: &mut impl Drawable) {
+        for line in &self.lines {
+            line.draw(canvas);
+        }
+    }
+}
+
+/// A piece of a span that lines are built from
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FragmentKind {
+    Word,
+    Space,
+    Newline,
+}
+
+#[derive(Clone, Copy)]
+struct Fragment {
+    span: usize,
+    start: usize,
+    end: usize,
+    kind: FragmentKind,
+}
+
+/// Text made of spans with their own fonts, sizes, colors and decorations, laid out
+/// on shared baselines and wrapped as a whole
+///
+/// Lines wrap between words, and words made of several spans stay together
+///
+/// ```
+/// use olivec::{ Canvas, FixedFont, Rect, RichText, TextLayout, TextSpan };
+///
+/// let mut canvas = Canvas::new(200, 50);
+/// let font = FixedFont::<6, 6>::default();
+///
+/// let text = RichText::new()
+///     .with_span(TextSpan::new("CPU: ", &font, 2, 0xFFCCCCCC))
+///     .with_span(TextSpan::new("93%", &font, 3, 0xFF0000FF).with_underline());
+///
+/// text.draw(&mut canvas, &TextLayout::new(), Rect::new(10, 10, 180, 30));
+/// ```
+#[derive(Clone, Default)]
+pub struct RichText<'a> {
+    pub spans: Vec<TextSpan<'a>>,
+}
+
+impl<'a> RichText<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_span(mut self, span: TextSpan<'a>) -> Self {
+        self.spans.push(span);
+        self
+    }
+
+    pub fn push(&mut self, span: TextSpan<'a>) {
+        self.spans.push(span);
+    }
+
+    /// Splits the spans into words, runs of spaces and newlines
+    fn fragments(&self) -> Vec<Fragment> {
+        let mut fragments: Vec<Fragment> = Vec::new();
+
+        for (span, TextSpan { text, .. }) in self.spans.iter().enumerate() {
+            for (start, c) in text.char_indices() {
+                let kind = match c {
+                    '\n' => FragmentKind::Newline,
+                    ' ' => FragmentKind::Space,
+                    _ => FragmentKind::Word,
+                };
+                let end = start + c.len_utf8();
+
+                match fragments.last_mut() {
+                    Some(last)
+                        if last.span == span
+                            && last.kind == kind
+                            && kind != FragmentKind::Newline =>
+                    {
+                        last.end = end
+                    }
+                    _ => fragments.push(Fragment {
+                        span,
+                        start,
+                        end,
+                        kind,
+                    }),
+                }
+            }
+        }
+
+        fragments
+    }
+
+    fn width_of(&self, fragment: &Fragment) -> i32 {
+        let span = &self.spans[fragment.span];
+        text_width(
+            &span.text[fragment.start..fragment.end],
+            span.font,
+            span.size,
+        )
+    }
+
+    /// Breaks the spans into lines no wider than `bounds` and positions them on
+    /// their baselines within it
+    ///
+    /// The alignment, line spacing and maximum number of lines of the layout are
+    /// used, justified lines are aligned to the left and lines are never ellipsized
+    pub fn layout(&self, options: &TextLayout, bounds: Rect) -> RichBlock<'a> {
+        let max_width = bounds.w.max(0);
+        let fragments = self.fragments();
+
+        // Lines of fragments along with the span whose metrics empty lines use
+        let mut lines: Vec<(Vec<Fragment>, usize)> = Vec::new();
+        let mut line: Vec<Fragment> = Vec::new();
+        let mut pen = 0;
+        let mut i = 0;
+
+        while i < fragments.len() {
+            let fragment = fragments[i];
+
+            match fragment.kind {
+                FragmentKind::Newline => {
+                    lines.push((std::mem::take(&mut line), fragment.span));
+                    pen = 0;
+                    i += 1;
+                }
+                FragmentKind::Space => {
+                    pen += self.width_of(&fragment);
+                    line.push(fragment);
+                    i += 1;
+                }
+                FragmentKind::Word => {
+                    // Words made of several spans are wrapped as one
+                    let word_end = fragments[i..]
+                        .iter()
+                        .position(|f| f.kind != FragmentKind::Word)
+                        .map_or(fragments.len(), |n| i + n);
+
+                    let word = &fragments[i..word_end];
+                    let width: i32 = word.iter().map(|f| self.width_of(f)).sum();
+                    let has_word = line.iter().any(|f| f.kind == FragmentKind::Word);
+
+                    if options.wrap && pen + width > max_width && has_word {
+                        // Spaces at the wrapping point are dropped
+                        lines.push((trim_spaces(std::mem::take(&mut line)), fragment.span));
+                        pen = 0;
+                    }
+
+                    if options.wrap && pen + width > max_width {
+                        // Words that don't fit on a line by themselves are broken
+                        // between characters
+                        for &f in word {
+                            let span = &self.spans[f.span];
+
+                            for (start, c) in span.text[f.start..f.end].char_indices() {
+                                let start = f.start + start;
+                                let end = start + c.len_utf8();
+                                let char_fragment = Fragment { start, end, ..f };
+                                let char_width = self.width_of(&char_fragment);
+
+                                if pen + char_width > max_width && !line.is_empty() {
+                                    lines.push((std::mem::take(&mut line), f.span));
+                                    pen = 0;
+                                }
+
+                                push_merged(&mut line, char_fragment);
+                                pen += char_width;
+                            }
+                        }
+                    } else {
+                        line.extend_from_slice(word);
+                        pen += width;
+                    }
+
+                    i = word_end;
+                }
+            }
+        }
+
+        let last_span = fragments.last().map_or(0, |f| f.span);
+        lines.push((line, last_span));
+
+        self.position(lines, options, bounds)
+    }
+
+    /// Computes the metrics of the lines and aligns them within the bounds
+    fn position(
+        &self,
+        lines: Vec<(Vec<Fragment>, usize)>,
+        options: &TextLayout,
+        bounds: Rect,
+    ) -> RichBlock<'a> {
+        let mut placed: Vec<RichLine<'a>> = Vec::new();
+        let mut top = 0;
+        let mut truncated = false;
+
+        for (fragments, fallback_span) in lines {
+            let fragments = trim_spaces(fragments);
+            let spans: Vec<&TextSpan> = if fragments.is_empty() {
+                self.spans.get(fallback_span).into_iter().collect()
+            } else {
+                fragments.iter().map(|f| &self.spans[f.span]).collect()
+            };
+
+            let ascent = spans.iter().map(|s| s.ascent()).max().unwrap_or(0);
+            let descent = spans.iter().map(|s| s.descent()).max().unwrap_or(0);
+            let height = ascent + descent;
+
+            let too_many = options.max_lines.is_some_and(|max| placed.len() >= max);
+            let too_low = !placed.is_empty() && top + height > bounds.h;
+
+            if too_many || too_low {
+                truncated = true;
+                break;
+            }
+
+            // Fragments of the same span are contiguous, so they merge back into a
+            // slice of its text, measured as a whole to keep the kerning between them
+            let mut merged: Vec<Fragment> = Vec::new();
+
+            for fragment in fragments {
+                push_merged(&mut merged, fragment);
+            }
+
+            let mut pen = 0;
+            let line_spans: Vec<PlacedSpan<'a>> = merged
+                .into_iter()
+                .map(|fragment| {
+                    let x = pen;
+                    let width = self.width_of(&fragment);
+                    pen += width;
+
+                    PlacedSpan {
+                        span: self.spans[fragment.span].slice(fragment.start, fragment.end),
+                        x,
+                        width,
+                    }
+                })
+                .collect();
+
+            placed.push(RichLine {
+                spans: line_spans,
+                y: top,
+                ascent,
+                height,
+                width: pen,
+            });
+
+            top += (height as f32 * options.line_spacing).round() as i32;
+        }
+
+        let height = placed.last().map_or(0, |line| line.y + line.height);
+        let dy = match options.vertical_align {
+            VerticalAlign::Top => bounds.y,
+            VerticalAlign::Middle => bounds.y + (bounds.h - height) / 2,
+            VerticalAlign::Bottom => bounds.y + bounds.h - height,
+        };
+
+        for line in &mut placed {
+            let free = bounds.w - line.width;
+            let dx = match options.align {
+                TextAlign::Left | TextAlign::Justify => bounds.x,
+                TextAlign::Center => bounds.x + free / 2,
+                TextAlign::Right => bounds.x + free,
+            };
+
+            line.y += dy;
+
+            for span in &mut line.spans {
+                span.x += dx;
+            }
+        }
+
+        RichBlock {
+            width: placed.iter().map(|line| line.width).max().unwrap_or(0),
+            height,
+            lines: placed,
+            truncated,
+        }
+    }
+
+    /// Lays the spans out within `bounds` and draws them
+    pub fn draw(
+        &self,
+        canvas: &mut impl Drawable,
+        options: &TextLayout,
+        bounds: Rect,
+    ) -> RichBlock<'a> {
+        let block = self.layout(options, bounds);
+        block.draw(canvas);
+        block
+    }
+}
+
+fn trim_spaces(mut line: Vec<Fragment>) -> Vec<Fragment> {
+    while line.last().is_some_and(|f| f.kind == FragmentKind::Space) {
+        line.pop();
+    }
+
+    line
+}
+
+/// Appends the fragment, extending the last one if it directly precedes it
+fn push_merged(line: &mut Vec<Fragment>, fragment: Fragment) {
+    match line.last_mut() {
+        Some(last) if last.span == fragment.span && last.end == fragment.start => {
+            last.end = fragment.end
+        }
+        _ => line.push(fragment),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Canvas, FixedFont, Glyph};
+
+    const W: u32 = 0xFFFFFFFF;
+    const B: u32 = 0xFF000000;
+
+    /// Pulls every pair of characters a pixel closer
+    struct Kerned(FixedFont<6, 6>);
+
+    impl Font for Kerned {
+        fn glyph(&self, c: char, size: usize) -> Option<Glyph<'_>> {
+            Font::glyph(&self.0, c, size)
+        }
+
+        fn line_height(&self, size: usize) -> i32 {
+            self.0.line_height(size)
+        }
+
+        fn ascent(&self, size: usize) -> i32 {
+            self.0.ascent(size)
+        }
+
+        fn kerning(&self, _left: char, _right: char, _size: usize) -> i32 {
+            -1
+        }
+    }
+
+    fn texts<'a>(block: &RichBlock<'a>) -> Vec<Vec<&'a str>> {
+        block
+            .lines
+            .iter()
+            .map(|line| line.spans.iter().map(|placed| placed.span.text).collect())
+            .collect()
+    }
+
+    #[test]
+    fn spans_wrap_as_a_whole() {
+        let font = FixedFont::<6, 6>::default();
+        let text = RichText::new()
+            .with_span(TextSpan::new("aa b", &font, 1, W))
+            .with_span(TextSpan::new("b cc\ndd", &font, 1, W));
+
+        let block = text.layout(&TextLayout::new(), Rect::new(0, 0, 36, 100));
+
+        // The word "bb" spanning both spans is kept together
+        assert_eq!(texts(&block), [vec!["aa b", "b"], vec!["cc"], vec!["dd"]]);
+        assert_eq!(block.lines[0].spans[1].x, 24);
+        assert_eq!(block.lines[0].width, 30);
+        assert_eq!((block.width, block.height), (30, 18));
+    }
+
+    #[test]
+    fn long_words_break_between_characters() {
+        let font = FixedFont::<6, 6>::default();
+        let text = RichText::new().with_span(TextSpan::new("a bcdefg", &font, 1, W));
+
+        let block = text.layout(&TextLayout::new(), Rect::new(0, 0, 18, 100));
+
+        assert_eq!(texts(&block), [vec!["a"], vec!["bcd"], vec!["efg"]]);
+    }
+
+    #[test]
+    fn merged_fragments_keep_their_kerning() {
+        let font = Kerned(FixedFont::<6, 6>::default());
+        let text = RichText::new().with_span(TextSpan::new("a b", &font, 1, W));
+
+        let block = text.layout(&TextLayout::new(), Rect::new(0, 0, 100, 100));
+
+        assert_eq!(texts(&block), [vec!["a b"]]);
+        assert_eq!(block.lines[0].spans[0].width, text_width("a b", &font, 1));
+        assert_eq!(block.lines[0].width, 16);
+    }
+
+    #[test]
+    fn spans_share_the_baseline_of_their_line() {
+        let font = FixedFont::<6, 6>::default();
+        let text = RichText::new()
+            .with_span(TextSpan::new("a", &font, 1, W))
+            .with_span(TextSpan::new("b", &font, 2, W));
+
+        let layout = TextLayout::new().with_align(TextAlign::Right);
+        let block = text.layout(&layout, Rect::new(10, 20, 40, 100));
+        let line = &block.lines[0];
+
+        assert_eq!((line.y, line.ascent, line.height), (20, 12, 12));
+        assert_eq!(line.baseline(), 32);
+        assert_eq!(line.spans[0].x, 32);
+        assert_eq!(line.spans[1].x, 38);
+    }
+
+    #[test]
+    fn lines_past_the_box_are_left_out() {
+        let font = FixedFont::<6, 6>::default();
+        let text = RichText::new().with_span(TextSpan::new("aa bb cc", &font, 1, W));
+
+        let block = text.layout(&TextLayout::new(), Rect::new(0, 0, 12, 13));
+        assert_eq!(texts(&block), [vec!["aa"], vec!["bb"]]);
+        assert!(block.truncated);
+
+        let layout = TextLayout::new().with_max_lines(1);
+        let block = text.layout(&layout, Rect::new(0, 0, 12, 100));
+        assert_eq!(texts(&block), [vec!["aa"]]);
+        assert!(block.truncated);
+    }
+
+    #[test]
+    fn underline_stays_within_the_line() {
+        let font = FixedFont::<6, 6>::default();
+        let text = RichText::new().with_span(TextSpan::new("a", &font, 1, W).with_underline());
+
+        let mut canvas = Canvas::new(8, 8);
+        canvas.fill(B);
+        text.draw(&mut canvas, &TextLayout::new(), Rect::new(0, 0, 8, 8));
+
+        let row = |y: usize| &canvas.pixels()[y * 8..y * 8 + 6];
+        assert!(row(5).iter().all(|&p| p == W));
+        assert!(row(6).iter().all(|&p| p == B));
+    }
+}
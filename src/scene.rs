@@ -0,0 +1,1317 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Canvas, DisplayList, DrawCommand, Drawable};
+
+/// The newest version of the scene format understood by this crate
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+/// Magic bytes at the start of the binary encoding of a scene
+const BINARY_MAGIC: &[u8; 4] = b"OLVS";
+
+/// Largest width or height of a scene, so that untrusted scenes can't make
+/// [`Scene::render`] allocate an arbitrarily large canvas
+pub const MAX_SCENE_SIZE: usize = 16384;
+
+/// Largest number of pixels of a scene, a 256 MiB canvas
+pub const MAX_SCENE_PIXELS: usize = 1 << 26;
+
+/// Largest absolute value of the coordinates and sizes of scene commands, so that
+/// untrusted scenes can't make drawing overflow
+pub const MAX_SCENE_COORDINATE: i32 = 1 << 20;
+
+/// A document describing a canvas and the primitives to draw on it, meant to be
+/// exchanged with other programs
+///
+/// # JSON
+///
+/// The JSON encoding is an object with the following fields, where colors are
+/// strings in the `#RRGGBB` or `#RRGGBBAA` form (plain packed integers are
+/// accepted too):
+///
+/// ```json
+/// {
+///     "version": 1,
+///     "width": 400,
+///     "height": 300,
+///     "background": "#181818",
+///     "commands": [
+///         { "type": "rect", "x": 10, "y": 10, "w": 100, "h": 50, "color": "#FF2020" },
+///         { "type": "text", "text": "hi", "x": 20, "y": 20, "size": 3, "color": "#FFFFFF" },
+///         { "type": "sprite_copy", "x": 200, "y": 10, "w": 64, "h": 64, "asset": "logo" }
+///     ]
+/// }
+/// ```
+///
+/// `background` is optional. The commands and their fields match the arguments of
+/// the [`Drawable`] function of the same name, with sprites referenced by an asset id
+/// that is resolved when rendering. `sprite_copy` takes an optional `bilinear` flag.
+/// Coordinates and sizes are limited to [`MAX_SCENE_COORDINATE`].
+///
+/// # Binary
+///
+/// The binary encoding stores the same data in little endian order:
+///
+/// | Field      | Encoding                                            |
+/// |------------|-----------------------------------------------------|
+/// | magic      | the 4 bytes `OLVS`                                  |
+/// | version    | `u32`                                               |
+/// | width      | `u32`                                               |
+/// | height     | `u32`                                               |
+/// | background | `u8` flag, followed by a `u32` color if it is 1     |
+/// | commands   | `u32` count, followed by every command              |
+///
+/// Each command is a `u8` tag (its position in [`SceneCommand`], starting at 0)
+/// followed by its fields in declaration order. Integers are `i32`, colors `u32`,
+/// sizes `u32`, flags `u8` and strings a `u32` byte length followed by UTF-8 bytes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    #[serde(default, with = "hex_color::option")]
+    pub background: Option<u32>,
+    #[serde(default)]
+    pub commands: Vec<SceneCommand>,
+}
+
+/// A primitive of a [`Scene`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SceneCommand {
+    Fill {
+        #[serde(with = "hex_color")]
+        color: u32,
+    },
+    Rect {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        #[serde(with = "hex_color")]
+        color: u32,
+    },
+    Frame {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        thiccness: usize,
+        #[serde(with = "hex_color")]
+        color: u32,
+    },
+    Circle {
+        cx: i32,
+        cy: i32,
+        r: i32,
+        #[serde(with = "hex_color")]
+        color: u32,
+    },
+    Ellipse {
+        cx: i32,
+        cy: i32,
+        rx: i32,
+        ry: i32,
+        #[serde(with = "hex_color")]
+        color: u32,
+    },
+    Line {
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        #[serde(with = "hex_color")]
+        color: u32,
+    },
+    Triangle {
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        #[serde(with = "hex_color")]
+        color: u32,
+    },
+    Triangle3c {
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        #[serde(with = "hex_color")]
+        c1: u32,
+        #[serde(with = "hex_color")]
+        c2: u32,
+        #[serde(with = "hex_color")]
+        c3: u32,
+    },
+    Text {
+        text: String,
+        x: i32,
+        y: i32,
+        size: usize,
+        #[serde(with = "hex_color")]
+        color: u32,
+    },
+    SpriteBlend {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        asset: String,
+    },
+    SpriteCopy {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        asset: String,
+        #[serde(default)]
+        bilinear: bool,
+    },
+}
+
+/// Error produced while decoding or rendering a [`Scene`]
+#[derive(Debug)]
+pub enum SceneError {
+    /// The scene was written for a newer version of the format
+    UnsupportedVersion(u32),
+    /// The scene is larger than [`MAX_SCENE_SIZE`] or [`MAX_SCENE_PIXELS`]
+    TooLarge {
+        width: usize,
+        height: usize,
+    },
+    /// The command at the index has a coordinate or size beyond
+    /// [`MAX_SCENE_COORDINATE`]
+    OutOfRange(usize),
+    /// A sprite references an asset id that was not provided
+    MissingAsset(String),
+    Json(serde_json::Error),
+    /// The binary encoding was malformed
+    Binary(String),
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported scene version {version}, the newest supported version is {SCENE_FORMAT_VERSION}"
+            ),
+            SceneError::TooLarge { width, height } => write!(
+                f,
+                "scene of {width}x{height} pixels is too large, the limit is {MAX_SCENE_SIZE} pixels per side and {MAX_SCENE_PIXELS} pixels in total"
+            ),
+            SceneError::OutOfRange(index) => write!(
+                f,
+                "command {index} has a coordinate or size beyond {MAX_SCENE_COORDINATE}"
+            ),
+            SceneError::MissingAsset(id) => write!(f, "missing asset `{id}`"),
+            SceneError::Json(err) => write!(f, "{err}"),
+            SceneError::Binary(message) => write!(f, "invalid binary scene: {message}"),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for SceneError {
+    fn from(value: serde_json::Error) -> Self {
+        SceneError::Json(value)
+    }
+}
+
+/// Canvases that sprites of a [`Scene`] can refer to by their asset id
+#[derive(Default)]
+pub struct SceneAssets {
+    ids: HashMap<String, usize>,
+    canvases: Vec<Canvas>,
+}
+
+impl SceneAssets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a canvas under the provided id, replacing any previous asset with
+    /// the same id
+    pub fn insert(&mut self, id: impl Into<String>, canvas: Canvas) {
+        match self.ids.entry(id.into()) {
+            std::collections::hash_map::Entry::Occupied(entry) => {
+                self.canvases[*entry.get()] = canvas;
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(self.canvases.len());
+                self.canvases.push(canvas);
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Canvas> {
+        self.ids.get(id).map(|&index| &self.canvases[index])
+    }
+
+    /// Returns the sprite table that the display lists created by
+    /// [`Scene::to_display_list`] index into
+    pub fn canvases(&self) -> &[Canvas] {
+        &self.canvases
+    }
+}
+
+impl Scene {
+    /// Creates an empty scene of the newest version
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            version: SCENE_FORMAT_VERSION,
+            width,
+            height,
+            background: None,
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SceneError> {
+        let scene: Scene = serde_json::from_str(json)?;
+        scene.check_version()?;
+        check_size(scene.width, scene.height)?;
+        check_commands(&scene.commands)?;
+
+        Ok(scene)
+    }
+
+    pub fn to_json(&self) -> Result<String, SceneError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    fn check_version(&self) -> Result<(), SceneError> {
+        if self.version > SCENE_FORMAT_VERSION {
+            Err(SceneError::UnsupportedVersion(self.version))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the ids of every asset referenced by the scene, without duplicates
+    pub fn asset_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = Vec::new();
+
+        for command in &self.commands {
+            if let SceneCommand::SpriteBlend { asset, .. }
+            | SceneCommand::SpriteCopy { asset, .. } = command
+            {
+                if !ids.contains(&asset.as_str()) {
+                    ids.push(asset);
+                }
+            }
+        }
+
+        ids
+    }
+
+    /// Converts the scene into a display list, with sprite indices referring to the
+    /// canvases of `assets`
+    ///
+    /// The background, if any, becomes a leading fill
+    pub fn to_display_list(&self, assets: &SceneAssets) -> Result<DisplayList, SceneError> {
+        check_commands(&self.commands)?;
+
+        let asset_index = |id: &String| {
+            assets
+                .ids
+                .get(id)
+                .copied()
+                .ok_or_else(|| SceneError::MissingAsset(id.clone()))
+        };
+
+        let mut list = DisplayList::new();
+
+        if let Some(color) = self.background {
+            list.fill(color);
+        }
+
+        for command in &self.commands {
+            list.push(match command.clone() {
+                SceneCommand::Fill { color } => DrawCommand::Fill { color },
+                SceneCommand::Rect { x, y, w, h, color } => DrawCommand::Rect { x, y, w, h, color },
+                SceneCommand::Frame {
+                    x,
+                    y,
+                    w,
+                    h,
+                    thiccness,
+                    color,
+                } => DrawCommand::Frame {
+                    x,
+                    y,
+                    w,
+                    h,
+                    thiccness,
+                    color,
+                },
+                SceneCommand::Circle { cx, cy, r, color } => {
+                    DrawCommand::Circle { cx, cy, r, color }
+                }
+                SceneCommand::Ellipse {
+                    cx,
+                    cy,
+                    rx,
+                    ry,
+                    color,
+                } => DrawCommand::Ellipse {
+                    cx,
+                    cy,
+                    rx,
+                    ry,
+                    color,
+                },
+                SceneCommand::Line {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    color,
+                } => DrawCommand::Line {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    color,
+                },
+                SceneCommand::Triangle {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x3,
+                    y3,
+                    color,
+                } => DrawCommand::Triangle {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x3,
+                    y3,
+                    color,
+                },
+                SceneCommand::Triangle3c {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x3,
+                    y3,
+                    c1,
+                    c2,
+                    c3,
+                } => DrawCommand::Triangle3c {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x3,
+                    y3,
+                    c1,
+                    c2,
+                    c3,
+                },
+                SceneCommand::Text {
+                    text,
+                    x,
+                    y,
+                    size,
+                    color,
+                } => DrawCommand::Text {
+                    text,
+                    x,
+                    y,
+                    size,
+                    color,
+                },
+                SceneCommand::SpriteBlend {
+                    x,
+                    y,
+                    w,
+                    h,
+                    ref asset,
+                } => DrawCommand::SpriteBlend {
+                    x,
+                    y,
+                    w,
+                    h,
+                    sprite: asset_index(asset)?,
+                },
+                SceneCommand::SpriteCopy {
+                    x,
+                    y,
+                    w,
+                    h,
+                    ref asset,
+                    bilinear,
+                } => DrawCommand::SpriteCopy {
+                    x,
+                    y,
+                    w,
+                    h,
+                    sprite: asset_index(asset)?,
+                    bilinear,
+                },
+            });
+        }
+
+        Ok(list)
+    }
+
+    /// Renders the scene into a new canvas of its size
+    ///
+    /// Fails without allocating anything if the scene is larger than
+    /// [`MAX_SCENE_SIZE`] or [`MAX_SCENE_PIXELS`]
+    ///
+    /// ```
+    /// use olivec::{Drawable, Scene, SceneAssets};
+    ///
+    /// let scene = Scene::from_json(r##"{
+    ///     "version": 1,
+    ///     "width": 64,
+    ///     "height": 64,
+    ///     "background": "#000000",
+    ///     "commands": [{ "type": "circle", "cx": 32, "cy": 32, "r": 16, "color": "#FF0000" }]
+    /// }"##).unwrap();
+    ///
+    /// let canvas = scene.render(&SceneAssets::new()).unwrap();
+    ///
+    /// assert_eq!(canvas.get_width(), 64);
+    /// ```
+    pub fn render(&self, assets: &SceneAssets) -> Result<Canvas, SceneError> {
+        check_size(self.width, self.height)?;
+
+        let mut canvas = Canvas::new(self.width, self.height);
+        self.render_into(&mut canvas, assets)?;
+
+        Ok(canvas)
+    }
+
+    /// Renders the scene onto an existing canvas, which does not need to match the
+    /// size of the scene
+    pub fn render_into(
+        &self,
+        canvas: &mut impl Drawable,
+        assets: &SceneAssets,
+    ) -> Result<(), SceneError> {
+        self.to_display_list(assets)?
+            .replay(canvas, &assets.canvases);
+
+        Ok(())
+    }
+
+    /// Encodes the scene in the compact binary format
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut w = BinaryWriter(&mut out);
+
+        w.bytes(BINARY_MAGIC);
+        w.u32(self.version);
+        w.u32(self.width as u32);
+        w.u32(self.height as u32);
+
+        match self.background {
+            Some(color) => {
+                w.u8(1);
+                w.u32(color);
+            }
+            None => w.u8(0),
+        }
+
+        w.u32(self.commands.len() as u32);
+
+        for command in &self.commands {
+            match command {
+                SceneCommand::Fill { color } => {
+                    w.u8(0);
+                    w.u32(*color);
+                }
+                SceneCommand::Rect {
+                    x,
+                    y,
+                    w: rw,
+                    h,
+                    color,
+                } => {
+                    w.u8(1);
+                    w.i32s(&[*x, *y, *rw, *h]);
+                    w.u32(*color);
+                }
+                SceneCommand::Frame {
+                    x,
+                    y,
+                    w: rw,
+                    h,
+                    thiccness,
+                    color,
+                } => {
+                    w.u8(2);
+                    w.i32s(&[*x, *y, *rw, *h]);
+                    w.u32(*thiccness as u32);
+                    w.u32(*color);
+                }
+                SceneCommand::Circle { cx, cy, r, color } => {
+                    w.u8(3);
+                    w.i32s(&[*cx, *cy, *r]);
+                    w.u32(*color);
+                }
+                SceneCommand::Ellipse {
+                    cx,
+                    cy,
+                    rx,
+                    ry,
+                    color,
+                } => {
+                    w.u8(4);
+                    w.i32s(&[*cx, *cy, *rx, *ry]);
+                    w.u32(*color);
+                }
+                SceneCommand::Line {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    color,
+                } => {
+                    w.u8(5);
+                    w.i32s(&[*x1, *y1, *x2, *y2]);
+                    w.u32(*color);
+                }
+                SceneCommand::Triangle {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x3,
+                    y3,
+                    color,
+                } => {
+                    w.u8(6);
+                    w.i32s(&[*x1, *y1, *x2, *y2, *x3, *y3]);
+                    w.u32(*color);
+                }
+                SceneCommand::Triangle3c {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x3,
+                    y3,
+                    c1,
+                    c2,
+                    c3,
+                } => {
+                    w.u8(7);
+                    w.i32s(&[*x1, *y1, *x2, *y2, *x3, *y3]);
+                    w.u32(*c1);
+                    w.u32(*c2);
+                    w.u32(*c3);
+                }
+                SceneCommand::Text {
+                    text,
+                    x,
+                    y,
+                    size,
+                    color,
+                } => {
+                    w.u8(8);
+                    w.string(text);
+                    w.i32s(&[*x, *y]);
+                    w.u32(*size as u32);
+                    w.u32(*color);
+                }
+                SceneCommand::SpriteBlend {
+                    x,
+                    y,
+                    w: rw,
+                    h,
+                    asset,
+                } => {
+                    w.u8(9);
+                    w.i32s(&[*x, *y, *rw, *h]);
+                    w.string(asset);
+                }
+                SceneCommand::SpriteCopy {
+                    x,
+                    y,
+                    w: rw,
+                    h,
+                    asset,
+                    bilinear,
+                } => {
+                    w.u8(10);
+                    w.i32s(&[*x, *y, *rw, *h]);
+                    w.string(asset);
+                    w.u8(*bilinear as u8);
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Decodes a scene from the compact binary format
+    pub fn from_binary(data: &[u8]) -> Result<Self, SceneError> {
+        let mut r = BinaryReader(data);
+
+        if r.take(4)? != BINARY_MAGIC {
+            return Err(SceneError::Binary("missing magic bytes".into()));
+        }
+
+        let version = r.u32()?;
+
+        if version > SCENE_FORMAT_VERSION {
+            return Err(SceneError::UnsupportedVersion(version));
+        }
+
+        let width = r.u32()? as usize;
+        let height = r.u32()? as usize;
+        check_size(width, height)?;
+
+        let background = match r.u8()? {
+            0 => None,
+            _ => Some(r.u32()?),
+        };
+
+        let count = r.u32()?;
+        let mut commands = Vec::new();
+
+        for _ in 0..count {
+            let command = match r.u8()? {
+                0 => SceneCommand::Fill { color: r.u32()? },
+                1 => {
+                    let [x, y, w, h] = r.i32s()?;
+                    SceneCommand::Rect {
+                        x,
+                        y,
+                        w,
+                        h,
+                        color: r.u32()?,
+                    }
+                }
+                2 => {
+                    let [x, y, w, h] = r.i32s()?;
+                    SceneCommand::Frame {
+                        x,
+                        y,
+                        w,
+                        h,
+                        thiccness: r.u32()? as usize,
+                        color: r.u32()?,
+                    }
+                }
+                3 => {
+                    let [cx, cy, r_] = r.i32s()?;
+                    SceneCommand::Circle {
+                        cx,
+                        cy,
+                        r: r_,
+                        color: r.u32()?,
+                    }
+                }
+                4 => {
+                    let [cx, cy, rx, ry] = r.i32s()?;
+                    SceneCommand::Ellipse {
+                        cx,
+                        cy,
+                        rx,
+                        ry,
+                        color: r.u32()?,
+                    }
+                }
+                5 => {
+                    let [x1, y1, x2, y2] = r.i32s()?;
+                    SceneCommand::Line {
+                        x1,
+                        y1,
+                        x2,
+                        y2,
+                        color: r.u32()?,
+                    }
+                }
+                6 => {
+                    let [x1, y1, x2, y2, x3, y3] = r.i32s()?;
+                    SceneCommand::Triangle {
+                        x1,
+                        y1,
+                        x2,
+                        y2,
+                        x3,
+                        y3,
+                        color: r.u32()?,
+                    }
+                }
+                7 => {
+                    let [x1, y1, x2, y2, x3, y3] = r.i32s()?;
+                    SceneCommand::Triangle3c {
+                        x1,
+                        y1,
+                        x2,
+                        y2,
+                        x3,
+                        y3,
+                        c1: r.u32()?,
+                        c2: r.u32()?,
+                        c3: r.u32()?,
+                    }
+                }
+                8 => {
+                    let text = r.string()?;
+                    let [x, y] = r.i32s()?;
+                    SceneCommand::Text {
+                        text,
+                        x,
+                        y,
+                        size: r.u32()? as usize,
+                        color: r.u32()?,
+                    }
+                }
+                9 => {
+                    let [x, y, w, h] = r.i32s()?;
+                    SceneCommand::SpriteBlend {
+                        x,
+                        y,
+                        w,
+                        h,
+                        asset: r.string()?,
+                    }
+                }
+                10 => {
+                    let [x, y, w, h] = r.i32s()?;
+                    SceneCommand::SpriteCopy {
+                        x,
+                        y,
+                        w,
+                        h,
+                        asset: r.string()?,
+                        bilinear: r.u8()? != 0,
+                    }
+                }
+                tag => return Err(SceneError::Binary(format!("unknown command tag {tag}"))),
+            };
+
+            commands.push(command);
+        }
+
+        if !r.0.is_empty() {
+            return Err(SceneError::Binary("trailing bytes".into()));
+        }
+
+        check_commands(&commands)?;
+
+        Ok(Self {
+            version,
+            width,
+            height,
+            background,
+            commands,
+        })
+    }
+}
+
+/// Rejects scenes larger than [`MAX_SCENE_SIZE`] or [`MAX_SCENE_PIXELS`]
+fn check_size(width: usize, height: usize) -> Result<(), SceneError> {
+    if width > MAX_SCENE_SIZE || height > MAX_SCENE_SIZE || width * height > MAX_SCENE_PIXELS {
+        Err(SceneError::TooLarge { width, height })
+    } else {
+        Ok(())
+    }
+}
+
+/// Rejects commands with coordinates or sizes beyond [`MAX_SCENE_COORDINATE`]
+fn check_commands(commands: &[SceneCommand]) -> Result<(), SceneError> {
+    for (index, command) in commands.iter().enumerate() {
+        let (coordinates, sizes): (Vec<i32>, Vec<usize>) = match command {
+            SceneCommand::Fill { .. } => (vec![], vec![]),
+            SceneCommand::Rect { x, y, w, h, .. }
+            | SceneCommand::SpriteBlend { x, y, w, h, .. }
+            | SceneCommand::SpriteCopy { x, y, w, h, .. } => (vec![*x, *y, *w, *h], vec![]),
+            SceneCommand::Frame {
+                x,
+                y,
+                w,
+                h,
+                thiccness,
+                ..
+            } => (vec![*x, *y, *w, *h], vec![*thiccness]),
+            SceneCommand::Circle { cx, cy, r, .. } => (vec![*cx, *cy, *r], vec![]),
+            SceneCommand::Ellipse { cx, cy, rx, ry, .. } => (vec![*cx, *cy, *rx, *ry], vec![]),
+            SceneCommand::Line { x1, y1, x2, y2, .. } => (vec![*x1, *y1, *x2, *y2], vec![]),
+            SceneCommand::Triangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            }
+            | SceneCommand::Triangle3c {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                ..
+            } => (vec![*x1, *y1, *x2, *y2, *x3, *y3], vec![]),
+            SceneCommand::Text { x, y, size, .. } => (vec![*x, *y], vec![*size]),
+        };
+
+        let max = MAX_SCENE_COORDINATE.unsigned_abs();
+
+        if coordinates.iter().any(|value| value.unsigned_abs() > max)
+            || sizes.iter().any(|&size| size > max as usize)
+        {
+            return Err(SceneError::OutOfRange(index));
+        }
+    }
+
+    Ok(())
+}
+
+struct BinaryWriter<'a>(&'a mut Vec<u8>);
+
+impl BinaryWriter<'_> {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn i32s(&mut self, values: &[i32]) {
+        for value in values {
+            self.bytes(&value.to_le_bytes());
+        }
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes(value.as_bytes());
+    }
+}
+
+struct BinaryReader<'a>(&'a [u8]);
+
+impl<'a> BinaryReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SceneError> {
+        if self.0.len() < n {
+            return Err(SceneError::Binary("unexpected end of data".into()));
+        }
+
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, SceneError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, SceneError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn i32s<const N: usize>(&mut self) -> Result<[i32; N], SceneError> {
+        let mut values = [0; N];
+
+        for value in values.iter_mut() {
+            *value = i32::from_le_bytes(self.take(4)?.try_into().unwrap());
+        }
+
+        Ok(values)
+    }
+
+    fn string(&mut self) -> Result<String, SceneError> {
+        let len = self.u32()? as usize;
+
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| SceneError::Binary("string is not valid UTF-8".into()))
+    }
+}
+
+/// Serializes colors as `#RRGGBBAA` strings, and accepts both strings and packed
+/// integers when deserializing
+mod hex_color {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use crate::{format_hex_color, parse_hex_color};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Hex(String),
+        Packed(u32),
+    }
+
+    impl Repr {
+        fn into_color<E: de::Error>(self) -> Result<u32, E> {
+            match self {
+                Repr::Packed(color) => Ok(color),
+                Repr::Hex(hex) => {
+                    parse_hex_color(&hex).ok_or_else(|| E::custom(format!("invalid color `{hex}`")))
+                }
+            }
+        }
+    }
+
+    pub fn serialize<S: Serializer>(color: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format_hex_color(*color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        Repr::deserialize(deserializer)?.into_color()
+    }
+
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        use super::Repr;
+
+        pub fn serialize<S: Serializer>(
+            color: &Option<u32>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match color {
+                Some(color) => super::serialize(color, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<u32>, D::Error> {
+            Option::<Repr>::deserialize(deserializer)?
+                .map(Repr::into_color)
+                .transpose()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every_command() -> Scene {
+        let mut scene = Scene::new(32, 16);
+        scene.background = Some(0xFF181818);
+        scene.commands = vec![
+            SceneCommand::Fill { color: 0xFF000000 },
+            SceneCommand::Rect {
+                x: 1,
+                y: -2,
+                w: 3,
+                h: 4,
+                color: 0xFF0000FF,
+            },
+            SceneCommand::Frame {
+                x: 0,
+                y: 0,
+                w: 8,
+                h: 8,
+                thiccness: 2,
+                color: 0x8000FF00,
+            },
+            SceneCommand::Circle {
+                cx: 4,
+                cy: 4,
+                r: 3,
+                color: 0xFFFF0000,
+            },
+            SceneCommand::Ellipse {
+                cx: 10,
+                cy: 5,
+                rx: 4,
+                ry: 2,
+                color: 0xFF00FFFF,
+            },
+            SceneCommand::Line {
+                x1: 0,
+                y1: 0,
+                x2: 31,
+                y2: 15,
+                color: 0xFFFFFFFF,
+            },
+            SceneCommand::Triangle {
+                x1: 0,
+                y1: 0,
+                x2: 5,
+                y2: 0,
+                x3: 0,
+                y3: 5,
+                color: 0xFF123456,
+            },
+            SceneCommand::Triangle3c {
+                x1: 1,
+                y1: 1,
+                x2: 9,
+                y2: 1,
+                x3: 1,
+                y3: 9,
+                c1: 0xFF0000FF,
+                c2: 0xFF00FF00,
+                c3: 0xFFFF0000,
+            },
+            SceneCommand::Text {
+                text: "héllo".into(),
+                x: 2,
+                y: 3,
+                size: 1,
+                color: 0xFFABCDEF,
+            },
+            SceneCommand::SpriteBlend {
+                x: 0,
+                y: 0,
+                w: 4,
+                h: 4,
+                asset: "logo".into(),
+            },
+            SceneCommand::SpriteCopy {
+                x: 4,
+                y: 4,
+                w: 8,
+                h: 8,
+                asset: "logo".into(),
+                bilinear: true,
+            },
+        ];
+        scene
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let scene = every_command();
+        let json = scene.to_json().unwrap();
+
+        assert_eq!(Scene::from_json(&json).unwrap(), scene);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let scene = every_command();
+
+        assert_eq!(Scene::from_binary(&scene.to_binary()).unwrap(), scene);
+    }
+
+    #[test]
+    fn json_colors_are_hex_or_packed() {
+        let scene = Scene::from_json(
+            r##"{
+                "version": 1,
+                "width": 4,
+                "height": 4,
+                "background": "#102030",
+                "commands": [{ "type": "fill", "color": 4278190335 }]
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(scene.background, Some(0xFF302010));
+        assert_eq!(scene.commands, [SceneCommand::Fill { color: 0xFF0000FF }]);
+        assert!(scene.to_json().unwrap().contains("\"#FF0000FF\""));
+
+        for color in ["#F80", "#+1+2+3", "#-1-2-3"] {
+            let invalid =
+                format!(r#"{{ "version": 1, "width": 4, "height": 4, "background": "{color}" }}"#);
+            assert!(matches!(
+                Scene::from_json(&invalid),
+                Err(SceneError::Json(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let mut scene = Scene::new(4, 4);
+        scene.version = SCENE_FORMAT_VERSION + 1;
+
+        assert!(matches!(
+            Scene::from_json(&scene.to_json().unwrap()),
+            Err(SceneError::UnsupportedVersion(v)) if v == SCENE_FORMAT_VERSION + 1
+        ));
+        assert!(matches!(
+            Scene::from_binary(&scene.to_binary()),
+            Err(SceneError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn oversized_scenes_are_rejected() {
+        let json = r#"{ "version": 1, "width": 4000000000, "height": 4000000000 }"#;
+        assert!(matches!(
+            Scene::from_json(json),
+            Err(SceneError::TooLarge {
+                width: 4000000000,
+                height: 4000000000
+            })
+        ));
+
+        // Each side is within the limit but the area isn't
+        let scene = Scene::new(MAX_SCENE_SIZE, MAX_SCENE_SIZE);
+        assert!(matches!(
+            Scene::from_binary(&scene.to_binary()),
+            Err(SceneError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            scene.render(&SceneAssets::new()),
+            Err(SceneError::TooLarge { .. })
+        ));
+
+        assert!(Scene::from_binary(&Scene::new(MAX_SCENE_SIZE, 16).to_binary()).is_ok());
+    }
+
+    #[test]
+    fn extreme_geometry_is_rejected() {
+        let json = r##"{ "version": 1, "width": 8, "height": 8, "commands": [
+            { "type": "rect", "x": 0, "y": 0, "w": 2, "h": 2, "color": "#FFFFFF" },
+            { "type": "circle", "cx": 0, "cy": 0, "r": 2147483647, "color": "#FFFFFF" }
+        ] }"##;
+        assert!(matches!(
+            Scene::from_json(json),
+            Err(SceneError::OutOfRange(1))
+        ));
+
+        let extremes = [
+            SceneCommand::Rect {
+                x: i32::MAX,
+                y: 0,
+                w: 2,
+                h: 2,
+                color: 0,
+            },
+            SceneCommand::Line {
+                x1: 0,
+                y1: i32::MIN,
+                x2: 0,
+                y2: 0,
+                color: 0,
+            },
+            SceneCommand::Frame {
+                x: 0,
+                y: 0,
+                w: 2,
+                h: 2,
+                thiccness: usize::MAX,
+                color: 0,
+            },
+            SceneCommand::Text {
+                text: "a".into(),
+                x: 0,
+                y: 0,
+                size: usize::MAX,
+                color: 0,
+            },
+        ];
+
+        for command in extremes {
+            let mut scene = Scene::new(8, 8);
+            scene.commands.push(command);
+
+            assert!(matches!(
+                Scene::from_binary(&scene.to_binary()),
+                Err(SceneError::OutOfRange(0))
+            ));
+            assert!(matches!(
+                scene.render(&SceneAssets::new()),
+                Err(SceneError::OutOfRange(0))
+            ));
+        }
+
+        let mut scene = Scene::new(8, 8);
+        scene.commands.push(SceneCommand::Circle {
+            cx: -MAX_SCENE_COORDINATE,
+            cy: 0,
+            r: MAX_SCENE_COORDINATE,
+            color: 0,
+        });
+        assert!(Scene::from_binary(&scene.to_binary()).is_ok());
+    }
+
+    #[test]
+    fn malformed_binary_is_rejected() {
+        let data = every_command().to_binary();
+
+        assert!(matches!(
+            Scene::from_binary(b"OLVX"),
+            Err(SceneError::Binary(_))
+        ));
+
+        for len in 0..data.len() {
+            assert!(
+                matches!(Scene::from_binary(&data[..len]), Err(SceneError::Binary(_))),
+                "truncated to {len} bytes"
+            );
+        }
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert!(matches!(
+            Scene::from_binary(&trailing),
+            Err(SceneError::Binary(_))
+        ));
+
+        let mut unknown = Scene::new(4, 4).to_binary();
+        let count = unknown.len() - 4;
+        unknown[count] = 1;
+        unknown.push(11);
+        assert!(matches!(
+            Scene::from_binary(&unknown),
+            Err(SceneError::Binary(message)) if message.contains("11")
+        ));
+    }
+
+    #[test]
+    fn render_resolves_assets() {
+        let mut scene = Scene::new(8, 8);
+        scene.background = Some(0xFF000000);
+        scene.commands.push(SceneCommand::SpriteCopy {
+            x: 2,
+            y: 2,
+            w: 2,
+            h: 2,
+            asset: "red".into(),
+            bilinear: false,
+        });
+
+        assert!(matches!(
+            scene.render(&SceneAssets::new()),
+            Err(SceneError::MissingAsset(id)) if id == "red"
+        ));
+
+        let mut red = Canvas::new(1, 1);
+        red.fill(0xFF0000FF);
+
+        let mut assets = SceneAssets::new();
+        assets.insert("red", red);
+
+        let canvas = scene.render(&assets).unwrap();
+        assert_eq!(canvas.pixels()[2 * 8 + 2], 0xFF0000FF);
+        assert_eq!(canvas.pixels()[0], 0xFF000000);
+        assert_eq!(scene.asset_ids(), ["red"]);
+    }
+}
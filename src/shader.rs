@@ -0,0 +1,151 @@
+use crate::{Vec2, Vec3, Vec4};
+
+/// Values that can be interpolated across a triangle with barycentric weights
+///
+/// Implemented for floats, vectors and tuples of them, as well as `()` for shaders
+/// that don't need any varyings
+pub trait Varying: Copy {
+    fn interpolate(a: Self, b: Self, c: Self, weights: [f32; 3]) -> Self;
+}
+
+macro_rules! impl_varying {
+    ($($type:ty),+) => {
+        $(impl Varying for $type {
+            #[inline]
+            fn interpolate(a: Self, b: Self, c: Self, [l1, l2, l3]: [f32; 3]) -> Self {
+                a * l1 + b * l2 + c * l3
+            }
+        })+
+    };
+}
+
+impl_varying!(f32, Vec2, Vec3, Vec4);
+
+impl Varying for () {
+    #[inline]
+    fn interpolate(_: Self, _: Self, _: Self, _: [f32; 3]) -> Self {}
+}
+
+macro_rules! impl_varying_tuple {
+    ($($name:ident: $index:tt),+) => {
+        impl<$($name: Varying),+> Varying for ($($name,)+) {
+            #[inline]
+            fn interpolate(a: Self, b: Self, c: Self, weights: [f32; 3]) -> Self {
+                ($($name::interpolate(a.$index, b.$index, c.$index, weights),)+)
+            }
+        }
+    };
+}
+
+impl_varying_tuple!(A: 0, B: 1);
+impl_varying_tuple!(A: 0, B: 1, C: 2);
+impl_varying_tuple!(A: 0, B: 1, C: 2, D: 3);
+
+/// A single pixel covered by a triangle, passed to the shader of
+/// [`Drawable::triangle_shaded`](crate::Drawable::triangle_shaded)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fragment<V> {
+    /// Horizontal position of the pixel in the canvas
+    pub x: i32,
+    /// Vertical position of the pixel in the canvas
+    pub y: i32,
+    /// Weights of each of the three vertices at this pixel, which sum up to 1
+    pub barycentric: [f32; 3],
+    /// The vertex varyings interpolated with the barycentric weights
+    pub varying: V,
+}
+
+/// A single pixel covered by a rectangle, passed to the shader of
+/// [`Drawable::rect_shaded`](crate::Drawable::rect_shaded)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RectFragment {
+    /// Horizontal position of the pixel in the canvas
+    pub x: i32,
+    /// Vertical position of the pixel in the canvas
+    pub y: i32,
+    /// Position of the pixel center within the rectangle, where `(0, 0)` is the top
+    /// left corner and `(1, 1)` the bottom right corner
+    pub uv: Vec2,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Canvas, Drawable};
+
+    #[test]
+    fn tuples_interpolate_every_component() {
+        let weights = [0.5, 0.25, 0.25];
+        let (a, b) = Varying::interpolate(
+            (0.0, Vec2::ZERO),
+            (4.0, Vec2::new(4.0, 0.0)),
+            (8.0, Vec2::new(0.0, 8.0)),
+            weights,
+        );
+
+        assert_eq!(a, 3.0);
+        assert_eq!(b, Vec2::new(1.0, 2.0));
+    }
+
+    #[test]
+    fn triangle_shaded_hands_the_vertex_varying_at_each_corner() {
+        let mut canvas = Canvas::new(11, 11);
+        canvas.fill(0xFF000000);
+        let mut corners = Vec::new();
+
+        canvas.triangle_shaded((0, 0, 1.0), (10, 0, 2.0), (0, 10, 3.0), |fragment| {
+            if matches!((fragment.x, fragment.y), (0, 0) | (10, 0) | (0, 10)) {
+                corners.push(fragment.varying);
+            }
+
+            let sum: f32 = fragment.barycentric.iter().sum();
+            assert!((sum - 1.0).abs() < 1e-5);
+
+            0xFFFFFFFF
+        });
+
+        corners.sort_by(f32::total_cmp);
+        assert_eq!(corners, vec![1.0, 2.0, 3.0]);
+        assert_eq!(canvas.get_pixel(2, 2), Some(0xFFFFFFFF));
+        assert_eq!(canvas.get_pixel(8, 8), Some(0xFF000000));
+    }
+
+    #[test]
+    fn shaded_colors_are_blended() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill(0xFF0000FF);
+
+        canvas.rect_shaded(0, 0, 4, 4, |fragment| {
+            if fragment.x < 2 {
+                0x00FFFFFF
+            } else {
+                0xFF00FF00
+            }
+        });
+
+        assert_eq!(canvas.get_pixel(0, 0), Some(0xFF0000FF));
+        assert_eq!(canvas.get_pixel(3, 3), Some(0xFF00FF00));
+    }
+
+    #[test]
+    fn rect_shaded_maps_pixel_centers_into_the_unit_square() {
+        let mut canvas = Canvas::new(10, 10);
+        let mut uvs = Vec::new();
+
+        canvas.rect_shaded(-2, 0, 4, 2, |fragment| {
+            uvs.push((fragment.x, fragment.y, fragment.uv));
+            0
+        });
+
+        // The left half of the rectangle is clipped, but keeps its coordinates
+        assert_eq!(
+            uvs,
+            vec![
+                (0, 0, Vec2::new(0.625, 0.25)),
+                (1, 0, Vec2::new(0.875, 0.25)),
+                (0, 1, Vec2::new(0.625, 0.75)),
+                (1, 1, Vec2::new(0.875, 0.75)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,530 @@
+use crate::{draw_chars, Drawable, Font, Rect};
+
+/// Horizontal alignment of the lines of a [`TextLayout`] within its box
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Stretches the spaces of wrapped lines to fill the box, the last line of each
+    /// paragraph stays aligned to the left
+    Justify,
+}
+
+/// Vertical alignment of the lines of a [`TextLayout`] within its box
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Returns the width of a single line of text, including the kerning of the font
+pub(crate) fn text_width(text: &str, font: &(impl Font + ?Sized), size: usize) -> i32 {
+    let mut width = 0;
+    let mut previous = None;
+
+    for c in text.chars() {
+        if let Some(previous) = previous {
+            width += font.kerning(previous, c, size);
+        }
+
+        width += font.advance(c, size);
+        previous = Some(c);
+    }
+
+    width
+}
+
+/// Returns the width and height that [`Drawable::text`] would cover
+///
+/// The text is measured as a single line, the same way it is drawn, so a `\n` counts
+/// as any other character. Use [`TextLayout`] for text spanning several lines
+///
+/// ```
+/// use olivec::{ measure_text, FixedFont };
+///
+/// let font = FixedFont::<6, 6>::default();
+///
+/// assert_eq!(measure_text("Hello, World!", &font, 2), (156, 12));
+/// ```
+pub fn measure_text(text: &str, font: &(impl Font + ?Sized), size: usize) -> (i32, i32) {
+    (text_width(text, font, size), font.line_height(size))
+}
+
+/// A line of text positioned by [`TextLayout::layout`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextLine {
+    pub text: String,
+    /// Position of the top left corner of the line on the canvas
+    pub x: i32,
+    pub y: i32,
+    /// Width of the line without the extra spacing of justified lines
+    pub width: i32,
+    /// Pixels to distribute between the words of a justified line
+    pub extra_spacing: i32,
+}
+
+impl TextLine {
+    /// Draws the line, spreading its extra spacing between the words
+    pub fn draw(
+        &self,
+        canvas: &mut impl Drawable,
+        font: &(impl Font + ?Sized),
+        size: usize,
+        color: u32,
+    ) {
+        let gaps = self.text.matches(' ').count() as i32;
+
+        if self.extra_spacing == 0 || gaps == 0 {
+            draw_chars(canvas, self.text.chars(), self.x, self.y, font, size, color);
+            return;
+        }
+
+        let space = font.advance(' ', size);
+        let mut pen = self.x;
+
+        for (i, word) in self.text.split(' ').enumerate() {
+            let i = i as i32;
+
+            if i > 0 {
+                // Spread the remainder evenly instead of piling it on the last gap
+                pen += space + self.extra_spacing * i / gaps - self.extra_spacing * (i - 1) / gaps;
+            }
+
+            pen = draw_chars(canvas, word.chars(), pen, self.y, font, size, color);
+        }
+    }
+}
+
+/// Lines of text positioned within a box by [`TextLayout::layout`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextBlock {
+    pub lines: Vec<TextLine>,
+    /// Width of the widest line
+    pub width: i32,
+    /// Distance from the top of the first line to the bottom of the last one
+    pub height: i32,
+    /// Whether lines or characters had to be left out to fit the box
+    pub truncated: bool,
+}
+
+impl TextBlock {
+    pub fn draw(
+        &self,
+        canvas: &mut impl Drawable,
+        font: &(impl Font + ?Sized),
+        size: usize,
+        color: u32,
+    ) {
+        for line in &self.lines {
+            line.draw(canvas, font, size, color);
+        }
+    }
+}
+
+/// Options to break text into lines and align them within a box
+///
+/// Every `\n` starts a new paragraph, and paragraphs wider than the box are wrapped
+/// between words, or between characters for words that don't fit on a line by
+/// themselves. Lines that don't fit in the height of the box are left out, with the
+/// ellipsis appended to the last visible one if there is one
+///
+/// ```
+/// use olivec::{ Canvas, FixedFont, Rect, TextAlign, TextLayout, VerticalAlign };
+///
+/// let mut canvas = Canvas::new(200, 100);
+/// let font = FixedFont::<6, 6>::default();
+///
+/// let layout = TextLayout::new()
+///     .with_align(TextAlign::Center)
+///     .with_vertical_align(VerticalAlign::Middle)
+///     .with_line_spacing(1.5)
+///     .with_ellipsis("...");
+///
+/// let cell = Rect::new(10, 10, 120, 40);
+/// let block = layout.layout("The quick brown fox jumps over the lazy dog", &font, 2, cell);
+///
+/// assert!(block.width <= cell.w);
+/// block.draw(&mut canvas, &font, 2, 0xFFFFFFFF);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayout {
+    pub align: TextAlign,
+    pub vertical_align: VerticalAlign,
+    /// Whether paragraphs wider than the box are wrapped, otherwise they are cut and
+    /// ellipsized
+    pub wrap: bool,
+    /// Distance between the tops of consecutive lines, relative to the line height
+    /// of the font
+    pub line_spacing: f32,
+    pub max_lines: Option<usize>,
+    /// Appended to lines cut to fit the box
+    pub ellipsis: Option<String>,
+}
+
+impl Default for TextLayout {
+    fn default() -> Self {
+        Self {
+            align: TextAlign::default(),
+            vertical_align: VerticalAlign::default(),
+            wrap: true,
+            line_spacing: 1.0,
+            max_lines: None,
+            ellipsis: None,
+        }
+    }
+}
+
+impl TextLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_vertical_align(mut self, vertical_align: VerticalAlign) -> Self {
+        self.vertical_align = vertical_align;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    pub fn with_ellipsis(mut self, ellipsis: impl Into<String>) -> Self {
+        self.ellipsis = Some(ellipsis.into());
+        self
+    }
+
+    /// Returns the distance between the tops of consecutive lines
+    pub fn line_step(&self, font: &(impl Font + ?Sized), size: usize) -> i32 {
+        (font.line_height(size) as f32 * self.line_spacing).round() as i32
+    }
+
+    /// Breaks the text into lines and positions them within `bounds`
+    pub fn layout(
+        &self,
+        text: &str,
+        font: &(impl Font + ?Sized),
+        size: usize,
+        bounds: Rect,
+    ) -> TextBlock {
+        let max_width = bounds.w.max(0);
+        let line_height = font.line_height(size);
+        let step = self.line_step(font, size).max(1);
+
+        // Each line along with whether it ends its paragraph
+        let mut lines: Vec<(String, bool)> = Vec::new();
+        let mut truncated = false;
+
+        for paragraph in text.split('\n') {
+            let paragraph = paragraph.strip_suffix('\r').unwrap_or(paragraph);
+
+            if self.wrap {
+                let wrapped = wrap_paragraph(paragraph, font, size, max_width);
+                let count = wrapped.len();
+
+                lines.extend(
+                    wrapped
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, l)| (l, i + 1 == count)),
+                );
+            } else if text_width(paragraph, font, size) > max_width {
+                let ellipsis = self.ellipsis.as_deref().unwrap_or("");
+                lines.push((ellipsize(paragraph, ellipsis, font, size, max_width), true));
+                truncated = true;
+            } else {
+                lines.push((paragraph.to_string(), true));
+            }
+        }
+
+        // Keep the lines whose bottom fits in the box, but always at least one
+        let fitting = ((bounds.h - line_height).max(0) / step + 1) as usize;
+        let visible = self.max_lines.unwrap_or(usize::MAX).min(fitting).max(1);
+
+        if lines.len() > visible {
+            lines.truncate(visible);
+            truncated = true;
+
+            if let (Some(ellipsis), Some((last, ends_paragraph))) =
+                (&self.ellipsis, lines.last_mut())
+            {
+                let cut = format!("{}{ellipsis}", last.trim_end());
+                *last = ellipsize(&cut, ellipsis, font, size, max_width);
+                *ends_paragraph = true;
+            }
+        }
+
+        let height = (lines.len() as i32 - 1) * step + line_height;
+        let top = match self.vertical_align {
+            VerticalAlign::Top => bounds.y,
+            VerticalAlign::Middle => bounds.y + (bounds.h - height) / 2,
+            VerticalAlign::Bottom => bounds.y + bounds.h - height,
+        };
+
+        let lines: Vec<TextLine> = lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, (text, ends_paragraph))| {
+                let width = text_width(&text, font, size);
+                let free = max_width - width;
+
+                let (x, extra_spacing) = match self.align {
+                    TextAlign::Left => (bounds.x, 0),
+                    TextAlign::Center => (bounds.x + free / 2, 0),
+                    TextAlign::Right => (bounds.x + free, 0),
+                    TextAlign::Justify if !ends_paragraph && text.contains(' ') => {
+                        (bounds.x, free.max(0))
+                    }
+                    TextAlign::Justify => (bounds.x, 0),
+                };
+
+                TextLine {
+                    text,
+                    x,
+                    y: top + i as i32 * step,
+                    width,
+                    extra_spacing,
+                }
+            })
+            .collect();
+
+        TextBlock {
+            width: lines.iter().map(|line| line.width).max().unwrap_or(0),
+            height,
+            lines,
+            truncated,
+        }
+    }
+
+    /// Lays the text out within `bounds` and draws it
+    pub fn draw(
+        &self,
+        canvas: &mut impl Drawable,
+        text: &str,
+        font: &(impl Font + ?Sized),
+        size: usize,
+        bounds: Rect,
+        color: u32,
+    ) -> TextBlock {
+        let block = self.layout(text, font, size, bounds);
+        block.draw(canvas, font, size, color);
+        block
+    }
+}
+
+/// Breaks a paragraph into lines no wider than `max_width`, between words when
+/// possible
+fn wrap_paragraph(
+    paragraph: &str,
+    font: &(impl Font + ?Sized),
+    size: usize,
+    max_width: i32,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in paragraph.split(' ') {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{line} {word}")
+        };
+
+        if text_width(&candidate, font, size) <= max_width {
+            line = candidate;
+            continue;
+        }
+
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        // Break words that don't fit on a line by themselves between characters
+        let mut rest = word;
+
+        while text_width(rest, font, size) > max_width {
+            let split = rest
+                .char_indices()
+                .skip(1)
+                .map(|(i, _)| i)
+                .take_while(|&i| text_width(&rest[..i], font, size) <= max_width)
+                .last()
+                .unwrap_or_else(|| rest.chars().next().map_or(0, char::len_utf8));
+
+            if split == 0 || split == rest.len() {
+                break;
+            }
+
+            lines.push(rest[..split].to_string());
+            rest = &rest[split..];
+        }
+
+        line = rest.to_string();
+    }
+
+    lines.push(line);
+    lines
+}
+
+/// Cuts the end of a line and appends the ellipsis so that it fits in `max_width`
+fn ellipsize(
+    line: &str,
+    ellipsis: &str,
+    font: &(impl Font + ?Sized),
+    size: usize,
+    max_width: i32,
+) -> String {
+    if text_width(line, font, size) <= max_width {
+        return line.to_string();
+    }
+
+    let mut ends: Vec<usize> = line.char_indices().map(|(i, _)| i).collect();
+    ends.reverse();
+
+    ends.into_iter()
+        .map(|end| format!("{}{ellipsis}", line[..end].trim_end()))
+        .find(|candidate| text_width(candidate, font, size) <= max_width)
+        .unwrap_or_else(|| ellipsis.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Canvas, FixedFont};
+
+    /// Every character is 6 pixels wide and tall
+    fn font() -> FixedFont<6, 6> {
+        FixedFont::<6, 6>::default()
+    }
+
+    fn texts(block: &TextBlock) -> Vec<&str> {
+        block.lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn measure_text_measures_a_single_line() {
+        assert_eq!(measure_text("abc", &font(), 2), (36, 12));
+        assert_eq!(measure_text("a\nb", &font(), 1), (18, 6));
+        assert_eq!(measure_text("", &font(), 1), (0, 6));
+    }
+
+    #[test]
+    fn paragraphs_wrap_between_words() {
+        let block =
+            TextLayout::new().layout("aa bb cc\ndd\r\n", &font(), 1, Rect::new(0, 0, 30, 100));
+
+        assert_eq!(texts(&block), ["aa bb", "cc", "dd", ""]);
+        assert_eq!((block.width, block.height), (30, 24));
+        assert!(!block.truncated);
+    }
+
+    #[test]
+    fn long_words_break_between_characters() {
+        let block = TextLayout::new().layout("x abcdefgh", &font(), 1, Rect::new(0, 0, 18, 100));
+
+        assert_eq!(texts(&block), ["x", "abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn unwrapped_lines_are_ellipsized() {
+        let layout = TextLayout::new().with_wrap(false).with_ellipsis("..");
+        let block = layout.layout("abcdefgh\nab", &font(), 1, Rect::new(0, 0, 30, 100));
+
+        assert_eq!(texts(&block), ["abc..", "ab"]);
+        assert!(block.truncated);
+    }
+
+    #[test]
+    fn lines_past_the_box_are_left_out_with_an_ellipsis() {
+        let layout = TextLayout::new().with_ellipsis(".");
+        let bounds = Rect::new(0, 0, 30, 13);
+
+        let block = layout.layout("aa bb cc dd ee ff", &font(), 1, bounds);
+        assert_eq!(texts(&block), ["aa bb", "cc d."]);
+        assert!(block.truncated);
+
+        let block = layout
+            .with_max_lines(1)
+            .layout("aa bb cc", &font(), 1, bounds);
+        assert_eq!(texts(&block), ["aa b."]);
+    }
+
+    #[test]
+    fn at_least_one_line_is_kept() {
+        let block = TextLayout::new().layout("aa bb", &font(), 1, Rect::new(0, 0, 12, 0));
+
+        assert_eq!(texts(&block), ["aa"]);
+        assert!(block.truncated);
+    }
+
+    #[test]
+    fn lines_are_aligned_within_the_box() {
+        let bounds = Rect::new(10, 20, 60, 40);
+        let position = |align, vertical_align| {
+            let layout = TextLayout::new()
+                .with_align(align)
+                .with_vertical_align(vertical_align)
+                .with_line_spacing(2.0);
+            let block = layout.layout("ab\nc", &font(), 1, bounds);
+
+            block
+                .lines
+                .iter()
+                .map(|line| (line.x, line.y))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            position(TextAlign::Left, VerticalAlign::Top),
+            [(10, 20), (10, 32)]
+        );
+        assert_eq!(
+            position(TextAlign::Center, VerticalAlign::Middle),
+            [(34, 31), (37, 43)]
+        );
+        assert_eq!(
+            position(TextAlign::Right, VerticalAlign::Bottom),
+            [(58, 42), (64, 54)]
+        );
+    }
+
+    #[test]
+    fn justified_lines_spread_their_spacing() {
+        let layout = TextLayout::new().with_align(TextAlign::Justify);
+        let block = layout.layout("a b c d", &font(), 1, Rect::new(0, 0, 35, 100));
+
+        let spacing: Vec<i32> = block.lines.iter().map(|l| l.extra_spacing).collect();
+        assert_eq!(texts(&block), ["a b c", "d"]);
+        assert_eq!(spacing, [5, 0]);
+
+        let mut justified = Canvas::new(40, 6);
+        block.lines[0].draw(&mut justified, &font(), 1, 0xFFFFFFFF);
+
+        // The 5 pixels are split as 2 and 3 between the two gaps
+        let mut expected = Canvas::new(40, 6);
+        for (text, x) in [("a", 0), ("b", 14), ("c", 29)] {
+            expected.text(text, x, 0, &font(), 1, 0xFFFFFFFF);
+        }
+
+        assert_eq!(justified.pixels(), expected.pixels());
+    }
+}
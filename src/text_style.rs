@@ -0,0 +1,446 @@
+use crate::{alpha, draw_chars, Drawable, Font, Glyph, Rect};
+
+/// The direction in which consecutive characters are laid out
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextDirection {
+    #[default]
+    Horizontal,
+    /// Upright characters stacked from top to bottom, centered in a column as wide
+    /// as the widest advance
+    Vertical,
+}
+
+/// Options for [`Drawable::text_styled`]
+///
+/// ```
+/// use olivec::{ Canvas, Drawable, TextDirection, TextStyle, FONT_8X8 };
+/// use std::f32::consts::FRAC_PI_2;
+///
+/// let mut canvas = Canvas::new(200, 200);
+///
+/// // An axis label reading from bottom to top
+/// let axis = TextStyle::new().with_rotation(-FRAC_PI_2);
+/// canvas.text_styled("Temperature", 10, 150, &FONT_8X8, 1, 0xFFFFFFFF, &axis);
+///
+/// // A map label with a dark halo
+/// let label = TextStyle::new().with_outline(0xFF000000, 2).with_bold(true);
+/// canvas.text_styled("Paris", 60, 20, &FONT_8X8, 2, 0xFFFFFFFF, &label);
+///
+/// let column = TextStyle::new().with_direction(TextDirection::Vertical);
+/// canvas.text_styled("TOP", 180, 10, &FONT_8X8, 1, 0xFF00FFFF, &column);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    /// Clockwise rotation in radians around the top left corner of the text
+    pub rotation: f32,
+    pub direction: TextDirection,
+    /// Width in pixels of the halo drawn around the glyphs, 0 for none
+    pub outline_width: usize,
+    pub outline_color: u32,
+    /// Emulates a bold weight by widening every glyph by one of its pixels
+    pub bold: bool,
+}
+
+impl TextStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_direction(mut self, direction: TextDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn with_outline(mut self, color: u32, width: usize) -> Self {
+        self.outline_color = color;
+        self.outline_width = width;
+        self
+    }
+
+    pub fn with_bold(mut self, bold: bool) -> Self {
+        self.bold = bold;
+        self
+    }
+
+    /// Returns true if the style draws the same as [`Drawable::text`]
+    fn is_plain(&self) -> bool {
+        self.rotation == 0.0
+            && self.direction == TextDirection::Horizontal
+            && self.outline_width == 0
+            && !self.bold
+    }
+}
+
+/// Coverage of the text drawn upright, before being rotated onto the canvas
+struct Mask {
+    width: usize,
+    height: usize,
+    coverage: Vec<u8>,
+}
+
+impl Mask {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            coverage: vec![0; width * height],
+        }
+    }
+
+    fn get(&self, x: i32, y: i32) -> u8 {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return 0;
+        }
+
+        self.coverage[y as usize * self.width + x as usize]
+    }
+
+    /// Combines the coverage of the glyph pen at `(x, y)` with the existing one
+    fn add_glyph(&mut self, glyph: &Glyph, x: i32, y: i32) {
+        let scale = glyph.scale.max(1) as i32;
+
+        for (i, &coverage) in glyph.coverage.iter().enumerate() {
+            let gx = x + glyph.left + (i % glyph.width) as i32 * scale;
+            let gy = y + glyph.top + (i / glyph.width) as i32 * scale;
+
+            for py in gy..gy + scale {
+                for px in gx..gx + scale {
+                    let index = py as usize * self.width + px as usize;
+                    self.coverage[index] = self.coverage[index].max(coverage);
+                }
+            }
+        }
+    }
+
+    /// Returns the mask with every pixel spreading its coverage over the `by` pixels
+    /// to its right
+    fn widen(&self, by: i32) -> Mask {
+        self.map(|x, y| (0..=by).map(|dx| self.get(x - dx, y)).max().unwrap_or(0))
+    }
+
+    /// Returns the mask with every pixel spreading its coverage over a disk
+    fn dilate(&self, radius: i32) -> Mask {
+        self.map(|x, y| {
+            let mut coverage = 0;
+
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    if dx * dx + dy * dy <= radius * radius {
+                        coverage = coverage.max(self.get(x + dx, y + dy));
+                    }
+                }
+            }
+
+            coverage
+        })
+    }
+
+    fn map(&self, f: impl Fn(i32, i32) -> u8) -> Mask {
+        let mut mask = Mask::new(self.width, self.height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                mask.coverage[y * self.width + x] = f(x as i32, y as i32);
+            }
+        }
+
+        mask
+    }
+
+    /// Returns the bilinearly interpolated coverage at a position where pixel centers
+    /// lie on integer coordinates
+    fn sample(&self, x: f32, y: f32) -> u32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+
+        let top = self.get(x0, y0) as f32 * (1.0 - fx) + self.get(x0 + 1, y0) as f32 * fx;
+        let bottom =
+            self.get(x0, y0 + 1) as f32 * (1.0 - fx) + self.get(x0 + 1, y0 + 1) as f32 * fx;
+
+        (top * (1.0 - fy) + bottom * fy).round() as u32
+    }
+}
+
+/// Rounds values that are within floating point error of -1, 0 or 1, so that
+/// rotations by right angles map pixels exactly
+fn snap(value: f32) -> f32 {
+    if value.abs() < 1e-6 {
+        0.0
+    } else if (value.abs() - 1.0).abs() < 1e-6 {
+        value.signum()
+    } else {
+        value
+    }
+}
+
+/// Implementation of [`Drawable::text_styled`]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn draw_styled_text(
+    canvas: &mut impl Drawable,
+    text: &str,
+    x: i32,
+    y: i32,
+    font: &(impl Font + ?Sized),
+    size: usize,
+    color: u32,
+    style: &TextStyle,
+) {
+    if style.is_plain() {
+        draw_chars(canvas, text.chars(), x, y, font, size, color);
+        return;
+    }
+
+    let line_height = font.line_height(size);
+
+    // Pen position of every glyph relative to the top left corner of the text
+    let mut glyphs: Vec<(i32, i32, Glyph)> = Vec::new();
+    let mut extent = Rect::new(0, 0, 0, line_height);
+
+    match style.direction {
+        TextDirection::Horizontal => {
+            let mut pen = 0;
+            let mut previous = None;
+
+            for c in text.chars() {
+                if let Some(previous) = previous {
+                    pen += font.kerning(previous, c, size);
+                }
+
+                glyphs.extend(font.glyph(c, size).map(|glyph| (pen, 0, glyph)));
+                pen += font.advance(c, size);
+                previous = Some(c);
+            }
+
+            extent.w = pen;
+        }
+        TextDirection::Vertical => {
+            let column = text
+                .chars()
+                .map(|c| font.advance(c, size))
+                .max()
+                .unwrap_or(0);
+
+            for (i, c) in text.chars().enumerate() {
+                let pen = (column - font.advance(c, size)) / 2;
+                let top = i as i32 * line_height;
+
+                glyphs.extend(font.glyph(c, size).map(|glyph| (pen, top, glyph)));
+            }
+
+            extent.w = column;
+            extent.h = line_height * text.chars().count() as i32;
+        }
+    }
+
+    for (pen, top, glyph) in &glyphs {
+        let scale = glyph.scale.max(1);
+        let (w, h) = ((glyph.width * scale) as i32, (glyph.height * scale) as i32);
+
+        extent = extent.union(&Rect::new(pen + glyph.left, top + glyph.top, w, h));
+    }
+
+    let bold = if style.bold {
+        glyphs
+            .iter()
+            .map(|(_, _, g)| g.scale.max(1))
+            .max()
+            .unwrap_or(1) as i32
+    } else {
+        0
+    };
+    let outline = style.outline_width as i32;
+    let pad = outline + bold;
+
+    // Position of the top left corner of the text in the mask
+    let origin = (pad - extent.x, pad - extent.y);
+    let mut mask = Mask::new(
+        (extent.w + 2 * pad).max(0) as usize,
+        (extent.h + 2 * pad).max(0) as usize,
+    );
+
+    for (pen, top, glyph) in &glyphs {
+        mask.add_glyph(glyph, origin.0 + pen, origin.1 + top);
+    }
+
+    if bold > 0 {
+        mask = mask.widen(bold);
+    }
+
+    let halo = (outline > 0).then(|| mask.dilate(outline));
+
+    // Bounds of the rotated mask on the canvas
+    let (sin, cos) = style.rotation.sin_cos();
+    let (sin, cos) = (snap(sin), snap(cos));
+
+    let corners = [
+        (0, 0),
+        (mask.width, 0),
+        (0, mask.height),
+        (mask.width, mask.height),
+    ]
+    .map(|(cx, cy)| {
+        let (dx, dy) = (cx as f32 - origin.0 as f32, cy as f32 - origin.1 as f32);
+        (
+            x as f32 + dx * cos - dy * sin,
+            y as f32 + dx * sin + dy * cos,
+        )
+    });
+
+    let (xs, ys) = (corners.map(|c| c.0), corners.map(|c| c.1));
+
+    let min_x = (xs.iter().copied().fold(f32::INFINITY, f32::min).floor() as i32).max(0);
+    let min_y = (ys.iter().copied().fold(f32::INFINITY, f32::min).floor() as i32).max(0);
+    let max_x = (xs.iter().copied().fold(f32::NEG_INFINITY, f32::max).ceil() as i32)
+        .min(canvas.get_width() as i32);
+    let max_y = (ys.iter().copied().fold(f32::NEG_INFINITY, f32::max).ceil() as i32)
+        .min(canvas.get_height() as i32);
+
+    let (rgb, color_alpha) = (color & 0x00FFFFFF, alpha!(color) as u32);
+    let outline_rgb = style.outline_color & 0x00FFFFFF;
+    let outline_alpha = alpha!(style.outline_color) as u32;
+
+    for py in min_y..max_y {
+        for px in min_x..max_x {
+            // Map the pixel center back into the mask
+            let (dx, dy) = (px as f32 + 0.5 - x as f32, py as f32 + 0.5 - y as f32);
+            let mx = dx * cos + dy * sin + origin.0 as f32 - 0.5;
+            let my = -dx * sin + dy * cos + origin.1 as f32 - 0.5;
+
+            if let Some(halo) = &halo {
+                let coverage = halo.sample(mx, my);
+
+                if coverage > 0 {
+                    let alpha = coverage * outline_alpha / 255;
+                    canvas.blend_pixel(px, py, outline_rgb | alpha << 24);
+                }
+            }
+
+            let coverage = mask.sample(mx, my);
+
+            if coverage > 0 {
+                canvas.blend_pixel(px, py, rgb | (coverage * color_alpha / 255) << 24);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Canvas, FixedFont};
+    use std::f32::consts::FRAC_PI_2;
+
+    const W: u32 = 0xFFFFFFFF;
+    const B: u32 = 0xFF000000;
+    const RED: u32 = 0xFF0000FF;
+
+    fn canvas() -> Canvas {
+        let mut canvas = Canvas::new(32, 32);
+        canvas.fill(B);
+        canvas
+    }
+
+    fn pixel(canvas: &Canvas, x: usize, y: usize) -> u32 {
+        canvas.pixels()[y * canvas.get_width() + x]
+    }
+
+    /// A font whose `.` is a single pixel in the middle of its cell
+    fn dot() -> FixedFont<3, 3> {
+        let mut rows = [[0; 3]; 128];
+        rows['.' as usize] = [0b000, 0b010, 0b000];
+        FixedFont::from_bitmasks(rows)
+    }
+
+    #[test]
+    fn plain_style_draws_the_same_as_text() {
+        let font = FixedFont::<6, 6>::default();
+
+        let mut styled = canvas();
+        styled.text_styled("ab", 3, 4, &font, 2, W, &TextStyle::new());
+
+        let mut expected = canvas();
+        expected.text("ab", 3, 4, &font, 2, W);
+
+        assert_eq!(styled.pixels(), expected.pixels());
+    }
+
+    #[test]
+    fn right_angle_rotations_map_pixels_exactly() {
+        let font = FixedFont::<6, 6>::default();
+        let style = TextStyle::new().with_rotation(FRAC_PI_2);
+
+        let mut upright = canvas();
+        upright.text("ab", 0, 0, &font, 1, W);
+
+        let mut rotated = canvas();
+        rotated.text_styled("ab", 20, 2, &font, 1, W, &style);
+
+        // Clockwise around the top left corner of the text
+        for y in 0..32 {
+            for x in 0..32 {
+                let (ux, uy) = (y as i32 - 2, 20 - 1 - x as i32);
+                let expected = if (0..32).contains(&ux) && (0..32).contains(&uy) {
+                    pixel(&upright, ux as usize, uy as usize)
+                } else {
+                    B
+                };
+
+                assert_eq!(pixel(&rotated, x, y), expected, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn vertical_text_stacks_characters() {
+        let font = FixedFont::<6, 6>::default();
+        let style = TextStyle::new().with_direction(TextDirection::Vertical);
+
+        let mut styled = canvas();
+        styled.text_styled("ab", 4, 2, &font, 2, W, &style);
+
+        let mut expected = canvas();
+        expected.text("a", 4, 2, &font, 2, W);
+        expected.text("b", 4, 14, &font, 2, W);
+
+        assert_eq!(styled.pixels(), expected.pixels());
+    }
+
+    #[test]
+    fn bold_widens_glyphs_by_a_pixel() {
+        let font = FixedFont::<6, 6>::default();
+        let style = TextStyle::new().with_bold(true);
+
+        let mut styled = canvas();
+        styled.text_styled("ab", 4, 2, &font, 1, W, &style);
+
+        let mut expected = canvas();
+        expected.text("ab", 4, 2, &font, 1, W);
+        expected.text("ab", 5, 2, &font, 1, W);
+
+        assert_eq!(styled.pixels(), expected.pixels());
+    }
+
+    #[test]
+    fn outline_surrounds_the_glyphs() {
+        let style = TextStyle::new().with_outline(RED, 1);
+
+        let mut styled = canvas();
+        styled.text_styled(".", 2, 2, &dot(), 1, W, &style);
+
+        assert_eq!(pixel(&styled, 3, 3), W);
+
+        for (x, y) in [(2, 3), (4, 3), (3, 2), (3, 4)] {
+            assert_eq!(pixel(&styled, x, y), RED, "({x}, {y})");
+        }
+
+        for (x, y) in [(2, 2), (4, 4), (5, 3), (1, 3)] {
+            assert_eq!(pixel(&styled, x, y), B, "({x}, {y})");
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use crate::{
+    barycentric, lerp_color, normalize_triangle, Drawable, Pipeline, ScreenVertex, Vec2, Vertex,
+};
+
+/// How texture coordinates outside of the `[0, 1]` range are mapped onto the texture
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// The texture tiles infinitely
+    #[default]
+    Repeat,
+    /// Coordinates stick to the nearest edge of the texture
+    Clamp,
+    /// The texture tiles, flipping on every repetition so the edges line up
+    Mirror,
+}
+
+impl WrapMode {
+    /// Maps a texel index onto the range `0..size`
+    pub fn wrap(&self, index: i32, size: i32) -> i32 {
+        match self {
+            WrapMode::Repeat => index.rem_euclid(size),
+            WrapMode::Clamp => index.clamp(0, size - 1),
+            WrapMode::Mirror => {
+                let index = index.rem_euclid(2 * size);
+
+                if index >= size {
+                    2 * size - 1 - index
+                } else {
+                    index
+                }
+            }
+        }
+    }
+}
+
+/// How the texture is read between texel centers
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Uses the closest texel
+    Nearest,
+    /// Blends the four surrounding texels
+    #[default]
+    Bilinear,
+}
+
+/// How texture coordinates are interpolated across a triangle
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UvInterpolation {
+    /// Linear in screen space, which warps textures on triangles that are slanted
+    /// away from the camera
+    Affine,
+    /// Interpolates `uv / w` and `1 / w` and divides them at every pixel
+    #[default]
+    PerspectiveCorrect,
+}
+
+/// Describes how a texture is read
+///
+/// ```
+/// use olivec::{Canvas, FilterMode, Sampler, Vec2, WrapMode};
+///
+/// let texture = Canvas::new(16, 16);
+/// let sampler = Sampler::new(WrapMode::Mirror, FilterMode::Nearest);
+///
+/// let texel = sampler.sample(&texture, Vec2::new(1.25, -0.5));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sampler {
+    pub wrap_u: WrapMode,
+    pub wrap_v: WrapMode,
+    pub filter: FilterMode,
+    pub interpolation: UvInterpolation,
+}
+
+impl Sampler {
+    /// Creates a perspective correct sampler with the same wrap mode on both axes
+    pub fn new(wrap: WrapMode, filter: FilterMode) -> Self {
+        Self {
+            wrap_u: wrap,
+            wrap_v: wrap,
+            filter,
+            interpolation: UvInterpolation::PerspectiveCorrect,
+        }
+    }
+
+    pub fn with_interpolation(mut self, interpolation: UvInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Reads the texture at the provided normalized coordinates, where `(0, 0)` is the
+    /// top left corner and `(1, 1)` the bottom right corner
+    ///
+    /// Returns 0 for empty textures
+    pub fn sample(&self, texture: &impl Drawable, uv: Vec2) -> u32 {
+        let (width, height) = (texture.get_width() as i32, texture.get_height() as i32);
+
+        if width == 0 || height == 0 {
+            return 0;
+        }
+
+        let texel = |x: i32, y: i32| {
+            texture
+                .get_pixel(self.wrap_u.wrap(x, width), self.wrap_v.wrap(y, height))
+                .unwrap_or(0)
+        };
+
+        let x = uv.x * width as f32;
+        let y = uv.y * height as f32;
+
+        match self.filter {
+            FilterMode::Nearest => texel(x.floor() as i32, y.floor() as i32),
+            FilterMode::Bilinear => {
+                // Texel centers sit at half coordinates
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i32, y0 as i32);
+
+                let top = lerp_color(texel(x0, y0), texel(x0 + 1, y0), tx);
+                let bottom = lerp_color(texel(x0, y0 + 1), texel(x0 + 1, y0 + 1), tx);
+
+                lerp_color(top, bottom, ty)
+            }
+        }
+    }
+}
+
+/// Rasterizes a screen space triangle, blending the texture sampled at every pixel
+/// onto the canvas
+///
+/// Unlike `triangle3uv`, the texture coordinates don't have to be pre-divided. With
+/// [`UvInterpolation::PerspectiveCorrect`] the `inv_w` of each vertex is used to undo
+/// the perspective distortion, so it must be set (to 1 for flat 2D triangles)
+pub fn textured_triangle(
+    canvas: &mut impl Drawable,
+    vertices: &[ScreenVertex; 3],
+    texture: &impl Drawable,
+    sampler: &Sampler,
+) {
+    let [a, b, c] = vertices;
+    let ((x1, y1), (x2, y2), (x3, y3)) = (a.pixel(), b.pixel(), c.pixel());
+
+    let (mut lx, mut hx, mut ly, mut hy) = (0, 0, 0, 0);
+
+    if !normalize_triangle(
+        canvas.get_width(),
+        canvas.get_height(),
+        x1,
+        y1,
+        x2,
+        y2,
+        x3,
+        y3,
+        &mut lx,
+        &mut hx,
+        &mut ly,
+        &mut hy,
+    ) {
+        return;
+    }
+
+    for y in ly..=hy {
+        for x in lx..=hx {
+            let (mut u1, mut u2, mut det) = (0, 0, 0);
+
+            if !barycentric(x1, y1, x2, y2, x3, y3, x, y, &mut u1, &mut u2, &mut det) || det == 0 {
+                continue;
+            }
+
+            let l1 = u1 as f32 / det as f32;
+            let l2 = u2 as f32 / det as f32;
+            let l3 = 1.0 - l1 - l2;
+
+            let uv = match sampler.interpolation {
+                UvInterpolation::Affine => a.uv * l1 + b.uv * l2 + c.uv * l3,
+                UvInterpolation::PerspectiveCorrect => {
+                    let (w1, w2, w3) = (l1 * a.inv_w, l2 * b.inv_w, l3 * c.inv_w);
+
+                    (a.uv * w1 + b.uv * w2 + c.uv * w3) / (w1 + w2 + w3)
+                }
+            };
+
+            canvas.blend_pixel(x, y, sampler.sample(texture, uv));
+        }
+    }
+}
+
+impl Pipeline {
+    /// Draws a textured triangle with the texture read through the provided sampler
+    ///
+    /// This rasterizes in Rust instead of olive.c, which allows texture coordinates to
+    /// extend outside of `[0, 1]` for tiled floors and walls
+    pub fn draw_triangle_sampled(
+        &self,
+        canvas: &mut impl Drawable,
+        triangle: &[Vertex; 3],
+        texture: &impl Drawable,
+        sampler: &Sampler,
+    ) {
+        let (width, height) = (canvas.get_width(), canvas.get_height());
+
+        for screen in self.project(width, height, triangle) {
+            textured_triangle(canvas, &screen, texture, sampler);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Canvas;
+
+    /// A 2x2 texture with a different color in every corner
+    fn checker() -> Canvas {
+        let mut texture = Canvas::new(2, 2);
+
+        texture.set_pixel(0, 0, 0xFF000000);
+        texture.set_pixel(1, 0, 0xFF0000FF);
+        texture.set_pixel(0, 1, 0xFF00FF00);
+        texture.set_pixel(1, 1, 0xFFFF0000);
+
+        texture
+    }
+
+    fn vertex(x: f32, y: f32, inv_w: f32, uv: Vec2) -> ScreenVertex {
+        ScreenVertex {
+            x,
+            y,
+            depth: 0.0,
+            inv_w,
+            color: 0xFFFFFFFF,
+            uv,
+        }
+    }
+
+    #[test]
+    fn wrap_modes_map_indices_into_the_texture() {
+        let indices = [-3, -1, 0, 3, 4, 7];
+        let wrap = |mode: WrapMode| indices.map(|i| mode.wrap(i, 4));
+
+        assert_eq!(wrap(WrapMode::Repeat), [1, 3, 0, 3, 0, 3]);
+        assert_eq!(wrap(WrapMode::Clamp), [0, 0, 0, 3, 3, 3]);
+        assert_eq!(wrap(WrapMode::Mirror), [2, 0, 0, 3, 3, 0]);
+    }
+
+    #[test]
+    fn nearest_sampling_reads_the_containing_texel() {
+        let texture = checker();
+        let sampler = Sampler::new(WrapMode::Repeat, FilterMode::Nearest);
+
+        assert_eq!(sampler.sample(&texture, Vec2::new(0.75, 0.25)), 0xFF0000FF);
+        assert_eq!(sampler.sample(&texture, Vec2::new(1.75, 1.25)), 0xFF0000FF);
+        assert_eq!(sampler.sample(&texture, Vec2::new(-0.25, 0.75)), 0xFFFF0000);
+        assert_eq!(sampler.sample(&Canvas::new(0, 0), Vec2::ZERO), 0);
+    }
+
+    #[test]
+    fn bilinear_sampling_blends_between_texel_centers() {
+        let mut texture = Canvas::new(2, 1);
+        texture.set_pixel(0, 0, 0xFF000000);
+        texture.set_pixel(1, 0, 0xFFFEFEFE);
+
+        let sampler = Sampler::new(WrapMode::Clamp, FilterMode::Bilinear);
+
+        assert_eq!(sampler.sample(&texture, Vec2::new(0.25, 0.5)), 0xFF000000);
+        assert_eq!(sampler.sample(&texture, Vec2::new(0.5, 0.5)), 0xFF7F7F7F);
+        assert_eq!(sampler.sample(&texture, Vec2::new(0.75, 0.5)), 0xFFFEFEFE);
+    }
+
+    #[test]
+    fn perspective_correct_interpolation_uses_the_depth_of_the_vertices() {
+        let mut texture = Canvas::new(4, 1);
+        for x in 0..4 {
+            texture.set_pixel(x, 0, 0xFF000000 | x as u32);
+        }
+
+        // The right edge is three times as far away as the left one, so the middle of
+        // the screen shows the first quarter of the texture
+        let (near, far) = (1.0, 1.0 / 3.0);
+        let triangle = [
+            vertex(0.0, 0.0, near, Vec2::new(0.0, 0.0)),
+            vertex(0.0, 8.0, near, Vec2::new(0.0, 1.0)),
+            vertex(16.0, 0.0, far, Vec2::new(1.0, 0.0)),
+        ];
+
+        let draw = |interpolation| {
+            let mut canvas = Canvas::new(16, 16);
+            canvas.fill(0xFF000000);
+            let sampler = Sampler::new(WrapMode::Clamp, FilterMode::Nearest)
+                .with_interpolation(interpolation);
+
+            textured_triangle(&mut canvas, &triangle, &texture, &sampler);
+            canvas.get_pixel(8, 0)
+        };
+
+        assert_eq!(draw(UvInterpolation::Affine), Some(0xFF000002));
+        assert_eq!(draw(UvInterpolation::PerspectiveCorrect), Some(0xFF000001));
+    }
+
+    #[test]
+    fn transparent_texels_are_blended_onto_the_canvas() {
+        let mut texture = Canvas::new(2, 1);
+        texture.set_pixel(0, 0, 0x00FFFFFF);
+        texture.set_pixel(1, 0, 0x80FFFFFF);
+
+        let triangle = [
+            vertex(0.0, 0.0, 1.0, Vec2::new(0.0, 0.0)),
+            vertex(0.0, 16.0, 1.0, Vec2::new(0.0, 1.0)),
+            vertex(16.0, 0.0, 1.0, Vec2::new(1.0, 0.0)),
+        ];
+
+        let mut canvas = Canvas::new(16, 16);
+        canvas.fill(0xFF0000FF);
+        let sampler = Sampler::new(WrapMode::Clamp, FilterMode::Nearest);
+        textured_triangle(&mut canvas, &triangle, &texture, &sampler);
+
+        assert_eq!(canvas.get_pixel(2, 2), Some(0xFF0000FF));
+        assert_eq!(canvas.get_pixel(10, 2), Some(0xFF8080FF));
+    }
+}
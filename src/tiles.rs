@@ -0,0 +1,216 @@
+use crate::{Canvas, DrawCommand, Drawable, Rect, SubCanvas};
+
+/// A tile of the target canvas along with its position
+struct Tile<'a> {
+    rect: Rect,
+    canvas: SubCanvas<'a>,
+}
+
+// Safety: The tiles of a single render never overlap, so each thread gets exclusive
+// access to the pixels behind its subcanvas
+unsafe impl Send for Tile<'_> {}
+
+impl Tile<'_> {
+    fn replay(&mut self, commands: &[DrawCommand], sprites: &[Canvas]) {
+        for command in commands {
+            command
+                .translated(-self.rect.x, -self.rect.y)
+                .execute(&mut self.canvas, sprites);
+        }
+    }
+}
+
+/// Renders a list of draw commands by splitting the target canvas into tiles and
+/// replaying every command into each of them concurrently
+///
+/// olive.c clips all of its primitives against the canvas and rasterizes them
+/// relative to integer coordinates, so drawing a translated command into a tile
+/// produces exactly the same pixels as the sequential render. Tiles are processed
+/// with rayon when the `rayon` feature is enabled, and with one scoped thread per
+/// available core otherwise
+///
+/// ```
+/// use olivec::{Canvas, DrawCommand, TileRenderer};
+///
+/// let mut canvas = Canvas::new(1024, 1024);
+///
+/// let commands = [
+///     DrawCommand::Fill { color: 0xFF181818 },
+///     DrawCommand::Circle { cx: 512, cy: 512, r: 300, color: 0xFF2020FF },
+/// ];
+///
+/// TileRenderer::new(256, 256).render(&mut canvas, &commands, &[]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRenderer {
+    pub tile_width: usize,
+    pub tile_height: usize,
+}
+
+impl Default for TileRenderer {
+    fn default() -> Self {
+        Self::new(128, 128)
+    }
+}
+
+impl TileRenderer {
+    pub fn new(tile_width: usize, tile_height: usize) -> Self {
+        Self {
+            tile_width: tile_width.max(1),
+            tile_height: tile_height.max(1),
+        }
+    }
+
+    /// Splits a canvas of the given size into disjoint tiles, covering it row by row
+    ///
+    /// Tiles on the right and bottom edges are cut short to fit the canvas
+    pub fn tiles(&self, width: usize, height: usize) -> Vec<Rect> {
+        (0..height)
+            .step_by(self.tile_height)
+            .flat_map(|y| {
+                (0..width).step_by(self.tile_width).map(move |x| {
+                    Rect::new(
+                        x as i32,
+                        y as i32,
+                        self.tile_width.min(width - x) as i32,
+                        self.tile_height.min(height - y) as i32,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Replays the commands onto the canvas, with sprite and texture indices
+    /// referring to `sprites`
+    pub fn render(&self, canvas: &mut impl Drawable, commands: &[DrawCommand], sprites: &[Canvas]) {
+        let mut tiles: Vec<Tile> = self
+            .tiles(canvas.get_width(), canvas.get_height())
+            .into_iter()
+            .filter_map(|rect| {
+                let canvas = canvas.subcanvas(rect.x, rect.y, rect.w, rect.h)?;
+
+                Some(Tile { rect, canvas })
+            })
+            .collect();
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+
+            tiles
+                .par_iter_mut()
+                .for_each(|tile| tile.replay(commands, sprites));
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        {
+            let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+            let chunk_size = tiles.len().div_ceil(threads).max(1);
+
+            std::thread::scope(|scope| {
+                for chunk in tiles.chunks_mut(chunk_size) {
+                    scope.spawn(move || {
+                        for tile in chunk {
+                            tile.replay(commands, sprites);
+                        }
+                    });
+                }
+            });
+        }
+
+        // The tiles draw through subcanvases, which don't record any damage
+        let full = Rect::new(0, 0, canvas.get_width() as i32, canvas.get_height() as i32);
+
+        for command in commands {
+            canvas.mark_damage(command.bounds().unwrap_or(full));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_cover_the_canvas_without_overlapping() {
+        let tiles = TileRenderer::new(4, 3).tiles(10, 7);
+
+        assert_eq!(tiles.len(), 9);
+        assert_eq!(tiles[2], Rect::new(8, 0, 2, 3));
+        assert_eq!(tiles[8], Rect::new(8, 6, 2, 1));
+
+        let area: i32 = tiles.iter().map(|t| t.w * t.h).sum();
+        assert_eq!(area, 70);
+
+        for (i, a) in tiles.iter().enumerate() {
+            for b in &tiles[i + 1..] {
+                assert!(a.intersection(b).is_none(), "{a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn render_matches_drawing_sequentially() {
+        let commands = [
+            DrawCommand::Fill { color: 0xFF181818 },
+            DrawCommand::Rect {
+                x: 5,
+                y: 5,
+                w: 40,
+                h: 30,
+                color: 0x802020FF,
+            },
+            DrawCommand::Circle {
+                cx: 32,
+                cy: 32,
+                r: 20,
+                color: 0xFF20FF20,
+            },
+            DrawCommand::Line {
+                x1: 0,
+                y1: 63,
+                x2: 63,
+                y2: 3,
+                color: 0xFFFFFFFF,
+            },
+            DrawCommand::Triangle3c {
+                x1: 60,
+                y1: 2,
+                x2: 10,
+                y2: 50,
+                x3: 55,
+                y3: 60,
+                c1: 0xFF0000FF,
+                c2: 0xFF00FF00,
+                c3: 0xFFFF0000,
+            },
+            DrawCommand::Frame {
+                x: 8,
+                y: 8,
+                w: 48,
+                h: 48,
+                thiccness: 3,
+                color: 0xFF00FFFF,
+            },
+            DrawCommand::Text {
+                text: "tiles".into(),
+                x: 3,
+                y: 12,
+                size: 2,
+                color: 0xFFFF00FF,
+            },
+        ];
+
+        let mut expected = Canvas::new(64, 64);
+        for command in &commands {
+            command.execute(&mut expected, &[]);
+        }
+
+        for size in [1, 7, 16, 100] {
+            let mut canvas = Canvas::new(64, 64);
+            TileRenderer::new(size, size).render(&mut canvas, &commands, &[]);
+
+            assert!(canvas.pixels() == expected.pixels(), "tiles of {size}");
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use std::{borrow::Cow, collections::HashMap, path::Path, sync::Mutex};
+
+use fontdue::{FontSettings, Metrics};
+
+use crate::{alpha, Drawable, Font, FontError, Glyph};
+
+const DEFAULT_MAX_CACHED_GLYPHS: usize = 1024;
+
+/// The vertical position of a line of text that the `y` coordinate refers to
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextBaseline {
+    /// The top of the tallest glyphs of the font
+    Top,
+    /// Halfway between the top and the bottom
+    Middle,
+    /// The line that most glyphs sit on
+    #[default]
+    Alphabetic,
+    /// The bottom of the lowest descenders of the font
+    Bottom,
+}
+
+/// A glyph rasterized at a specific size
+#[derive(Clone)]
+struct RasterGlyph {
+    metrics: Metrics,
+    coverage: Vec<u8>,
+}
+
+/// A TrueType or OpenType font, with its glyphs rasterized with anti-aliasing
+///
+/// Implements [`Font`] with the size being the height in pixels, so it can be drawn
+/// with [`Drawable::text`] too
+///
+/// Glyphs are cached per character and pixel size the first time they are drawn,
+/// so drawing the same text again only blends the cached coverage onto the canvas.
+/// Once the cache holds [`TrueTypeFont::with_max_cached_glyphs`] glyphs, further
+/// glyphs are rasterized every time they are drawn until it is cleared
+///
+/// ```no_run
+/// use olivec::{Canvas, TextBaseline, TrueTypeFont};
+///
+/// let font = TrueTypeFont::load("assets/Roboto-Regular.ttf").unwrap();
+/// let mut canvas = Canvas::new(400, 100);
+///
+/// let y = font.baseline_offset(32.0, TextBaseline::Top);
+/// font.draw(&mut canvas, "Hello, World!", 10, 10 + y, 32.0, 0xFFFFFFFF);
+/// ```
+pub struct TrueTypeFont {
+    font: fontdue::Font,
+    cache: Mutex<HashMap<(char, u32), Box<RasterGlyph>>>,
+    max_cached_glyphs: usize,
+}
+
+impl TrueTypeFont {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, FontError> {
+        let font = fontdue::Font::from_bytes(data, FontSettings::default())
+            .map_err(|message| FontError::Parse(message.into()))?;
+
+        Ok(Self {
+            font,
+            cache: Mutex::default(),
+            max_cached_glyphs: DEFAULT_MAX_CACHED_GLYPHS,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Sets how many rasterized glyphs are kept in the cache, 1024 by default
+    pub fn with_max_cached_glyphs(mut self, max: usize) -> Self {
+        self.max_cached_glyphs = max;
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.font.name()
+    }
+
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.font.has_glyph(c)
+    }
+
+    /// Returns the distance from the alphabetic baseline to the top of the font at
+    /// the given size, or 0 if the font has no horizontal metrics
+    pub fn ascent(&self, px: f32) -> i32 {
+        self.font
+            .horizontal_line_metrics(px)
+            .map_or(0, |m| m.ascent.round() as i32)
+    }
+
+    /// Returns the distance from the alphabetic baseline to the bottom of the font
+    /// at the given size, which is usually negative
+    pub fn descent(&self, px: f32) -> i32 {
+        self.font
+            .horizontal_line_metrics(px)
+            .map_or(0, |m| m.descent.round() as i32)
+    }
+
+    /// Returns the distance between the baselines of two consecutive lines
+    pub fn line_height(&self, px: f32) -> i32 {
+        self.font
+            .horizontal_line_metrics(px)
+            .map_or(px, |m| m.new_line_size)
+            .round() as i32
+    }
+
+    /// Returns the offset to add to a `y` coordinate referring to `baseline` to get
+    /// the alphabetic baseline that [`TrueTypeFont::draw`] expects
+    pub fn baseline_offset(&self, px: f32, baseline: TextBaseline) -> i32 {
+        match baseline {
+            TextBaseline::Top => self.ascent(px),
+            TextBaseline::Middle => (self.ascent(px) + self.descent(px)) / 2,
+            TextBaseline::Alphabetic => 0,
+            TextBaseline::Bottom => self.descent(px),
+        }
+    }
+
+    /// Returns the kerning adjustment between two consecutive characters
+    pub fn kerning(&self, left: char, right: char, px: f32) -> f32 {
+        self.font.horizontal_kern(left, right, px).unwrap_or(0.0)
+    }
+
+    fn glyph(&self, c: char, px: f32) -> Cow<'_, RasterGlyph> {
+        let mut cache = self.cache.lock().unwrap();
+        let key = (c, px.to_bits());
+
+        if !cache.contains_key(&key) {
+            let (metrics, coverage) = self.font.rasterize(c, px);
+            let glyph = RasterGlyph { metrics, coverage };
+
+            if cache.len() >= self.max_cached_glyphs {
+                return Cow::Owned(glyph);
+            }
+
+            cache.insert(key, Box::new(glyph));
+        }
+
+        let glyph = &cache[&key];
+
+        // Safety: Every glyph has its own allocation, which doesn't move when the
+        // cache grows, and glyphs are only removed by `clear_cache` which borrows the
+        // font mutably, so the glyph lives as long as the borrow of the font
+        Cow::Borrowed(unsafe { &*(glyph.as_ref() as *const RasterGlyph) })
+    }
+
+    /// Removes every rasterized glyph from the cache
+    pub fn clear_cache(&mut self) {
+        self.cache.get_mut().unwrap().clear();
+    }
+
+    /// Calls `f` with the pen position and glyph of every character of the text,
+    /// applying the advances and kerning of the font
+    fn layout(&self, text: &str, px: f32, mut f: impl FnMut(f32, &RasterGlyph)) -> f32 {
+        let mut pen = 0.0;
+        let mut previous = None;
+
+        for c in text.chars() {
+            if let Some(previous) = previous {
+                pen += self.kerning(previous, c, px);
+            }
+
+            let glyph = self.glyph(c, px);
+            f(pen, &glyph);
+
+            pen += glyph.metrics.advance_width;
+            previous = Some(c);
+        }
+
+        pen
+    }
+
+    /// Returns the width and height of a line of text, where the height spans from
+    /// the ascent to the descent of the font
+    pub fn measure(&self, text: &str, px: f32) -> (i32, i32) {
+        let width = self.layout(text, px, |_, _| {});
+
+        (width.ceil() as i32, self.ascent(px) - self.descent(px))
+    }
+
+    /// Draws a single line of text starting at `x`, with `y` being its alphabetic
+    /// baseline
+    ///
+    /// Use [`TrueTypeFont::baseline_offset`] to position the text by another
+    /// baseline. The coverage of the glyphs is blended onto the canvas, multiplied
+    /// by the alpha of the color
+    pub fn draw(
+        &self,
+        canvas: &mut impl Drawable,
+        text: &str,
+        x: i32,
+        y: i32,
+        px: f32,
+        color: u32,
+    ) {
+        let rgb = color & 0x00FFFFFF;
+        let color_alpha = alpha!(color) as u32;
+
+        self.layout(text, px, |pen, glyph| {
+            let Metrics {
+                xmin,
+                ymin,
+                width,
+                height,
+                ..
+            } = glyph.metrics;
+
+            let gx = x + (pen.round() as i32) + xmin;
+            let gy = y - ymin - height as i32;
+
+            for (i, &coverage) in glyph.coverage.iter().enumerate() {
+                if coverage == 0 {
+                    continue;
+                }
+
+                let alpha = coverage as u32 * color_alpha / 255;
+                let (dx, dy) = ((i % width) as i32, (i / width) as i32);
+
+                canvas.blend_pixel(gx + dx, gy + dy, rgb | alpha << 24);
+            }
+        });
+    }
+}
+
+impl Font for TrueTypeFont {
+    fn glyph(&self, c: char, size: usize) -> Option<Glyph<'_>> {
+        let px = size as f32;
+        let glyph = TrueTypeFont::glyph(self, c, px);
+        let Metrics {
+            xmin,
+            ymin,
+            width,
+            height,
+            advance_width,
+            ..
+        } = glyph.metrics;
+
+        Some(Glyph {
+            width,
+            height,
+            left: xmin,
+            top: TrueTypeFont::ascent(self, px) - ymin - height as i32,
+            advance: advance_width.round() as i32,
+            scale: 1,
+            coverage: match glyph {
+                Cow::Borrowed(glyph) => Cow::Borrowed(&glyph.coverage),
+                Cow::Owned(glyph) => Cow::Owned(glyph.coverage),
+            },
+        })
+    }
+
+    fn line_height(&self, size: usize) -> i32 {
+        TrueTypeFont::line_height(self, size as f32)
+    }
+
+    fn ascent(&self, size: usize) -> i32 {
+        TrueTypeFont::ascent(self, size as f32)
+    }
+
+    fn advance(&self, c: char, size: usize) -> i32 {
+        let glyph = TrueTypeFont::glyph(self, c, size as f32);
+
+        glyph.metrics.advance_width.round() as i32
+    }
+
+    fn kerning(&self, left: char, right: char, size: usize) -> i32 {
+        TrueTypeFont::kerning(self, left, right, size as f32).round() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Canvas;
+
+    /// Loads the font at the path in `OLIVEC_TEST_FONT`, as no font ships with the
+    /// crate. Tests needing one pass trivially when it isn't set
+    fn test_font() -> Option<TrueTypeFont> {
+        let path = std::env::var_os("OLIVEC_TEST_FONT")?;
+        Some(TrueTypeFont::load(path).expect("OLIVEC_TEST_FONT should be a font file"))
+    }
+
+    fn covered_rows(canvas: &Canvas) -> Vec<usize> {
+        let width = canvas.get_width();
+
+        (0..canvas.get_height())
+            .filter(|y| {
+                canvas.pixels()[y * width..(y + 1) * width]
+                    .iter()
+                    .any(|&p| p != 0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn invalid_data_is_a_parse_error() {
+        assert!(matches!(
+            TrueTypeFont::from_bytes(b"not a font"),
+            Err(FontError::Parse(_))
+        ));
+        assert!(matches!(
+            TrueTypeFont::load("/nonexistent/font.ttf"),
+            Err(FontError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn baselines_are_ordered() {
+        let Some(font) = test_font() else { return };
+
+        let top = font.baseline_offset(32.0, TextBaseline::Top);
+        let middle = font.baseline_offset(32.0, TextBaseline::Middle);
+        let bottom = font.baseline_offset(32.0, TextBaseline::Bottom);
+
+        assert!(top > middle && middle > 0 && bottom < 0);
+        assert_eq!(font.baseline_offset(32.0, TextBaseline::Alphabetic), 0);
+        assert_eq!(font.measure("", 32.0), (0, top - bottom));
+    }
+
+    #[test]
+    fn glyphs_sit_on_the_baseline() {
+        let Some(font) = test_font() else { return };
+        let mut canvas = Canvas::new(80, 60);
+
+        font.draw(&mut canvas, "H", 10, 40, 32.0, 0xFFFFFFFF);
+
+        let rows = covered_rows(&canvas);
+        assert_eq!(rows.last(), Some(&39));
+        assert!(rows[0] as i32 >= 40 - font.ascent(32.0));
+    }
+
+    #[test]
+    fn measure_matches_the_advances() {
+        let Some(font) = test_font() else { return };
+
+        let (width, _) = font.measure("AV", 24.0);
+        let advances = Font::advance(&font, 'A', 24)
+            + Font::advance(&font, 'V', 24)
+            + Font::kerning(&font, 'A', 'V', 24);
+
+        assert!((width - advances).abs() <= 2);
+    }
+
+    #[test]
+    fn drawing_through_the_font_trait_matches_draw() {
+        let Some(font) = test_font() else { return };
+        let (mut direct, mut text) = (Canvas::new(120, 40), Canvas::new(120, 40));
+
+        font.draw(
+            &mut direct,
+            "Hi",
+            5,
+            5 + font.ascent(20.0),
+            20.0,
+            0xFFFFFFFF,
+        );
+        text.text("Hi", 5, 5, &font, 20, 0xFFFFFFFF);
+
+        assert_eq!(direct.pixels(), text.pixels());
+    }
+
+    #[test]
+    fn glyphs_are_cached_until_cleared() {
+        let Some(mut font) = test_font() else { return };
+
+        font.measure("abca", 16.0);
+        assert_eq!(font.cache.lock().unwrap().len(), 3);
+
+        font.clear_cache();
+        assert!(font.cache.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn the_cache_stops_growing_once_full() {
+        let Some(font) = test_font() else { return };
+        let font = font.with_max_cached_glyphs(2);
+
+        let cached = font.measure("ab", 16.0);
+        assert_eq!(font.measure("abcd", 17.0), font.measure("abcd", 17.0));
+        assert_eq!(font.cache.lock().unwrap().len(), 2);
+        assert_eq!(font.measure("ab", 16.0), cached);
+
+        let glyph = Font::glyph(&font, 'g', 16).unwrap();
+        assert!(matches!(glyph.coverage, Cow::Owned(_)));
+        assert_eq!(glyph.coverage.len(), glyph.width * glyph.height);
+    }
+
+    #[test]
+    fn glyphs_borrow_the_cached_coverage() {
+        let Some(font) = test_font() else { return };
+        let glyph = Font::glyph(&font, 'g', 16).unwrap();
+
+        assert!(matches!(glyph.coverage, Cow::Borrowed(_)));
+        assert_eq!(glyph.coverage.len(), glyph.width * glyph.height);
+    }
+}
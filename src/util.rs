@@ -57,10 +57,10 @@ pub struct NormalizedRect {
     pub x2: i32,
     pub y1: i32,
     pub y2: i32,
-    ox1: i32,
-    ox2: i32,
-    oy1: i32,
-    oy2: i32,
+    pub(crate) ox1: i32,
+    pub(crate) ox2: i32,
+    pub(crate) oy1: i32,
+    pub(crate) oy2: i32,
 }
 
 impl From<Olivec_Normalized_Rect> for NormalizedRect {
@@ -140,3 +140,172 @@ pub fn normalize_rect(
         .then_some(onr.into())
     }
 }
+
+/// An axis aligned rectangle with its top left corner at `(x, y)`
+///
+/// Unlike the arguments of the drawing functions, the width and height are never
+/// negative for a rectangle that isn't empty. Rectangles reaching past the range of
+/// `i32` are clamped to it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    /// Creates the rectangle spanning from `(x1, y1)` to `(x2, y2)` inclusive, in any order
+    pub fn from_corners(x1: i32, y1: i32, x2: i32, y2: i32) -> Self {
+        let (lx, hx) = (x1.min(x2), x1.max(x2));
+        let (ly, hy) = (y1.min(y2), y1.max(y2));
+
+        Self::new(
+            lx,
+            ly,
+            hx.saturating_sub(lx).saturating_add(1),
+            hy.saturating_sub(ly).saturating_add(1),
+        )
+    }
+
+    /// Creates a rectangle from the drawing function convention, where a negative
+    /// width or height extends the rectangle to the left or up
+    pub fn from_signed(x: i32, y: i32, w: i32, h: i32) -> Self {
+        if w == 0 || h == 0 {
+            return Self::new(x, y, 0, 0);
+        }
+
+        Self::from_corners(
+            x,
+            y,
+            x.saturating_add(w - w.signum()),
+            y.saturating_add(h - h.signum()),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.w <= 0 || self.h <= 0
+    }
+
+    /// Returns whether the point lies inside of the rectangle
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Returns the smallest rectangle containing both rectangles
+    ///
+    /// Empty rectangles are ignored
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+
+        if other.is_empty() {
+            return *self;
+        }
+
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+
+        Rect::new(
+            x,
+            y,
+            self.right().max(other.right()).saturating_sub(x),
+            self.bottom().max(other.bottom()).saturating_sub(y),
+        )
+    }
+
+    /// Returns the area covered by both rectangles, or `None` if they don't overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let w = self.right().min(other.right()).saturating_sub(x);
+        let h = self.bottom().min(other.bottom()).saturating_sub(y);
+
+        (w > 0 && h > 0).then_some(Rect::new(x, y, w, h))
+    }
+
+    /// Returns the rectangle moved by `(dx, dy)`
+    pub fn translate(&self, dx: i32, dy: i32) -> Rect {
+        Rect::new(
+            self.x.saturating_add(dx),
+            self.y.saturating_add(dy),
+            self.w,
+            self.h,
+        )
+    }
+
+    /// Returns the x coordinate just past the right edge
+    fn right(&self) -> i32 {
+        self.x.saturating_add(self.w)
+    }
+
+    /// Returns the y coordinate just past the bottom edge
+    fn bottom(&self) -> i32 {
+        self.y.saturating_add(self.h)
+    }
+}
+
+/// Returns the area covered by a frame, which olive.c centers on the edges of the
+/// rectangle
+pub(crate) fn frame_bounds(x: i32, y: i32, w: i32, h: i32, thiccness: usize) -> Rect {
+    let t = i32::try_from(thiccness / 2).unwrap_or(i32::MAX);
+    let rect = Rect::from_signed(x, y, w, h);
+
+    Rect::new(
+        rect.x.saturating_sub(t),
+        rect.y.saturating_sub(t),
+        rect.w.saturating_add(t).saturating_add(t),
+        rect.h.saturating_add(t).saturating_add(t),
+    )
+}
+
+/// Returns the smallest rectangle containing the three points of a triangle
+pub(crate) fn triangle_bounds(x1: i32, y1: i32, x2: i32, y2: i32, x3: i32, y3: i32) -> Rect {
+    Rect::from_corners(
+        x1.min(x2).min(x3),
+        y1.min(y2).min(y3),
+        x1.max(x2).max(x3),
+        y1.max(y2).max(y3),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rects_are_clamped_to_the_range_of_i32() {
+        let full = Rect::from_corners(i32::MIN, 0, i32::MAX, 0);
+        assert_eq!(full, Rect::new(i32::MIN, 0, i32::MAX, 1));
+
+        let right = Rect::from_signed(i32::MAX, 0, 2, 2);
+        assert_eq!(right, Rect::new(i32::MAX, 0, 1, 2));
+
+        let union = right.union(&Rect::new(i32::MIN, 0, 1, 1));
+        assert_eq!(union, Rect::new(i32::MIN, 0, i32::MAX, 2));
+
+        assert_eq!(
+            right.intersection(&Rect::new(0, 0, i32::MAX, i32::MAX)),
+            None
+        );
+        assert_eq!(
+            Rect::new(i32::MAX - 1, 0, 4, 4).intersection(&Rect::new(i32::MAX - 3, 1, 3, 4)),
+            Some(Rect::new(i32::MAX - 1, 1, 1, 3))
+        );
+        assert_eq!(
+            right.translate(4, i32::MIN),
+            Rect::new(i32::MAX, i32::MIN, 1, 2)
+        );
+
+        assert_eq!(
+            frame_bounds(0, 0, 4, 4, usize::MAX),
+            Rect::new(-i32::MAX, -i32::MAX, i32::MAX, i32::MAX)
+        );
+    }
+}